@@ -0,0 +1,114 @@
+//! Flat iteration against `Quadtree` for 10,000 objects, spread uniformly and in clusters.
+//! Run with `cargo bench --bench quadtree`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rust_2d_engine::aabb::AABB;
+use rust_2d_engine::quadtree::Quadtree;
+
+const OBJECT_COUNT: u32 = 10_000;
+const QUERY_COUNT: u32 = 1_000;
+const WORLD_SIZE: f64 = 10_000.0;
+const OBJECT_SIZE: f64 = 10.0;
+const VIEWPORT_SIZE: f64 = 500.0;
+const CLUSTER_COUNT: u32 = 8;
+// Same settings the engine's hit-test index uses.
+const MAX_OBJECTS: usize = 8;
+const MAX_DEPTH: u32 = 8;
+
+/// Linear congruential generator, uniform in `[0, 1)`; keeps runs comparable.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn square(x: f64, y: f64) -> AABB {
+    AABB::new(x, y, x + OBJECT_SIZE, y + OBJECT_SIZE)
+}
+
+fn uniform(rng: &mut Lcg) -> Vec<AABB> {
+    (0..OBJECT_COUNT).map(|_| square(rng.next() * WORLD_SIZE, rng.next() * WORLD_SIZE)).collect()
+}
+
+/// Objects bunched around a few centres, roughly normally with a 200 px spread.
+fn clustered(rng: &mut Lcg) -> Vec<AABB> {
+    let centres: Vec<(f64, f64)> = (0..CLUSTER_COUNT).map(|_| (rng.next() * WORLD_SIZE, rng.next() * WORLD_SIZE)).collect();
+    (0..OBJECT_COUNT)
+        .map(|i| {
+            let (cx, cy) = centres[(i % CLUSTER_COUNT) as usize];
+            let spread = |rng: &mut Lcg| (0..4).map(|_| rng.next() - 0.5).sum::<f64>() * 200.0;
+            square(cx + spread(rng), cy + spread(rng))
+        })
+        .collect()
+}
+
+/// Viewport-sized regions, half of them centred on objects so clusters get queried too.
+fn regions(objects: &[AABB], rng: &mut Lcg) -> Vec<AABB> {
+    (0..QUERY_COUNT)
+        .map(|i| {
+            let (x, y) = if i % 2 == 0 {
+                (rng.next() * WORLD_SIZE, rng.next() * WORLD_SIZE)
+            } else {
+                let object = &objects[(rng.next() * objects.len() as f64) as usize];
+                (object.min_x(), object.min_y())
+            };
+            AABB::new(x - VIEWPORT_SIZE / 2.0, y - VIEWPORT_SIZE / 2.0, x + VIEWPORT_SIZE / 2.0, y + VIEWPORT_SIZE / 2.0)
+        })
+        .collect()
+}
+
+fn time<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    let start = Instant::now();
+    let result = black_box(f());
+    (result, start.elapsed())
+}
+
+fn build(objects: &[AABB]) -> Quadtree {
+    let root = AABB::new(-WORLD_SIZE, -WORLD_SIZE, 2.0 * WORLD_SIZE, 2.0 * WORLD_SIZE);
+    let mut tree = Quadtree::new(root, MAX_OBJECTS, MAX_DEPTH);
+    for (id, bounds) in objects.iter().enumerate() {
+        tree.insert(id as u32, bounds);
+    }
+    tree
+}
+
+fn flat_query(objects: &[AABB], region: &AABB) -> usize {
+    objects.iter().filter(|bounds| bounds.intersects(region)).count()
+}
+
+fn flat_query_point(objects: &[AABB], x: f64, y: f64) -> usize {
+    objects.iter().filter(|bounds| bounds.contains_point(x, y)).count()
+}
+
+fn bench(name: &str, objects: &[AABB], rng: &mut Lcg) {
+    let regions = regions(objects, rng);
+    let points: Vec<(f64, f64)> = regions.iter().map(|r| (r.min_x() + VIEWPORT_SIZE / 2.0, r.min_y() + VIEWPORT_SIZE / 2.0)).collect();
+
+    let (tree, build_time) = time(|| build(objects));
+    let (flat_hits, flat_query_time) = time(|| regions.iter().map(|r| flat_query(objects, r)).sum::<usize>());
+    let (tree_hits, tree_query_time) = time(|| regions.iter().map(|r| tree.query(r).len()).sum::<usize>());
+    let (flat_point_hits, flat_point_time) =
+        time(|| points.iter().map(|&(x, y)| flat_query_point(objects, x, y)).sum::<usize>());
+    let (tree_point_hits, tree_point_time) = time(|| points.iter().map(|&(x, y)| tree.query_point(x, y).len()).sum::<usize>());
+
+    assert_eq!(flat_hits, tree_hits, "{}: region queries disagree", name);
+    assert_eq!(flat_point_hits, tree_point_hits, "{}: point queries disagree", name);
+
+    println!("{} ({} objects, {} queries, {} region hits)", name, OBJECT_COUNT, QUERY_COUNT, tree_hits);
+    println!("  quadtree build      {:>10.3?}", build_time);
+    println!("  region query  flat  {:>10.3?}  quadtree {:>10.3?}", flat_query_time, tree_query_time);
+    println!("  point query   flat  {:>10.3?}  quadtree {:>10.3?}", flat_point_time, tree_point_time);
+}
+
+fn main() {
+    let mut rng = Lcg(0x5eed);
+    let objects = uniform(&mut rng);
+    bench("uniform", &objects, &mut rng);
+    let objects = clustered(&mut rng);
+    bench("clustered", &objects, &mut rng);
+}
@@ -0,0 +1,13 @@
+// Loads keyframe chunks from IndexedDB off the main thread for `enable_fetch_worker`.
+const ready = import('./pkg');
+
+self.onmessage = async event => {
+  try {
+    const wasm = await ready;
+    self.postMessage(await wasm.handle_chunk_worker_message(event.data));
+  } catch (error) {
+    // Always answer, so the engine falls back instead of waiting forever.
+    const { request_id } = JSON.parse(event.data);
+    self.postMessage(JSON.stringify({ type: 'Error', request_id, message: String(error) }));
+  }
+};
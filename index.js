@@ -12,6 +12,9 @@ import('./pkg')
     fpsEl.style.display     = 'none';
 
     const engine = await new wasm.Rust2DEngine("canvas");
+    if (window.Worker) {
+      engine.enable_fetch_worker(new Worker(new URL('./chunk_worker.js', import.meta.url)));
+    }
 
     const totalObjects = 10;
     const size         = 100;
@@ -23,7 +26,12 @@ import('./pkg')
         await engine.generate_objects(
           totalObjects, 
           keyframesPer, 
-          size
+          size,
+          ({ created, total, percentage }) => {
+            loadingEl.textContent = created < total
+              ? `Creating objects: ${created} / ${total} (${percentage.toFixed(1)}%)`
+              : 'Preprocessing...';
+          }
         );
         
         const endTime = performance.now();
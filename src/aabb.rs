@@ -1,7 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct AABB {
+    #[serde(rename = "min_x")]
     min_x: f64,
+    #[serde(rename = "min_y")]
     min_y: f64,
+    #[serde(rename = "max_x")]
     max_x: f64,
+    #[serde(rename = "max_y")]
     max_y: f64,
 }
 
@@ -15,6 +22,23 @@ impl AABB {
         }
     }
     
+    /// Smallest box containing every point, or `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = (f64, f64)>) -> Option<AABB> {
+        points.into_iter().fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => AABB::new(x, y, x, y),
+                Some(b) => AABB::new(b.min_x.min(x), b.min_y.min(y), b.max_x.max(x), b.max_y.max(y)),
+            })
+        })
+    }
+
+    pub fn min_x(&self) -> f64 { self.min_x }
+    pub fn min_y(&self) -> f64 { self.min_y }
+    pub fn max_x(&self) -> f64 { self.max_x }
+    pub fn max_y(&self) -> f64 { self.max_y }
+    pub fn width(&self) -> f64 { self.max_x - self.min_x }
+    pub fn height(&self) -> f64 { self.max_y - self.min_y }
+
     pub fn contains_point(&self, x: f64, y: f64) -> bool {
         x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
     }
@@ -25,4 +49,167 @@ impl AABB {
           || self.max_y < other.min_y
           || self.min_y > other.max_y)
     }
-}
\ No newline at end of file
+
+    /// Slab test for the ray from (ox, oy) along (dx, dy). Returns the smallest `t >= 0` at
+    /// which `(ox + t * dx, oy + t * dy)` is in the box, so `t` is a distance when the
+    /// direction is a unit vector. A ray starting inside the box hits at 0.
+    pub fn intersect_ray(&self, ox: f64, oy: f64, dx: f64, dy: f64) -> Option<f64> {
+        let mut t_min: f64 = 0.0;
+        let mut t_max = f64::INFINITY;
+        for (origin, dir, min, max) in [(ox, dx, self.min_x, self.max_x), (oy, dy, self.min_y, self.max_y)] {
+            if dir == 0.0 {
+                // Parallel to this slab: either always within it or never.
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / dir;
+            let t2 = (max - origin) / dir;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+
+    /// Nearest point on the box's border: outside points are clamped onto the box, inside
+    /// points are pushed out to the closest edge.
+    pub fn closest_point_on_boundary(&self, x: f64, y: f64) -> (f64, f64) {
+        if !self.contains_point(x, y) {
+            return (x.clamp(self.min_x, self.max_x), y.clamp(self.min_y, self.max_y));
+        }
+        let to_left = x - self.min_x;
+        let to_right = self.max_x - x;
+        let to_top = y - self.min_y;
+        let to_bottom = self.max_y - y;
+        let nearest = to_left.min(to_right).min(to_top).min(to_bottom);
+        if nearest == to_left {
+            (self.min_x, y)
+        } else if nearest == to_right {
+            (self.max_x, y)
+        } else if nearest == to_top {
+            (x, self.min_y)
+        } else {
+            (x, self.max_y)
+        }
+    }
+
+    /// Distance from the point to the box's border, negative when the point is inside.
+    pub fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        let (bx, by) = self.closest_point_on_boundary(x, y);
+        let distance = (x - bx).hypot(y - by);
+        if self.contains_point(x, y) { -distance } else { distance }
+    }
+
+    /// Interpolate every edge from `self` (t = 0) to `other` (t = 1).
+    pub fn lerp(&self, other: &AABB, t: f64) -> AABB {
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+        AABB::new(
+            mix(self.min_x, other.min_x),
+            mix(self.min_y, other.min_y),
+            mix(self.max_x, other.max_x),
+            mix(self.max_y, other.max_y),
+        )
+    }
+
+    /// Scale about (anchor_x, anchor_y), keeping each corner's offset from the anchor
+    /// proportional to `factor`.
+    pub fn scale(&self, factor: f64, anchor_x: f64, anchor_y: f64) -> AABB {
+        AABB::new(
+            anchor_x + (self.min_x - anchor_x) * factor,
+            anchor_y + (self.min_y - anchor_y) * factor,
+            anchor_x + (self.max_x - anchor_x) * factor,
+            anchor_y + (self.max_y - anchor_y) * factor,
+        )
+    }
+
+    /// Convert from screen space to world space for a viewport whose top-left sits at
+    /// (viewport_offset_x, viewport_offset_y) in world space and is magnified by `zoom`.
+    pub fn to_world(&self, viewport_offset_x: f64, viewport_offset_y: f64, zoom: f64) -> AABB {
+        AABB::new(
+            self.min_x / zoom + viewport_offset_x,
+            self.min_y / zoom + viewport_offset_y,
+            self.max_x / zoom + viewport_offset_x,
+            self.max_y / zoom + viewport_offset_y,
+        )
+    }
+
+    /// Inverse of `to_world`.
+    pub fn from_world(&self, viewport_offset_x: f64, viewport_offset_y: f64, zoom: f64) -> AABB {
+        AABB::new(
+            (self.min_x - viewport_offset_x) * zoom,
+            (self.min_y - viewport_offset_y) * zoom,
+            (self.max_x - viewport_offset_x) * zoom,
+            (self.max_y - viewport_offset_y) * zoom,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_aabb_close(actual: &AABB, expected: &AABB) {
+        for (a, e) in [
+            (actual.min_x, expected.min_x),
+            (actual.min_y, expected.min_y),
+            (actual.max_x, expected.max_x),
+            (actual.max_y, expected.max_y),
+        ] {
+            assert!((a - e).abs() < EPSILON, "expected {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn scale_about_origin() {
+        let b = AABB::new(10.0, 20.0, 30.0, 40.0);
+        assert_eq!(b.scale(2.0, 0.0, 0.0), AABB::new(20.0, 40.0, 60.0, 80.0));
+    }
+
+    #[test]
+    fn scale_about_centre_keeps_centre() {
+        let b = AABB::new(0.0, 0.0, 10.0, 20.0);
+        assert_eq!(b.scale(0.5, 5.0, 10.0), AABB::new(2.5, 5.0, 7.5, 15.0));
+    }
+
+    #[test]
+    fn scale_by_one_is_identity() {
+        let b = AABB::new(-3.0, 4.0, 8.5, 9.0);
+        assert_eq!(b.scale(1.0, 100.0, -50.0), b);
+    }
+
+    #[test]
+    fn to_world_applies_zoom_then_offset() {
+        let screen = AABB::new(0.0, 0.0, 200.0, 100.0);
+        assert_eq!(screen.to_world(50.0, 25.0, 2.0), AABB::new(50.0, 25.0, 150.0, 75.0));
+    }
+
+    #[test]
+    fn to_world_then_from_world_is_identity() {
+        let screen = AABB::new(-12.5, 7.25, 640.0, 480.5);
+        for (offset_x, offset_y, zoom) in [(0.0, 0.0, 1.0), (100.0, -40.0, 2.0), (-3.5, 999.0, 0.3), (1e4, 1e4, 7.0)] {
+            let round_trip = screen.to_world(offset_x, offset_y, zoom).from_world(offset_x, offset_y, zoom);
+            assert_aabb_close(&round_trip, &screen);
+        }
+    }
+
+    #[test]
+    fn from_world_then_to_world_is_identity() {
+        let world = AABB::new(300.0, -20.0, 310.0, 45.0);
+        let round_trip = world.from_world(120.0, 60.0, 0.75).to_world(120.0, 60.0, 0.75);
+        assert_aabb_close(&round_trip, &world);
+    }
+
+    #[test]
+    fn serde_round_trip_uses_stable_field_names() {
+        let b = AABB::new(-1.5, 2.0, 30.25, 40.0);
+        let json = serde_json::to_value(&b).unwrap();
+        assert_eq!(json, serde_json::json!({ "min_x": -1.5, "min_y": 2.0, "max_x": 30.25, "max_y": 40.0 }));
+        assert_eq!(serde_json::from_value::<AABB>(json).unwrap(), b);
+    }
+}
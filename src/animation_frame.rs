@@ -1,31 +1,96 @@
-use std::{rc::Rc, cell::RefCell};
+use std::{rc::Rc, cell::{Cell, RefCell}};
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::Window;
 
-pub fn request_recursive(
+type FrameCallback = Rc<RefCell<dyn FnMut(f64) -> Result<(), JsValue>>>;
+
+/// A `requestAnimationFrame` loop calling `callback` with each frame's timestamp, in ms on
+/// the `performance.now()` timeline. It can be paused and resumed, or stopped for good.
+#[derive(Clone)]
+pub struct AnimationFrameLoop {
     window: Rc<Window>,
-    callback: Rc<RefCell<dyn FnMut() -> Result<(), JsValue>>>,
-) -> Result<(), JsValue> {
-    fn request_frame(
-        window: &Window,
-        callback: &Rc<RefCell<dyn FnMut() -> Result<(), JsValue>>>,
-    ) -> Result<(), JsValue> {
-        let window_clone = window.clone();
-        let callback_clone = callback.clone();
-        
-        let closure = Closure::once_into_js(Box::new(move || {
-            callback_clone.borrow_mut()().unwrap();
-            
-            // Schedule the next frame
-            request_frame(&window_clone, &callback_clone).unwrap();
-        }) as Box<dyn FnOnce()>);
-        
-        // Start the animation frame
-        window.request_animation_frame(closure.unchecked_ref())?;
-        
+    /// Id of the pending frame request, if one is scheduled.
+    handle: Rc<Cell<Option<i32>>>,
+    paused: Rc<Cell<bool>>,
+    stopped: Rc<Cell<bool>>,
+    callback: FrameCallback,
+}
+
+impl AnimationFrameLoop {
+    /// Start the loop; the first call happens on the next animation frame.
+    pub fn start(
+        window: Rc<Window>,
+        callback: impl FnMut(f64) -> Result<(), JsValue> + 'static,
+    ) -> Result<Self, JsValue> {
+        let frame_loop = AnimationFrameLoop {
+            window,
+            handle: Rc::new(Cell::new(None)),
+            paused: Rc::new(Cell::new(false)),
+            stopped: Rc::new(Cell::new(false)),
+            callback: Rc::new(RefCell::new(callback)),
+        };
+        frame_loop.request_frame()?;
+        Ok(frame_loop)
+    }
+
+    /// Cancel the pending frame and schedule no more until `resume`.
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.paused.set(true);
+        self.cancel_pending()
+    }
+
+    /// Continue a paused loop. Does nothing if the loop isn't paused or was stopped.
+    pub fn resume(&self) -> Result<(), JsValue> {
+        if self.stopped.get() || !self.paused.get() {
+            return Ok(());
+        }
+        self.paused.set(false);
+        if self.handle.get().is_none() {
+            self.request_frame()?;
+        }
+        Ok(())
+    }
+
+    /// End the loop: the pending frame is cancelled and no further frames are scheduled.
+    pub fn stop(&self) -> Result<(), JsValue> {
+        self.stopped.set(true);
+        self.cancel_pending()
+    }
+
+    /// Whether frames are being scheduled, i.e. the loop is neither paused nor stopped.
+    pub fn is_running(&self) -> bool {
+        !self.stopped.get() && !self.paused.get()
+    }
+
+    fn cancel_pending(&self) -> Result<(), JsValue> {
+        match self.handle.take() {
+            Some(id) => self.window.cancel_animation_frame(id),
+            None => Ok(()),
+        }
+    }
+
+    fn request_frame(&self) -> Result<(), JsValue> {
+        let frame_loop = self.clone();
+        let closure = Closure::once_into_js(Box::new(move |timestamp: f64| {
+            frame_loop.handle.set(None);
+            if !frame_loop.is_running() {
+                return;
+            }
+
+            if let Err(e) = (frame_loop.callback.borrow_mut())(timestamp) {
+                web_sys::console::error_1(&e);
+            }
+
+            // The callback may have paused or stopped the loop.
+            if frame_loop.is_running() && frame_loop.handle.get().is_none() {
+                if let Err(e) = frame_loop.request_frame() {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        }) as Box<dyn FnOnce(f64)>);
+
+        let id = self.window.request_animation_frame(closure.unchecked_ref())?;
+        self.handle.set(Some(id));
         Ok(())
     }
-    
-    // Start the recursive loop
-    request_frame(&window, &callback)
 }
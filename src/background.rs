@@ -0,0 +1,94 @@
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
+
+/// How a background image is fitted to the canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackgroundMode {
+    /// Scaled to fill the canvas, cropping whichever axis overflows.
+    Cover,
+    /// Scaled to fit entirely inside the canvas.
+    Contain,
+    /// Repeated at its natural size.
+    Tile,
+    /// Scaled to the canvas, ignoring the aspect ratio.
+    Stretch,
+}
+
+impl BackgroundMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "cover" => Ok(BackgroundMode::Cover),
+            "contain" => Ok(BackgroundMode::Contain),
+            "tile" => Ok(BackgroundMode::Tile),
+            "stretch" => Ok(BackgroundMode::Stretch),
+            other => Err(format!(
+                "Unknown background mode '{}', expected 'cover', 'contain', 'tile' or 'stretch'",
+                other
+            )),
+        }
+    }
+}
+
+/// An image drawn behind the scene in screen space. It scrolls by `parallax_factor` times
+/// the camera movement: 0 keeps it fixed, 1 moves it with the objects.
+pub struct Background {
+    image: HtmlImageElement,
+    mode: BackgroundMode,
+    parallax_factor: f64,
+}
+
+impl Background {
+    pub fn new(image: HtmlImageElement, mode: BackgroundMode, parallax_factor: f64) -> Self {
+        Self { image, mode, parallax_factor }
+    }
+
+    /// Draw over a `width` x `height` canvas. Nothing is drawn until the image has loaded.
+    pub fn render(
+        &self,
+        context: &CanvasRenderingContext2d,
+        width: f64,
+        height: f64,
+        camera: (f64, f64),
+        zoom: f64,
+    ) -> Result<(), JsValue> {
+        let image_width = self.image.natural_width() as f64;
+        let image_height = self.image.natural_height() as f64;
+        if !self.image.complete() || image_width == 0.0 || image_height == 0.0 {
+            return Ok(());
+        }
+        let offset_x = camera.0 * zoom * self.parallax_factor;
+        let offset_y = camera.1 * zoom * self.parallax_factor;
+
+        if self.mode == BackgroundMode::Tile {
+            let Some(pattern) = context.create_pattern_with_html_image_element(&self.image, "repeat")? else {
+                return Ok(());
+            };
+            context.save();
+            // The pattern is anchored to the origin, so shifting the origin scrolls the tiles.
+            context.translate(-offset_x, -offset_y)?;
+            context.set_fill_style_canvas_pattern(&pattern);
+            context.fill_rect(offset_x, offset_y, width, height);
+            context.restore();
+            return Ok(());
+        }
+
+        let (draw_width, draw_height) = match self.mode {
+            BackgroundMode::Cover => {
+                let scale = (width / image_width).max(height / image_height);
+                (image_width * scale, image_height * scale)
+            }
+            BackgroundMode::Contain => {
+                let scale = (width / image_width).min(height / image_height);
+                (image_width * scale, image_height * scale)
+            }
+            BackgroundMode::Stretch | BackgroundMode::Tile => (width, height),
+        };
+        context.draw_image_with_html_image_element_and_dw_and_dh(
+            &self.image,
+            (width - draw_width) / 2.0 - offset_x,
+            (height - draw_height) / 2.0 - offset_y,
+            draw_width,
+            draw_height,
+        )
+    }
+}
@@ -0,0 +1,148 @@
+//! Compact binary form of an exported scene, see `Rust2DEngine::serialize_to_arraybuffer`.
+//!
+//! All numbers are little-endian. The file is a 16-byte header followed by one record per
+//! object, each followed by its chunks:
+//!
+//! ```text
+//! header    magic     [u8; 4]   b"R2DS"
+//!           version   u32       BINARY_SCENE_VERSION
+//!           objects   u32       number of object records
+//!           keyframes u32       keyframes across all objects
+//! object    id         u32
+//!           size       f64
+//!           color      u32      0x00RRGGBB
+//!           chunk_size f32      ms
+//!           chunks     u32      number of chunk records that follow
+//! chunk     keyframes  u32      number of keyframes that follow
+//! keyframe  time, x, y f32 x 3
+//! ```
+//!
+//! Keyframe easing is not stored; keyframes come back linear.
+
+use crate::color::Rgb;
+use crate::keyframe::{self, Keyframe};
+use crate::scene::{SceneObjectV1, SceneObjectV2};
+
+const MAGIC: &[u8; 4] = b"R2DS";
+pub const BINARY_SCENE_VERSION: u32 = 1;
+const HEADER_LEN: usize = 16;
+const OBJECT_RECORD_LEN: usize = 24;
+const KEYFRAME_LEN: usize = 12;
+
+/// Encode objects as exported by `export_scene`. Fails for an object whose colour isn't
+/// a hex colour, since colours are stored as packed RGB.
+pub fn encode(objects: &[SceneObjectV1]) -> Result<Vec<u8>, String> {
+    let total_keyframes: usize = objects.iter().map(|obj| obj.keyframes.len()).sum();
+    let mut bytes = Vec::with_capacity(HEADER_LEN + objects.len() * OBJECT_RECORD_LEN + total_keyframes * KEYFRAME_LEN);
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&BINARY_SCENE_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&count(objects.len())?.to_le_bytes());
+    bytes.extend_from_slice(&count(total_keyframes)?.to_le_bytes());
+
+    for obj in objects {
+        let color = Rgb::parse_hex(&obj.color)
+            .map_err(|e| format!("Object {} can't be stored in a binary scene: {}", obj.object_id, e))?;
+        let chunks = keyframe::split_into_chunks(&obj.object_id.to_string(), obj.keyframes.clone(), obj.chunk_size);
+        bytes.extend_from_slice(&obj.object_id.to_le_bytes());
+        bytes.extend_from_slice(&obj.size.to_le_bytes());
+        bytes.extend_from_slice(&color.to_rgb24().to_le_bytes());
+        bytes.extend_from_slice(&obj.chunk_size.to_le_bytes());
+        bytes.extend_from_slice(&count(chunks.len())?.to_le_bytes());
+        for chunk in &chunks {
+            bytes.extend_from_slice(&count(chunk.keyframe_count())?.to_le_bytes());
+            for k in chunk.keyframes() {
+                bytes.extend_from_slice(&k.time().to_le_bytes());
+                bytes.extend_from_slice(&k.x().to_le_bytes());
+                bytes.extend_from_slice(&k.y().to_le_bytes());
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decode a scene written by `encode`. Object ids are dropped, as imports allocate fresh ones.
+pub fn decode(bytes: &[u8]) -> Result<Vec<SceneObjectV2>, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err("Not a binary scene: bad magic".to_string());
+    }
+    let version = reader.u32()?;
+    if version != BINARY_SCENE_VERSION {
+        return Err(format!("Unsupported binary scene version {}", version));
+    }
+    let object_count = reader.u32()?;
+    let total_keyframes = reader.u32()?;
+
+    let mut objects = Vec::new();
+    let mut keyframes_read = 0u64;
+    for _ in 0..object_count {
+        let object_id = reader.u32()?;
+        let size = reader.f64()?;
+        let color = Rgb::from_rgb24(reader.u32()?).to_hex();
+        let chunk_size = reader.f32()?;
+        if !(chunk_size > 0.0 && chunk_size.is_finite()) {
+            return Err(format!("Object {} has invalid chunk size {}", object_id, chunk_size));
+        }
+        let chunk_count = reader.u32()?;
+        let mut keyframes = Vec::new();
+        for _ in 0..chunk_count {
+            let n = reader.u32()?;
+            // Checked before allocating, so a corrupt count can't request a huge buffer.
+            reader.ensure((n as usize).saturating_mul(KEYFRAME_LEN))?;
+            keyframes.reserve(n as usize);
+            for _ in 0..n {
+                keyframes.push(Keyframe::new(reader.f32()?, reader.f32()?, reader.f32()?));
+            }
+            keyframes_read += u64::from(n);
+        }
+        objects.push(SceneObjectV2 { size, color, chunk_size, keyframes });
+    }
+
+    if keyframes_read != u64::from(total_keyframes) {
+        return Err(format!(
+            "Header promises {} keyframes but the records hold {}",
+            total_keyframes, keyframes_read
+        ));
+    }
+    if reader.pos != bytes.len() {
+        return Err(format!("{} unexpected trailing bytes", bytes.len() - reader.pos));
+    }
+    Ok(objects)
+}
+
+fn count(n: usize) -> Result<u32, String> {
+    u32::try_from(n).map_err(|_| format!("{} entries don't fit in a binary scene", n))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn ensure(&self, len: usize) -> Result<(), String> {
+        if self.bytes.len() - self.pos < len {
+            return Err(format!("Binary scene truncated at byte {}", self.pos));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        self.ensure(len)?;
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
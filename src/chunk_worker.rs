@@ -0,0 +1,132 @@
+use futures::channel::oneshot;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+use web_sys::{MessageEvent, Worker};
+
+use crate::keyframe::KeyframeChunk;
+use crate::keyframe_database::{DbError, KeyframeDatabase};
+
+type MessageListener = Closure<dyn FnMut(MessageEvent)>;
+type PendingFetches = Rc<RefCell<HashMap<u32, oneshot::Sender<Result<KeyframeChunk, DbError>>>>>;
+
+/// Messages exchanged, as JSON strings, between the engine and `chunk_worker.js`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum WorkerMessage {
+    FetchChunk { request_id: u32, object_id: String, chunk_id: u32 },
+    ChunkData { request_id: u32, chunk: KeyframeChunk },
+    /// The stored chunk failed its checksum; carries the chunk's key.
+    CorruptedChunk { request_id: u32, key: String },
+    Error { request_id: u32, message: String },
+}
+
+thread_local! {
+    static WORKER_DB: RefCell<Option<Arc<KeyframeDatabase>>> = const { RefCell::new(None) };
+}
+
+/// Worker-side entry point: answer one `FetchChunk` message from the worker's own
+/// connection to the keyframe database, opened on first use.
+#[wasm_bindgen]
+pub async fn handle_chunk_worker_message(message: String) -> Result<String, JsValue> {
+    let request: WorkerMessage = serde_json::from_str(&message)
+        .map_err(|e| JsValue::from_str(&format!("Invalid worker message: {}", e)))?;
+    let WorkerMessage::FetchChunk { request_id, object_id, chunk_id } = request else {
+        return Err(JsValue::from_str("Chunk worker only handles FetchChunk messages"));
+    };
+
+    let db = match WORKER_DB.with(|db| db.borrow().clone()) {
+        Some(db) => db,
+        None => {
+            let db = KeyframeDatabase::open_existing()
+                .await
+                .map_err(|e| JsValue::from_str(&format!("Worker failed to open keyframe database: {}", e)))?;
+            WORKER_DB.with(|slot| *slot.borrow_mut() = Some(db.clone()));
+            db
+        }
+    };
+
+    let response = match db.load_chunk(&object_id, chunk_id).await {
+        Ok(chunk) => WorkerMessage::ChunkData { request_id, chunk },
+        Err(DbError::CorruptedChunk(key)) => WorkerMessage::CorruptedChunk { request_id, key },
+        Err(e) => WorkerMessage::Error { request_id, message: e.to_string() },
+    };
+    serde_json::to_string(&response)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialise worker response: {}", e)))
+}
+
+/// Main-thread handle to a worker running `chunk_worker.js`. Replies are matched to
+/// requests by id, so any number of fetches can be in flight.
+pub struct ChunkWorker {
+    worker: Worker,
+    next_request_id: Cell<u32>,
+    pending: PendingFetches,
+    _on_message: MessageListener,
+}
+
+impl ChunkWorker {
+    pub fn new(worker: Worker) -> Self {
+        let pending: PendingFetches = Rc::new(RefCell::new(HashMap::new()));
+        let pending_clone = pending.clone();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let Some(data) = event.data().as_string() else {
+                return;
+            };
+            let (request_id, result) = match serde_json::from_str(&data) {
+                Ok(WorkerMessage::ChunkData { request_id, chunk }) => (request_id, Ok(chunk)),
+                Ok(WorkerMessage::CorruptedChunk { request_id, key }) => (request_id, Err(DbError::CorruptedChunk(key))),
+                Ok(WorkerMessage::Error { request_id, message }) => {
+                    (request_id, Err(idb::Error::AddFailed(JsValue::from_str(&message)).into()))
+                }
+                Ok(WorkerMessage::FetchChunk { .. }) | Err(_) => {
+                    web_sys::console::warn_1(&JsValue::from_str(&format!("Unexpected chunk worker message: {}", data)));
+                    return;
+                }
+            };
+            if let Some(sender) = pending_clone.borrow_mut().remove(&request_id) {
+                let _ = sender.send(result);
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        Self {
+            worker,
+            next_request_id: Cell::new(0),
+            pending,
+            _on_message: on_message,
+        }
+    }
+
+    pub async fn fetch_chunk(&self, object_id: &str, chunk_id: u32) -> Result<KeyframeChunk, DbError> {
+        let request_id = self.next_request_id.get();
+        self.next_request_id.set(request_id.wrapping_add(1));
+
+        let message = serde_json::to_string(&WorkerMessage::FetchChunk {
+            request_id,
+            object_id: object_id.to_string(),
+            chunk_id,
+        })
+        .map_err(|e| idb::Error::AddFailed(JsValue::from_str(&format!("Failed to serialise worker request: {}", e))))?;
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending.borrow_mut().insert(request_id, sender);
+        if let Err(e) = self.worker.post_message(&JsValue::from_str(&message)) {
+            self.pending.borrow_mut().remove(&request_id);
+            return Err(idb::Error::AddFailed(e).into());
+        }
+
+        receiver.await.unwrap_or_else(|_| {
+            Err(idb::Error::AddFailed(JsValue::from_str("Chunk worker dropped the request")).into())
+        })
+    }
+}
+
+impl Drop for ChunkWorker {
+    fn drop(&mut self) {
+        self.worker.set_onmessage(None);
+        self.worker.terminate();
+    }
+}
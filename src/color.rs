@@ -0,0 +1,59 @@
+/// An opaque colour with 0-255 channels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl Rgb {
+    /// Parse a CSS hex colour, `#rgb` or `#rrggbb`.
+    pub fn parse_hex(color: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid colour '{}', expected '#rgb' or '#rrggbb'", color);
+        let digits = color.strip_prefix('#').ok_or_else(invalid)?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let channel = |hex: &str| u8::from_str_radix(hex, 16).map(f64::from).map_err(|_| invalid());
+        match digits.len() {
+            3 => {
+                let expand = |i: usize| channel(&digits[i..i + 1].repeat(2));
+                Ok(Rgb { r: expand(0)?, g: expand(1)?, b: expand(2)? })
+            }
+            6 => Ok(Rgb {
+                r: channel(&digits[0..2])?,
+                g: channel(&digits[2..4])?,
+                b: channel(&digits[4..6])?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn lerp(&self, other: &Rgb, t: f64) -> Rgb {
+        Rgb {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+
+    /// Unpack `0x00RRGGBB`.
+    pub fn from_rgb24(packed: u32) -> Self {
+        Rgb {
+            r: f64::from((packed >> 16) & 0xFF),
+            g: f64::from((packed >> 8) & 0xFF),
+            b: f64::from(packed & 0xFF),
+        }
+    }
+
+    /// Pack as `0x00RRGGBB`, rounding each channel.
+    pub fn to_rgb24(self) -> u32 {
+        let channel = |value: f64| value.round().clamp(0.0, 255.0) as u32;
+        (channel(self.r) << 16) | (channel(self.g) << 8) | channel(self.b)
+    }
+
+    /// Format as `#rrggbb`, rounding each channel.
+    pub fn to_hex(self) -> String {
+        format!("#{:06x}", self.to_rgb24())
+    }
+}
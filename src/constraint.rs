@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// Spring acceleration, in pixels per ms², per pixel of stretch.
+const DEFAULT_STIFFNESS: f64 = 0.000_05;
+/// Fraction of the closing speed removed per ms.
+const DEFAULT_DAMPING: f64 = 0.01;
+
+/// Optional settings for `Constraint::parse`; anything left out takes a default.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ConstraintParams {
+    target: Option<f64>,
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+/// A joint between the centres of two objects, applied after keyframe interpolation.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum Constraint {
+    /// Both objects are moved, equally, until their centres are exactly `target` apart.
+    FixedDistance { target: f64 },
+    /// A damped spring pulling the centres towards `rest_length` apart.
+    SpringDamper { rest_length: f64, stiffness: f64, damping: f64 },
+}
+
+impl Constraint {
+    /// Build a constraint of `kind` (`"fixed_distance"` or `"spring_damper"`). Lengths not
+    /// given in `params` default to `current_distance`, so the objects start at rest.
+    pub fn parse(kind: &str, params: ConstraintParams, current_distance: f64) -> Result<Self, String> {
+        let target = params.target.unwrap_or(current_distance);
+        match kind {
+            "fixed_distance" => Ok(Constraint::FixedDistance { target }),
+            "spring_damper" => Ok(Constraint::SpringDamper {
+                rest_length: target,
+                stiffness: params.stiffness.unwrap_or(DEFAULT_STIFFNESS),
+                damping: params.damping.unwrap_or(DEFAULT_DAMPING),
+            }),
+            other => Err(format!(
+                "Unknown constraint kind '{}', expected 'fixed_distance' or 'spring_damper'",
+                other
+            )),
+        }
+    }
+}
+
+/// Those of `ids` that no spring in `constraints` acts on any more. Springs are the only
+/// constraints that give objects velocity, so these should be brought to rest.
+pub fn unsprung(constraints: &[(u32, u32, Constraint)], ids: &[u32]) -> Vec<u32> {
+    ids.iter()
+        .copied()
+        .filter(|id| {
+            !constraints.iter().any(|(a, b, constraint)| {
+                matches!(constraint, Constraint::SpringDamper { .. }) && (a == id || b == id)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spring() -> Constraint {
+        Constraint::SpringDamper { rest_length: 10.0, stiffness: 0.1, damping: 0.1 }
+    }
+
+    #[test]
+    fn objects_still_on_a_spring_keep_moving() {
+        let constraints = vec![(1, 2, spring())];
+        assert!(unsprung(&constraints, &[1, 2]).is_empty());
+    }
+
+    #[test]
+    fn objects_without_springs_are_unsprung() {
+        let constraints = vec![(1, 2, spring())];
+        assert_eq!(unsprung(&constraints, &[2, 3, 4]), vec![3, 4]);
+        assert_eq!(unsprung(&[], &[1]), vec![1]);
+    }
+
+    #[test]
+    fn fixed_distance_constraints_do_not_count_as_springs() {
+        let constraints = vec![(1, 2, Constraint::FixedDistance { target: 5.0 }), (2, 3, spring())];
+        assert_eq!(unsprung(&constraints, &[1, 2, 3]), vec![1]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+/// A text readout the engine can write into a DOM element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DebugReadout {
+    /// Frame rate averaged over recent frames.
+    Fps,
+    /// Ids of the objects under the mouse while a button is held.
+    HitIndices,
+    /// World-space position of the mouse.
+    MouseCoords,
+}
+
+impl DebugReadout {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "fps" => Ok(DebugReadout::Fps),
+            "hit-indices" => Ok(DebugReadout::HitIndices),
+            "mouse-coords" => Ok(DebugReadout::MouseCoords),
+            other => Err(format!(
+                "Unknown debug display '{}', expected 'fps', 'hit-indices' or 'mouse-coords'",
+                other
+            )),
+        }
+    }
+}
+
+/// Which readouts are shown, and the id of the element each one is written to.
+/// `fps` and `hit-indices` start enabled on the elements of the same name.
+pub struct DebugDisplay {
+    element_ids: HashMap<DebugReadout, String>,
+}
+
+impl Default for DebugDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugDisplay {
+    pub fn new() -> Self {
+        let element_ids = HashMap::from([
+            (DebugReadout::Fps, "fps".to_string()),
+            (DebugReadout::HitIndices, "hit-indices".to_string()),
+        ]);
+        Self { element_ids }
+    }
+
+    pub fn enable(&mut self, readout: DebugReadout, element_id: &str) {
+        self.element_ids.insert(readout, element_id.to_string());
+    }
+
+    pub fn disable(&mut self, readout: DebugReadout) {
+        self.element_ids.remove(&readout);
+    }
+
+    pub fn is_enabled(&self, readout: DebugReadout) -> bool {
+        self.element_ids.contains_key(&readout)
+    }
+
+    /// Write `text` into the readout's element, if the readout is enabled and the element
+    /// is on the page.
+    pub fn show(&self, readout: DebugReadout, text: &str) {
+        let Some(element_id) = self.element_ids.get(&readout) else {
+            return;
+        };
+        if let Some(el) = web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|doc| doc.get_element_by_id(element_id))
+        {
+            el.set_inner_html(text);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+/// Shape of the transition from one keyframe to the next. Maps linear progress
+/// `t` in [0, 1] to eased progress.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    EaseInBounce,
+    EaseOutBounce,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+            Easing::EaseInBounce => 1.0 - Self::bounce_out(1.0 - t),
+            Easing::EaseOutBounce => Self::bounce_out(t),
+        }
+    }
+
+    /// Slope of `apply` at `t`, estimated with a central difference.
+    pub fn derivative(&self, t: f64) -> f64 {
+        const H: f64 = 1e-4;
+        let lo = (t - H).max(0.0);
+        let hi = (t + H).min(1.0);
+        (self.apply(hi) - self.apply(lo)) / (hi - lo)
+    }
+
+    /// The same curve played backwards in time.
+    pub fn reversed(&self) -> Easing {
+        match self {
+            Easing::EaseIn => Easing::EaseOut,
+            Easing::EaseOut => Easing::EaseIn,
+            Easing::EaseInBounce => Easing::EaseOutBounce,
+            Easing::EaseOutBounce => Easing::EaseInBounce,
+            Easing::Linear | Easing::EaseInOut => *self,
+        }
+    }
+
+    /// Accepts the names used from JS: `"linear"`, `"ease_in"`, `"ease_out"`,
+    /// `"ease_in_out"`, `"ease_in_bounce"` and `"ease_out_bounce"`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "linear" => Ok(Easing::Linear),
+            "ease_in" => Ok(Easing::EaseIn),
+            "ease_out" => Ok(Easing::EaseOut),
+            "ease_in_out" => Ok(Easing::EaseInOut),
+            "ease_in_bounce" => Ok(Easing::EaseInBounce),
+            "ease_out_bounce" => Ok(Easing::EaseOutBounce),
+            other => Err(format!("Unknown easing '{}'", other)),
+        }
+    }
+
+    fn bounce_out(t: f64) -> f64 {
+        const N: f64 = 7.5625;
+        const D: f64 = 2.75;
+        if t < 1.0 / D {
+            N * t * t
+        } else if t < 2.0 / D {
+            let t = t - 1.5 / D;
+            N * t * t + 0.75
+        } else if t < 2.5 / D {
+            let t = t - 2.25 / D;
+            N * t * t + 0.9375
+        } else {
+            let t = t - 2.625 / D;
+            N * t * t + 0.984375
+        }
+    }
+}
@@ -1,66 +1,581 @@
+#![warn(clippy::cast_precision_loss)]
+
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
+use wasm_bindgen_futures::{future_to_promise, spawn_local};
 use wasm_bindgen_futures::JsFuture;
+use serde::{Deserialize, Serialize};
 use web_sys::window;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::{Cell, RefCell}, rc::Rc};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
 use crate::aabb::AABB;
-use crate::animation_frame;
+use crate::animation_frame::AnimationFrameLoop;
+use crate::chunk_worker::ChunkWorker;
+use crate::color::Rgb;
+use crate::constraint::{self, Constraint, ConstraintParams};
+use crate::debug_display::{DebugDisplay, DebugReadout};
+use crate::easing::Easing;
+use crate::error::EngineError;
+use crate::fill_style::{ColorStop, FillStyle};
+use crate::background::{Background, BackgroundMode};
+use crate::binary_scene;
+use crate::heat_map::HeatMap;
+use crate::scene_tree::{self, ScenePanel, SceneTreeRow};
+use crate::keyframe;
 use crate::keyframe::Keyframe;
-use crate::keyframe::KeyframeChunk;
-use crate::keyframe_database::KeyframeDatabase;
+use crate::keyframe_database::{CompressionLevel, DbError, EvictionPolicy, KeyframeDatabase};
+use crate::keyframe_editor::KeyframeEditor;
+use crate::keyframe_store::{ChunkCacheMetrics, KeyframeStore};
+use crate::layout::{Alignment, LayoutParams};
+use crate::lazy_squre_object::LazySquareObject;
+use crate::math::Vector2;
+use crate::object_watcher::ObjectWatcher;
+use crate::profiler::Profiler;
+use crate::pool::ObjectPool;
+use crate::quadtree::Quadtree;
+use crate::scene::{
+    KeyframeSpec, SceneDeserialiser, SceneObjectSpec, SceneObjectV1, SceneObjectV2, SceneSpec, SceneV1,
+    CURRENT_SCENE_VERSION, CURRENT_SPEC_VERSION,
+};
 use crate::squre_object;
 use crate::input;
+use crate::ring_buffer::RingBuffer;
 use crate::squre_object::SquareObject;
+use crate::timed_text::TimedText;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 static NEXT_SQUARE_INDEX: AtomicU32 = AtomicU32::new(0);
+static NEXT_TRACK_INDEX: AtomicU32 = AtomicU32::new(0);
+static NEXT_TIMED_TEXT_ID: AtomicU32 = AtomicU32::new(0);
+static NEXT_WATCHER_ID: AtomicU32 = AtomicU32::new(0);
+static NEXT_RENDER_HOOK_ID: AtomicU32 = AtomicU32::new(0);
+static NEXT_SPAWN_SEQ: AtomicU64 = AtomicU64::new(0);
+const SHARED_TRACK_CHUNK_SIZE: f32 = 10_000.0;
+const STRESS_TEST_KEYFRAMES: u32 = 1_000;
+const STRESS_TEST_SIZE: f64 = 20.0;
+/// Frames taking longer than this multiple of the 60 Hz budget count as dropped.
+const DROPPED_FRAME_FACTOR: f64 = 1.5;
+/// Object slots reserved up front so typical scenes never grow the pool.
+const INITIAL_OBJECT_CAPACITY: usize = 1_024;
+const FRAME_HISTORY_SIZE: usize = 60;
+const MIN_RENDER_SCALE: f64 = 0.1;
+const MAX_RENDER_SCALE: f64 = 2.0;
+/// Frames arriving this close to the target interval still count, so a cap equal to the
+/// display rate doesn't drop frames to vsync jitter.
+const FRAME_LIMIT_SLACK_MS: f64 = 1.0;
+/// Camera pan speed, in pixels per ms, at full stick deflection.
+const GAMEPAD_PAN_SPEED: f64 = 0.5;
+const DEFAULT_PHYSICS_FRICTION: f64 = 0.002;
+const DEFAULT_BOUNCE_COEFFICIENT: f64 = 0.8;
+const QUADTREE_MAX_OBJECTS: usize = 8;
+const QUADTREE_MAX_DEPTH: u32 = 8;
+/// Values accepted by the canvas `globalCompositeOperation`.
+const COMPOSITE_OPERATIONS: &[&str] = &[
+    "source-over", "source-in", "source-out", "source-atop",
+    "destination-over", "destination-in", "destination-out", "destination-atop",
+    "lighter", "copy", "xor", "multiply", "screen", "overlay", "darken", "lighten",
+    "color-dodge", "color-burn", "hard-light", "soft-light", "difference", "exclusion",
+    "hue", "saturation", "color", "luminosity",
+];
+const MAX_EXPORTED_FRAMES: u32 = 300;
+/// How often debug builds log chunk cache metrics, in ms of engine time.
+const CACHE_METRICS_LOG_INTERVAL_MS: f64 = 5_000.0;
+const BENCHMARK_FRAME_STEP_MS: f64 = 1000.0 / 60.0;
+/// Spacing of the keyframes written by `animate_object_along_path`: one per 60 Hz frame.
+const PATH_KEYFRAME_STEP_MS: f64 = 1000.0 / 60.0;
+/// Most keyframes `animate_object_along_path` writes: ten minutes at 60 Hz.
+const MAX_PATH_KEYFRAMES: u32 = 36_000;
+/// Most empty placeholder chunks `add_keyframe_to_object` writes to reach a keyframe past
+/// the end of the track.
+const MAX_KEYFRAME_GAP_CHUNKS: u32 = 1_000;
+
+type FetchInterval = Option<(i32, Closure<dyn FnMut()>)>;
+type RenderHooks = HashMap<RenderPhase, Vec<(u32, js_sys::Function)>>;
+
+/// Reject canvas filter strings that could break out of the `filter` value, e.g. by
+/// smuggling in another declaration.
+fn validate_filter(filter: &str) -> Result<(), String> {
+    if filter.trim().is_empty() {
+        return Err("Filter must not be empty, use 'none' to disable it".to_string());
+    }
+    if let Some(c) = filter.chars().find(|c| matches!(c, ';' | '{' | '}' | '<' | '>' | '\\' | '"' | '\'') || c.is_control()) {
+        return Err(format!("Filter '{}' contains the disallowed character {:?}", filter, c));
+    }
+    Ok(())
+}
+
+/// Canvas position of a point seen by a camera whose top-left corner is at `camera` in world
+/// space, magnified by `zoom`.
+fn world_to_screen(wx: f64, wy: f64, camera: (f64, f64), zoom: f64) -> Vector2 {
+    Vector2::new((wx - camera.0) * zoom, (wy - camera.1) * zoom)
+}
+
+/// Inverse of `world_to_screen`.
+fn screen_to_world(sx: f64, sy: f64, camera: (f64, f64), zoom: f64) -> Vector2 {
+    Vector2::new(sx / zoom + camera.0, sy / zoom + camera.1)
+}
+
+/// Reject shoves that would leave an object at a non-finite position, or run for a
+/// negative time.
+fn validate_force(fx: f64, fy: f64, duration_ms: f64) -> Result<(), String> {
+    if !fx.is_finite() || !fy.is_finite() {
+        return Err(format!("Force must be finite, got ({}, {})", fx, fy));
+    }
+    if !duration_ms.is_finite() || duration_ms < 0.0 {
+        return Err(format!("Force duration must be a non-negative number of ms, got {}", duration_ms));
+    }
+    Ok(())
+}
+
+/// Reject keyframes that can't be stored, and times so far past the end of a track of
+/// `chunk_count` chunks that reaching them would take more than `MAX_KEYFRAME_GAP_CHUNKS`
+/// placeholders.
+fn validate_keyframe(time: f64, x: f64, y: f64, chunk_size: f64, chunk_count: u32) -> Result<(), String> {
+    if !time.is_finite() || time < 0.0 {
+        return Err(format!("Keyframe time must be a non-negative number of ms, got {}", time));
+    }
+    if !x.is_finite() || !y.is_finite() {
+        return Err(format!("Keyframe position must be finite, got ({}, {})", x, y));
+    }
+    let gap = (time / chunk_size).floor() - f64::from(chunk_count);
+    if gap > f64::from(MAX_KEYFRAME_GAP_CHUNKS) {
+        return Err(format!(
+            "Keyframe at {} ms is {} chunks past the end of the track, more than the limit of {}",
+            time, gap, MAX_KEYFRAME_GAP_CHUNKS
+        ));
+    }
+    Ok(())
+}
+
+/// Number of `PATH_KEYFRAME_STEP_MS` steps a path of `duration_ms` takes, refusing
+/// paths that would need more than `MAX_PATH_KEYFRAMES` keyframes.
+fn path_keyframe_steps(duration_ms: f64) -> Result<u32, String> {
+    if !duration_ms.is_finite() || duration_ms <= 0.0 {
+        return Err(format!("Path duration must be a positive number of ms, got {}", duration_ms));
+    }
+    let steps = (duration_ms / PATH_KEYFRAME_STEP_MS).ceil();
+    if steps >= f64::from(MAX_PATH_KEYFRAMES) {
+        return Err(format!(
+            "Path of {} ms needs {} keyframes, more than the limit of {}",
+            duration_ms,
+            steps + 1.0,
+            MAX_PATH_KEYFRAMES
+        ));
+    }
+    Ok(steps as u32)
+}
+
+/// `frames + 1` keyframes at random points in `[0, span_x] x [0, span_y]`, starting at
+/// time 0 and 0-1000 ms apart, drawing from `rng` (uniform in `[0, 1)`).
+fn random_keyframes(frames: u32, span_x: f64, span_y: f64, mut rng: impl FnMut() -> f64) -> Vec<Keyframe> {
+    let mut keyframes: Vec<Keyframe> = Vec::with_capacity(frames as usize + 1);
+
+    let mut t = 0.0f64;
+    let x0 = rng() * span_x;
+    let y0 = rng() * span_y;
+    keyframes.push(Keyframe::new(t as f32, x0 as f32, y0 as f32));
+
+    for _ in 0..frames {
+        t += rng() * 1000.0;
+        let x = rng() * span_x;
+        let y = rng() * span_y;
+        keyframes.push(Keyframe::new(t as f32, x as f32, y as f32));
+    }
+    keyframes
+}
+
+/// Whether an animation frame `delta` ms after the last rendered one should render under
+/// the optional frame rate cap.
+fn frame_due(target_fps: Option<f64>, delta: f64) -> bool {
+    target_fps.is_none_or(|fps| delta >= 1000.0 / fps - FRAME_LIMIT_SLACK_MS)
+}
+
+/// Objects in the order they are drawn: by render order, ties in insertion order.
+fn draw_order(objs: &ObjectPool<SquareObject>) -> Vec<&SquareObject> {
+    let mut ordered: Vec<&SquareObject> = objs.iter().collect();
+    // Pool slots are reused, so slot order is not insertion order; the spawn sequence is,
+    // and keeps objects sharing an order from swapping places between frames.
+    ordered.sort_by_key(|obj| (obj.render_order(), obj.spawn_seq()));
+    ordered
+}
+
+/// Quadtree of object hit bounds, rebuilt on every render. Entries are indices into `ids`,
+/// which lists the objects in draw order, so query results can be put back in that order.
+struct SpatialIndex {
+    tree: Quadtree,
+    ids: Vec<u32>,
+}
+
+impl SpatialIndex {
+    fn build(objs: &[&SquareObject]) -> Self {
+        let bounds: Vec<AABB> = objs.iter().map(|obj| obj.hit_bounds()).collect();
+        let root = AABB::from_points(
+            bounds.iter().flat_map(|b| [(b.min_x(), b.min_y()), (b.max_x(), b.max_y())]),
+        )
+        .unwrap_or_else(|| AABB::new(0.0, 0.0, 0.0, 0.0));
+
+        let mut tree = Quadtree::new(root, QUADTREE_MAX_OBJECTS, QUADTREE_MAX_DEPTH);
+        for (rank, b) in bounds.iter().enumerate() {
+            tree.insert(rank as u32, b);
+        }
+        SpatialIndex {
+            tree,
+            ids: objs.iter().map(|obj| obj.object_id()).collect(),
+        }
+    }
+
+    /// Draw-order ranks of the objects intersecting `region`, ascending.
+    fn query_ranks(&self, region: &AABB) -> Vec<usize> {
+        let mut ranks: Vec<usize> = self.tree.query(region).into_iter().map(|rank| rank as usize).collect();
+        ranks.sort_unstable();
+        ranks
+    }
+}
+
+/// Points in `render` where JS hooks can draw. Object hooks run under the camera transform
+/// and clip region, the others in screen space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum RenderPhase {
+    BeforeBackground,
+    AfterBackground,
+    BeforeObjects,
+    AfterObjects,
+    BeforeOverlay,
+    AfterOverlay,
+}
+
+impl RenderPhase {
+    fn parse(phase: &str) -> Result<Self, String> {
+        match phase {
+            "before_background" => Ok(RenderPhase::BeforeBackground),
+            "after_background" => Ok(RenderPhase::AfterBackground),
+            "before_objects" => Ok(RenderPhase::BeforeObjects),
+            "after_objects" => Ok(RenderPhase::AfterObjects),
+            "before_overlay" => Ok(RenderPhase::BeforeOverlay),
+            "after_overlay" => Ok(RenderPhase::AfterOverlay),
+            other => Err(format!(
+                "Unknown render phase '{}', expected before_/after_ followed by background, objects or overlay",
+                other
+            )),
+        }
+    }
+}
+
+/// Screen-space mask outside which objects are not drawn.
+enum ClipRegion {
+    Rect(AABB),
+    Polygon(Vec<(f64, f64)>),
+}
+
+/// Camera transition between two world-space regions, advanced by `update`.
+struct ViewportAnimation {
+    start: AABB,
+    target: AABB,
+    elapsed: f64,
+    duration: f64,
+    easing: Easing,
+}
+
+/// One-shot colour transition on a single object, advanced by `update`.
+struct ColorTween {
+    start: Rgb,
+    target: Rgb,
+    elapsed: f64,
+    duration: f64,
+    easing: Easing,
+}
 
 enum EngineTask {
     FetchData,
+    /// Load chunks that playback found missing, see `KeyframeDatabase::request_urgent_fetch`.
+    FetchUrgent,
     UpdateAndRender(f64),
 }
 
+#[derive(Serialize)]
+struct BenchmarkResult {
+    frames: u32,
+    total_ms: f64,
+    avg_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+    fps: f64,
+}
+
+#[derive(Serialize)]
+struct StressTestResult {
+    objects: u32,
+    frames_rendered: u32,
+    avg_fps: f64,
+    min_fps: f64,
+    max_fps: f64,
+    dropped_frames: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoundingRect {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl From<&AABB> for BoundingRect {
+    fn from(bounds: &AABB) -> Self {
+        BoundingRect {
+            min_x: bounds.min_x(),
+            min_y: bounds.min_y(),
+            max_x: bounds.max_x(),
+            max_y: bounds.max_y(),
+            width: bounds.width(),
+            height: bounds.height(),
+        }
+    }
+}
+
+/// One entry of `batch_set_properties`; fields left out are unchanged.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PropertyUpdate {
+    object_id: u32,
+    color: Option<String>,
+    size: Option<f64>,
+    paused: Option<bool>,
+    render_order: Option<u32>,
+    filter: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PropertyUpdateError {
+    object_id: u32,
+    message: String,
+}
+
+/// Description of an event the engine dispatches on its canvas, for `list_events`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventDoc {
+    name: &'static str,
+    description: &'static str,
+    payload_schema: &'static str,
+}
+
+const EVENT_DOCS: &[EventDoc] = &[
+    EventDoc {
+        name: "color_tween_complete",
+        description: "A colour tween started by tween_object_color reached its target colour. \
+                      Dispatched after the update that finished it.",
+        payload_schema: "CustomEvent; detail: number (object id)",
+    },
+    EventDoc {
+        name: "viewport_animation_complete",
+        description: "The camera reached the target region of a viewport animation.",
+        payload_schema: "Event; no payload",
+    },
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RaycastHit {
+    object_id: u32,
+    distance: f64,
+    hit_x: f64,
+    hit_y: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TextMeasurement {
+    width: f64,
+    actual_bounding_box_ascent: f64,
+    actual_bounding_box_descent: f64,
+    /// Ascent plus descent.
+    height: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateProgress {
+    created: u32,
+    total: u32,
+    percentage: f64,
+    object_id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheMetricsReport {
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    hit_rate: f64,
+    avg_load_ms: f64,
+}
+
+impl From<ChunkCacheMetrics> for CacheMetricsReport {
+    fn from(metrics: ChunkCacheMetrics) -> Self {
+        Self {
+            hits: metrics.hits,
+            misses: metrics.misses,
+            evictions: metrics.evictions,
+            hit_rate: metrics.hit_rate(),
+            avg_load_ms: metrics.avg_load_ms(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageStats {
+    usage_bytes: f64,
+    total_keyframes: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConstraintInfo<'a> {
+    object_a: u32,
+    object_b: u32,
+    #[serde(flatten)]
+    constraint: &'a Constraint,
+}
+
+#[derive(Serialize)]
+struct KeyframeSample {
+    x: f64,
+    y: f64,
+    time: f64,
+}
+
+#[derive(Deserialize)]
+struct SizeKeyframe {
+    time: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Everything about an object's current state that JS can read.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectSnapshot {
+    object_id: u32,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    color: String,
+    current_time: f64,
+    selected: bool,
+    paused: bool,
+    render_order: Option<u32>,
+}
+
+impl From<&SquareObject> for ObjectSnapshot {
+    fn from(obj: &SquareObject) -> Self {
+        ObjectSnapshot {
+            object_id: obj.object_id(),
+            x: obj.current_x(),
+            y: obj.current_y(),
+            width: obj.width(),
+            height: obj.height(),
+            color: obj.color().to_string(),
+            current_time: obj.current_time(),
+            selected: obj.is_selected(),
+            paused: obj.is_paused(),
+            render_order: Some(obj.render_order()).filter(|order| *order != squre_object::DEFAULT_RENDER_ORDER),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RenderResolution {
+    width: u32,
+    height: u32,
+}
+
 #[wasm_bindgen]
 pub struct Rust2DEngine {
     window: Rc<Window>,
     window_width: f64,
     window_height: f64,
     viewport: AABB,
+    canvas: HtmlCanvasElement,
     context: CanvasRenderingContext2d,
     last_frame_time: f64,
-    objects: RefCell<Vec<squre_object::SquareObject>>,
-    input_handler: input::InputHandler,
+    objects: Rc<RefCell<ObjectPool<squre_object::SquareObject>>>,
+    lazy_objects: Rc<RefCell<Vec<LazySquareObject>>>,
+    constraints: Rc<RefCell<Vec<(u32, u32, Constraint)>>>,
+    watchers: Rc<RefCell<Vec<ObjectWatcher>>>,
+    render_hooks: Rc<RefCell<RenderHooks>>,
+    input_handler: Rc<input::InputHandler>,
     keyframe_db: Arc<KeyframeDatabase>,
     task_queue: Rc<RefCell<VecDeque<EngineTask>>>,
+    frame_times: RingBuffer<f64>,
+    running: Rc<Cell<bool>>,
+    animation_handle: Rc<RefCell<Option<AnimationFrameLoop>>>,
+    fetch_interval: Rc<RefCell<FetchInterval>>,
+    heat_map: Rc<RefCell<Option<HeatMap>>>,
+    background: Rc<RefCell<Option<Background>>>,
+    scene_panel: Rc<RefCell<Option<ScenePanel>>>,
+    debug_display: Rc<RefCell<DebugDisplay>>,
+    headless: Rc<Cell<bool>>,
+    shared_stores: Rc<RefCell<HashMap<String, KeyframeStore>>>,
+    auto_cursor: Rc<Cell<bool>>,
+    camera_offset: Rc<Cell<(f64, f64)>>,
+    camera_zoom: Rc<Cell<f64>>,
+    viewport_animation: Rc<RefCell<Option<ViewportAnimation>>>,
+    color_tweens: Rc<RefCell<HashMap<u32, ColorTween>>>,
+    /// Object id for each name given through `create_named_object` or `set_object_name`.
+    name_registry: Rc<RefCell<HashMap<String, u32>>>,
+    clip_region: Rc<RefCell<Option<ClipRegion>>>,
+    physics_friction: Rc<Cell<f64>>,
+    /// Pixels per ms², applied to physics-enabled objects.
+    gravity: Rc<RefCell<Vector2>>,
+    bounce_coefficient: Rc<Cell<f64>>,
+    spatial_index: Rc<RefCell<Option<SpatialIndex>>>,
+    use_quadtree: Rc<Cell<bool>>,
+    gamepad_camera_control: Rc<Cell<bool>>,
+    target_fps: Rc<Cell<Option<f64>>>,
+    render_scale: Rc<Cell<f64>>,
+    global_time: Rc<Cell<f64>>,
+    last_cache_metrics_log: Rc<Cell<f64>>,
+    timed_texts: Rc<RefCell<Vec<TimedText>>>,
+    selection_color: Rc<RefCell<String>>,
+    composite_operation: Rc<RefCell<String>>,
+    /// CSS filter applied while objects are drawn, e.g. `"blur(4px)"`.
+    render_filter: Rc<RefCell<String>>,
+    profiler: Rc<RefCell<Profiler>>,
+    dragged_object: Rc<Cell<Option<u32>>>,
+    drag_offset: Rc<RefCell<Vector2>>,
 }
 
 #[wasm_bindgen]
 impl Rust2DEngine {
     #[wasm_bindgen(constructor)]
     pub async fn new(canvas_id: &str) -> Result<Rust2DEngine, JsValue> {
-        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window`"))?;
-        let document = window.document().ok_or_else(|| JsValue::from_str("no `document`"))?;
+        let window = web_sys::window().ok_or(EngineError::WindowNotFound)?;
+        let document = window.document().ok_or(EngineError::DocumentNotFound)?;
         let canvas_el = document
             .get_element_by_id(canvas_id)
-            .ok_or_else(|| JsValue::from_str("canvas not found"))?
+            .ok_or(EngineError::CanvasNotFound)?
             .dyn_into::<HtmlCanvasElement>()?;
 
         let context = canvas_el
             .get_context("2d")?
-            .ok_or_else(|| JsValue::from_str("failed to get 2d context"))?
+            .ok_or(EngineError::ContextCreationFailed)?
             .dyn_into::<CanvasRenderingContext2d>()?;
         let last_frame_time = window.performance().unwrap().now();
         let input_handler = input::InputHandler::new(&canvas_el)?;
         let keyframe_db = KeyframeDatabase::new()
             .await
-            .map_err(|e| {
-                JsValue::from_str(&format!("KeyframeDatabase init failed: {}", e))
-            })?;
+            .map_err(|e| EngineError::DatabaseInitFailed(e.to_string()))?;
         let task_queue = Rc::new(RefCell::new(VecDeque::new()));
         let (width, height) = Rust2DEngine::get_window_inner_size(&window.clone());
         let viewport = AABB::new (0.0, 0.0, width as f64, height as f64);
@@ -69,18 +584,118 @@ impl Rust2DEngine {
             window_width: width.into(),
             window_height: height.into(),
             viewport: viewport,
+            canvas: canvas_el,
             context,
             last_frame_time,
-            objects: RefCell::new(Vec::new()),
-            input_handler,
+            objects: Rc::new(RefCell::new(ObjectPool::with_capacity(INITIAL_OBJECT_CAPACITY))),
+            lazy_objects: Rc::new(RefCell::new(Vec::new())),
+            constraints: Rc::new(RefCell::new(Vec::new())),
+            watchers: Rc::new(RefCell::new(Vec::new())),
+            render_hooks: Rc::new(RefCell::new(HashMap::new())),
+            input_handler: Rc::new(input_handler),
             keyframe_db: keyframe_db,
             task_queue: task_queue,
+            frame_times: RingBuffer::new(FRAME_HISTORY_SIZE),
+            running: Rc::new(Cell::new(false)),
+            animation_handle: Rc::new(RefCell::new(None)),
+            fetch_interval: Rc::new(RefCell::new(None)),
+            heat_map: Rc::new(RefCell::new(None)),
+            background: Rc::new(RefCell::new(None)),
+            scene_panel: Rc::new(RefCell::new(None)),
+            debug_display: Rc::new(RefCell::new(DebugDisplay::new())),
+            headless: Rc::new(Cell::new(false)),
+            shared_stores: Rc::new(RefCell::new(HashMap::new())),
+            auto_cursor: Rc::new(Cell::new(false)),
+            camera_offset: Rc::new(Cell::new((0.0, 0.0))),
+            camera_zoom: Rc::new(Cell::new(1.0)),
+            viewport_animation: Rc::new(RefCell::new(None)),
+            color_tweens: Rc::new(RefCell::new(HashMap::new())),
+            name_registry: Rc::new(RefCell::new(HashMap::new())),
+            clip_region: Rc::new(RefCell::new(None)),
+            physics_friction: Rc::new(Cell::new(DEFAULT_PHYSICS_FRICTION)),
+            gravity: Rc::new(RefCell::new(Vector2::new(0.0, 0.0))),
+            bounce_coefficient: Rc::new(Cell::new(DEFAULT_BOUNCE_COEFFICIENT)),
+            spatial_index: Rc::new(RefCell::new(None)),
+            use_quadtree: Rc::new(Cell::new(false)),
+            gamepad_camera_control: Rc::new(Cell::new(false)),
+            target_fps: Rc::new(Cell::new(None)),
+            render_scale: Rc::new(Cell::new(1.0)),
+            global_time: Rc::new(Cell::new(0.0)),
+            last_cache_metrics_log: Rc::new(Cell::new(0.0)),
+            timed_texts: Rc::new(RefCell::new(Vec::new())),
+            selection_color: Rc::new(RefCell::new(squre_object::DEFAULT_HIGHLIGHT_COLOR.to_string())),
+            composite_operation: Rc::new(RefCell::new("source-over".to_string())),
+            render_filter: Rc::new(RefCell::new("none".to_string())),
+            profiler: Rc::new(RefCell::new(Profiler::new())),
+            dragged_object: Rc::new(Cell::new(None)),
+            drag_offset: Rc::new(RefCell::new(Vector2::new(0.0, 0.0))),
         })
     }
 
+    /// Copy of this engine that shares all runtime state (objects, input, task queue,
+    /// loop handles), so the JS-side handle keeps working while `run` drives the copy.
+    fn share(&self) -> Self {
+        Rust2DEngine {
+            window: self.window.clone(),
+            window_width: self.window_width,
+            window_height: self.window_height,
+            viewport: AABB::new(0.0, 0.0, self.window_width, self.window_height),
+            canvas: self.canvas.clone(),
+            context: self.context.clone(),
+            last_frame_time: self.window.performance().unwrap().now(),
+            objects: self.objects.clone(),
+            lazy_objects: self.lazy_objects.clone(),
+            constraints: self.constraints.clone(),
+            watchers: self.watchers.clone(),
+            render_hooks: self.render_hooks.clone(),
+            input_handler: self.input_handler.clone(),
+            keyframe_db: self.keyframe_db.clone(),
+            task_queue: self.task_queue.clone(),
+            frame_times: RingBuffer::new(FRAME_HISTORY_SIZE),
+            running: self.running.clone(),
+            animation_handle: self.animation_handle.clone(),
+            fetch_interval: self.fetch_interval.clone(),
+            heat_map: self.heat_map.clone(),
+            background: self.background.clone(),
+            scene_panel: self.scene_panel.clone(),
+            debug_display: self.debug_display.clone(),
+            headless: self.headless.clone(),
+            shared_stores: self.shared_stores.clone(),
+            auto_cursor: self.auto_cursor.clone(),
+            camera_offset: self.camera_offset.clone(),
+            camera_zoom: self.camera_zoom.clone(),
+            viewport_animation: self.viewport_animation.clone(),
+            color_tweens: self.color_tweens.clone(),
+            name_registry: self.name_registry.clone(),
+            clip_region: self.clip_region.clone(),
+            physics_friction: self.physics_friction.clone(),
+            gravity: self.gravity.clone(),
+            bounce_coefficient: self.bounce_coefficient.clone(),
+            spatial_index: self.spatial_index.clone(),
+            use_quadtree: self.use_quadtree.clone(),
+            gamepad_camera_control: self.gamepad_camera_control.clone(),
+            target_fps: self.target_fps.clone(),
+            render_scale: self.render_scale.clone(),
+            global_time: self.global_time.clone(),
+            last_cache_metrics_log: self.last_cache_metrics_log.clone(),
+            timed_texts: self.timed_texts.clone(),
+            selection_color: self.selection_color.clone(),
+            composite_operation: self.composite_operation.clone(),
+            render_filter: self.render_filter.clone(),
+            profiler: self.profiler.clone(),
+            dragged_object: self.dragged_object.clone(),
+            drag_offset: self.drag_offset.clone(),
+        }
+    }
+
     #[wasm_bindgen]
-    pub async fn run(self) -> Result<(), JsValue> {
-        let engine = Rc::new(RefCell::new(self));
+    pub async fn run(&self) -> Result<(), JsValue> {
+        if self.running.get() {
+            return Err(EngineError::AlreadyRunning.into());
+        }
+        self.running.set(true);
+
+        let engine = Rc::new(RefCell::new(self.share()));
         let task_queue = engine.borrow().task_queue.clone();
 
         // Initial data fetch
@@ -95,18 +710,21 @@ impl Rust2DEngine {
             let task_queue = task_queue.clone();
             let window = engine.borrow().window.clone();
 
-            let f: Rc<RefCell<dyn FnMut() -> Result<(), JsValue>>> =
-                Rc::new(RefCell::new(move || {
-                    if let Ok(mut eng) = engine_clone.try_borrow_mut() {
-                        let now = eng.window.performance().unwrap().now();
-                        let delta = now - eng.last_frame_time;
-                        eng.last_frame_time = now;
-                        task_queue.borrow_mut().push_back(EngineTask::UpdateAndRender(delta));
+            let handle = AnimationFrameLoop::start(window, move |timestamp| {
+                if let Ok(mut eng) = engine_clone.try_borrow_mut() {
+                    // The frame timestamp can fall just before the time taken when the
+                    // engine was created.
+                    let delta = (timestamp - eng.last_frame_time).max(0.0);
+                    // Skip this frame; the delta keeps growing until the next one is due.
+                    if !frame_due(eng.target_fps.get(), delta) {
+                        return Ok(());
                     }
-                    Ok(())
-                }));
-
-            animation_frame::request_recursive(window, f)?;
+                    eng.last_frame_time = timestamp;
+                    task_queue.borrow_mut().push_back(EngineTask::UpdateAndRender(delta));
+                }
+                Ok(())
+            })?;
+            *self.animation_handle.borrow_mut() = Some(handle);
         }
 
         // Set up periodic data fetching task (every 20ms)
@@ -115,13 +733,13 @@ impl Rust2DEngine {
             let closure = Closure::wrap(Box::new(move || {
                 task_queue.borrow_mut().push_back(EngineTask::FetchData);
             }) as Box<dyn FnMut()>);
-            window().unwrap()
+            let interval_id = window().unwrap()
                 .set_interval_with_callback_and_timeout_and_arguments_0(
                     closure.as_ref().unchecked_ref(),
                     20,
                 )
                 .unwrap();
-            closure.forget();
+            *self.fetch_interval.borrow_mut() = Some((interval_id, closure));
         }
 
         // Start the task processing loop
@@ -130,10 +748,113 @@ impl Rust2DEngine {
         Ok(())
     }
 
+    /// Cap update+render at `fps` frames per second, skipping animation frames that
+    /// arrive early. Takes effect immediately, including on a running loop.
+    #[wasm_bindgen]
+    pub fn set_target_fps(&self, fps: f64) -> Result<(), JsValue> {
+        if !(fps > 0.0 && fps.is_finite()) {
+            return Err(EngineError::invalid("target fps must be a positive number").into());
+        }
+        self.target_fps.set(Some(fps));
+        Ok(())
+    }
+
+    /// Remove the cap set by `set_target_fps` and render on every animation frame again.
+    #[wasm_bindgen]
+    pub fn clear_target_fps(&self) {
+        self.target_fps.set(None);
+    }
+
+    /// Stop the frame loop, the periodic fetch and the task loop started by `run`.
+    #[wasm_bindgen]
+    pub fn stop(&self) -> Result<(), JsValue> {
+        self.running.set(false);
+
+        if let Some(handle) = self.animation_handle.borrow_mut().take() {
+            handle.stop()?;
+        }
+        if let Some((interval_id, _closure)) = self.fetch_interval.borrow_mut().take() {
+            self.window.clear_interval_with_handle(interval_id);
+        }
+        self.task_queue.borrow_mut().clear();
+
+        Ok(())
+    }
+
+    /// Stop scheduling animation frames without stopping the engine; data keeps loading in
+    /// the background. The first frame after `resume_frame_loop` covers the whole pause, so
+    /// animations jump ahead by it.
+    #[wasm_bindgen]
+    pub fn pause_frame_loop(&self) -> Result<(), JsValue> {
+        match self.animation_handle.borrow().as_ref() {
+            Some(handle) => handle.pause(),
+            None => Ok(()),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_frame_loop(&self) -> Result<(), JsValue> {
+        match self.animation_handle.borrow().as_ref() {
+            Some(handle) => handle.resume(),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the engine is running and its frame loop isn't paused.
+    #[wasm_bindgen]
+    pub fn is_frame_loop_running(&self) -> bool {
+        self.animation_handle
+            .borrow()
+            .as_ref()
+            .is_some_and(|handle| handle.is_running())
+    }
+
+    /// Run `frames` update+render cycles back to back, each advancing the animation by
+    /// one 60 Hz step, and resolve with `{ frames, total_ms, avg_ms, min_ms, max_ms, fps }`.
+    /// The cycles run synchronously, so the regular loop and input handling are held
+    /// off until the benchmark finishes.
+    #[wasm_bindgen]
+    pub fn benchmark_mode(&self, frames: u32) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            if frames == 0 {
+                return Err(EngineError::invalid("benchmark needs at least one frame").into());
+            }
+            engine.fetch_data().await?;
+
+            let performance = engine.window.performance().unwrap();
+            let mut total_ms = 0.0;
+            let mut min_ms = f64::MAX;
+            let mut max_ms = 0.0f64;
+            for _ in 0..frames {
+                let start = performance.now();
+                engine.update(BENCHMARK_FRAME_STEP_MS)?;
+                engine.render()?;
+                let elapsed = performance.now() - start;
+
+                total_ms += elapsed;
+                min_ms = min_ms.min(elapsed);
+                max_ms = max_ms.max(elapsed);
+            }
+
+            let avg_ms = total_ms / frames as f64;
+            let result = BenchmarkResult {
+                frames,
+                total_ms,
+                avg_ms,
+                min_ms,
+                max_ms,
+                fps: if avg_ms > 0.0 { 1000.0 / avg_ms } else { 0.0 },
+            };
+            Ok(serde_wasm_bindgen::to_value(&result)?)
+        })
+    }
 
     fn start_task_loop(engine: Rc<RefCell<Self>>) {
+        let running = engine.borrow().running.clone();
         spawn_local(async move {
-            loop {
+            let mut mouse_was_pressed = false;
+            while running.get() {
                 let task_opt = {
                     let eng_ref = engine.borrow();
                     let mut queue_ref = eng_ref.task_queue.borrow_mut();
@@ -144,22 +865,37 @@ impl Rust2DEngine {
                     let mut eng = engine.borrow_mut();
                     match task {
                         EngineTask::FetchData => {
+                            eng.profiler.borrow_mut().begin("fetch");
                             if let Err(e) = eng.fetch_data().await {
                                 web_sys::console::error_1(&e);
                             }
+                            eng.profiler.borrow_mut().end("fetch");
+                        }
+                        EngineTask::FetchUrgent => {
+                            eng.profiler.borrow_mut().begin("fetch");
+                            eng.fetch_urgent().await;
+                            eng.profiler.borrow_mut().end("fetch");
                         }
+                        EngineTask::UpdateAndRender(_) if eng.headless.get() => {}
                         EngineTask::UpdateAndRender(delta) => {
+                            eng.input_handler.tick(eng.window.performance().unwrap().now());
                             let mouse_pressed = eng.input_handler.is_mouse_button_pressed(0)
                                 || eng.input_handler.is_mouse_button_pressed(1)
                                 || eng.input_handler.is_mouse_button_pressed(2);
+                            eng.update_drag(mouse_pressed, mouse_was_pressed);
+                            mouse_was_pressed = mouse_pressed;
                             if !mouse_pressed {
+                                eng.profiler.borrow_mut().begin("update");
                                 if let Err(e) = eng.update(delta) {
                                     web_sys::console::error_1(&e);
                                 }
+                                eng.profiler.borrow_mut().end("update");
+                                eng.profiler.borrow_mut().begin("render");
                                 if let Err(e) = eng.render() {
                                     web_sys::console::error_1(&e);
                                 }
-                                Rust2DEngine::update_hit_indices_display("None");
+                                eng.profiler.borrow_mut().end("render");
+                                eng.show_debug(DebugReadout::HitIndices, "None");
                             } else {
                                 let pos = eng.input_handler.get_mouse_position();
                                 let hits = eng.hit_indices(pos.x, pos.y);
@@ -171,10 +907,23 @@ impl Rust2DEngine {
                                         .collect::<Vec<_>>()
                                         .join(", ")
                                 };
-                                Rust2DEngine::update_hit_indices_display(&hits_str);
+                                eng.show_debug(DebugReadout::HitIndices, &hits_str);
+                                // Animation stays paused while the button is held, but a
+                                // dragged object still has to be redrawn as it moves.
+                                if eng.dragged_object.get().is_some() {
+                                    if let Err(e) = eng.render() {
+                                        web_sys::console::error_1(&e);
+                                    }
+                                }
+                            }
+                            eng.frame_times.push(delta);
+                            let fps = eng.average_fps();
+                            eng.show_debug(DebugReadout::Fps, &format!("{:.1} FPS", fps));
+                            if eng.debug_display.borrow().is_enabled(DebugReadout::MouseCoords) {
+                                let pos = eng.input_handler.get_mouse_position();
+                                let world = eng.convert_screen_to_world(pos.x, pos.y);
+                                eng.show_debug(DebugReadout::MouseCoords, &format!("{:.1}, {:.1}", world.x, world.y));
                             }
-                            let fps = if delta > 0.0 { 1000.0 / delta } else { 0.0 };
-                            Rust2DEngine::update_fps_display(fps);
                         }
                     }
                 }
@@ -184,59 +933,368 @@ impl Rust2DEngine {
         });
     }
 
-    pub fn update_hit_indices_display(text: &str) {
-        if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
-            if let Some(el) = doc.get_element_by_id("hit-indices") {
-                el.set_inner_html(text);
-            }
-        }
+    fn show_debug(&self, readout: DebugReadout, text: &str) {
+        self.debug_display.borrow().show(readout, text);
     }
 
-    pub fn update_fps_display(fps: f64) {
-        if let Some(doc) = web_sys::window().and_then(|w| w.document()) {
-            if let Some(el) = doc.get_element_by_id("fps") {
-                el.set_inner_html(&format!("{:.1} FPS", fps));
-            }
+    /// Write the `"fps"`, `"hit-indices"` or `"mouse-coords"` readout into the element with
+    /// id `element_id` on every frame. `fps` and `hit-indices` are on by default, writing to
+    /// the elements with those ids.
+    #[wasm_bindgen]
+    pub fn enable_debug_display(&self, name: &str, element_id: &str) -> Result<(), JsValue> {
+        let readout = DebugReadout::parse(name).map_err(EngineError::InvalidArgument)?;
+        self.debug_display.borrow_mut().enable(readout, element_id);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn disable_debug_display(&self, name: &str) -> Result<(), JsValue> {
+        let readout = DebugReadout::parse(name).map_err(EngineError::InvalidArgument)?;
+        self.debug_display.borrow_mut().disable(readout);
+        Ok(())
+    }
+
+    /// Frame rate averaged over the last `FRAME_HISTORY_SIZE` frames.
+    fn average_fps(&self) -> f64 {
+        if self.frame_times.is_empty() {
+            return 0.0;
         }
+        let (total, count) = self
+            .frame_times
+            .iter()
+            .fold((0.0, 0.0), |(total, count), delta| (total + delta, count + 1.0));
+        let avg_delta = total / count;
+        if avg_delta > 0.0 { 1000.0 / avg_delta } else { 0.0 }
+    }
+
+    /// Resolve on the next animation frame with its timestamp.
+    async fn next_animation_frame(&self) -> Result<f64, JsValue> {
+        let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+            self.window
+                .request_animation_frame(&resolve)
+                .unwrap();
+        });
+        let timestamp = JsFuture::from(promise).await?;
+        Ok(timestamp.as_f64().unwrap_or_default())
     }
 
     async fn fetch_data(&mut self) -> Result<(), JsValue> {
-        let mut objs = self.objects.borrow_mut();
-        for obj in objs.iter_mut() {
-            obj.fetch_data().await?;
+        // Collect the stores up front so the object list is not borrowed across the
+        // IndexedDB awaits, where calls coming in from JS could otherwise hit it.
+        let pending: Vec<_> = self.objects
+            .borrow()
+            .iter()
+            .flat_map(|obj| {
+                let time = obj.current_time();
+                std::iter::once(obj.keyframe_store())
+                    .chain(obj.size_store())
+                    .map(move |store| (store.clone(), time))
+            })
+            .collect();
+        for (store, time) in pending {
+            let _ = store.fetch_data(time).await;
         }
         Ok(())
     }
 
+    /// Load the chunks queued by position lookups that missed the cache.
+    async fn fetch_urgent(&self) {
+        for (track_id, chunk_idx) in self.keyframe_db.take_urgent_fetches() {
+            let store = self.shared_stores.borrow().get(&track_id).cloned().or_else(|| {
+                self.objects.borrow().iter().find_map(|obj| {
+                    std::iter::once(obj.keyframe_store())
+                        .chain(obj.size_store())
+                        .find(|store| store.object_id() == track_id)
+                        .cloned()
+                })
+            });
+            if let Some(store) = store {
+                let _ = store.fetch_chunk(chunk_idx).await;
+            }
+        }
+    }
+
     fn update(&mut self, delta_time: f64) -> Result<(), JsValue>{
+        self.global_time.set(self.global_time.get() + delta_time);
+        if cfg!(debug_assertions) {
+            self.log_cache_metrics();
+        }
+
+        if self.gamepad_camera_control.get() {
+            self.input_handler.poll_gamepad()?;
+            let (x, y) = self.camera_offset.get();
+            let step = GAMEPAD_PAN_SPEED * delta_time;
+            self.camera_offset.set((
+                x + self.input_handler.get_gamepad_axis(0, 0) * step,
+                y + self.input_handler.get_gamepad_axis(0, 1) * step,
+            ));
+        } else {
+            // Pages outside a secure context have no Gamepad API; only camera control needs it.
+            let _ = self.input_handler.poll_gamepad();
+        }
+        self.advance_viewport_animation(delta_time)?;
+
+        let (gx, gy) = {
+            let gravity = self.gravity.borrow();
+            (gravity.x, gravity.y)
+        };
+        let (camera_x, camera_y) = self.camera_offset.get();
+        let walls = self.viewport.to_world(camera_x, camera_y, self.camera_zoom.get());
+        let bounce = self.bounce_coefficient.get();
         let mut objs = self.objects.borrow_mut();
         for obj in objs.iter_mut() {
+            obj.apply_gravity(gx, gy, delta_time);
+            obj.update(delta_time)?;
+            obj.bounce_within(&walls, bounce);
+        }
+        self.apply_constraints(&mut objs, delta_time);
+        let finished_tweens = self.advance_color_tweens(&mut objs, delta_time);
+        if self.keyframe_db.has_urgent_fetches() {
+            let mut queue = self.task_queue.borrow_mut();
+            if !matches!(queue.front(), Some(EngineTask::FetchUrgent)) {
+                queue.push_front(EngineTask::FetchUrgent);
+            }
+        }
+
+        if let Some(heat_map) = self.heat_map.borrow_mut().as_mut() {
+            for obj in objs.iter() {
+                let center = obj.center();
+                heat_map.record(center.x, center.y);
+            }
+        }
+        drop(objs);
+        self.notify_watchers()?;
+        for object_id in finished_tweens {
+            let init = web_sys::CustomEventInit::new();
+            init.set_detail(&JsValue::from(object_id));
+            let event = web_sys::CustomEvent::new_with_event_init_dict("color_tween_complete", &init)?;
+            self.canvas.dispatch_event(&event)?;
+        }
+
+        for obj in self.lazy_objects.borrow_mut().iter_mut() {
             obj.update(delta_time)?;
         }
+
+        if self.auto_cursor.get() {
+            let pos = self.input_handler.get_mouse_position();
+            let cursor = if self.hit_indices(pos.x, pos.y).is_empty() { "default" } else { "pointer" };
+            self.set_canvas_cursor(cursor)?;
+        }
         Ok(())
     }
 
+    /// Call the callback of every watcher whose object moved past its threshold. Callbacks
+    /// run after the object borrow is released, so they may call back into the engine.
+    fn notify_watchers(&self) -> Result<(), JsValue> {
+        let triggered: Vec<(js_sys::Function, u32, f64, f64)> = {
+            let objs = self.objects.borrow();
+            let mut watchers = self.watchers.borrow_mut();
+            watchers
+                .iter_mut()
+                .filter_map(|watcher| {
+                    let obj = objs.iter().find(|obj| obj.object_id() == watcher.object_id)?;
+                    let (x, y) = (obj.current_x(), obj.current_y());
+                    watcher
+                        .check(x, y)
+                        .then(|| (watcher.callback.clone(), watcher.object_id, x, y))
+                })
+                .collect()
+        };
+        for (callback, object_id, x, y) in triggered {
+            callback.call3(&JsValue::NULL, &object_id.into(), &x.into(), &y.into())?;
+        }
+        Ok(())
+    }
+
+    /// Correct positions (fixed distances) and velocities (springs) for every constraint
+    /// whose objects both still exist.
+    fn apply_constraints(&self, objs: &mut ObjectPool<SquareObject>, delta_time: f64) {
+        for (object_a, object_b, constraint) in self.constraints.borrow().iter() {
+            let state = |id: u32| {
+                objs.iter()
+                    .find(|obj| obj.object_id() == id)
+                    .map(|obj| (obj.center(), obj.constraint_velocity()))
+            };
+            let (Some((center_a, velocity_a)), Some((center_b, velocity_b))) = (state(*object_a), state(*object_b)) else {
+                continue;
+            };
+            let distance = center_a.distance(&center_b);
+            if distance == 0.0 {
+                continue;
+            }
+            // Unit vector from a towards b.
+            let axis = Vector2::new((center_b.x - center_a.x) / distance, (center_b.y - center_a.y) / distance);
+
+            for obj in objs.iter_mut() {
+                let sign = if obj.object_id() == *object_a {
+                    1.0
+                } else if obj.object_id() == *object_b {
+                    -1.0
+                } else {
+                    continue;
+                };
+                match constraint {
+                    Constraint::FixedDistance { target } => {
+                        let shift = (distance - target) / 2.0 * sign;
+                        obj.nudge(axis.x * shift, axis.y * shift);
+                    }
+                    Constraint::SpringDamper { rest_length, stiffness, damping } => {
+                        let separating_speed = Vector2::new(velocity_b.x - velocity_a.x, velocity_b.y - velocity_a.y).dot(&axis);
+                        let accel = (stiffness * (distance - rest_length) + damping * separating_speed) * sign;
+                        obj.accelerate(axis.x * accel, axis.y * accel, delta_time);
+                    }
+                }
+            }
+        }
+    }
+
     fn render(&mut self) -> Result<(), JsValue> {
+        // Offscreen targets (GIF export) are sized in world pixels and drawn at 1:1.
+        let scale = if self.context.canvas().as_ref() == Some(&self.canvas) {
+            self.render_scale.get()
+        } else {
+            1.0
+        };
+        self.context.set_transform(scale, 0.0, 0.0, scale, 0.0, 0.0)?;
+
+        self.run_render_hooks(RenderPhase::BeforeBackground)?;
         let bg_color = JsValue::from_str("#6C5B7B");
         self.context.set_fill_style(&bg_color);
         self.context
             .fill_rect(0.0, 0.0, self.window_width as f64, self.window_height as f64);
-        let objs = self.objects.get_mut();
-        for obj in objs.iter_mut() {
-            let bbox = AABB::new(
-                    obj.current_x(), 
-                    obj.current_y(), 
-                    obj.current_x() + obj.get_size(),
-                    obj.current_y() + obj.get_size(),
-                );
-            if !bbox.intersects(&self.viewport) {
-                continue;
+
+        let (camera_x, camera_y) = self.camera_offset.get();
+        let zoom = self.camera_zoom.get();
+        if let Some(background) = self.background.borrow().as_ref() {
+            background.render(&self.context, self.window_width, self.window_height, (camera_x, camera_y), zoom)?;
+        }
+        self.run_render_hooks(RenderPhase::AfterBackground)?;
+        // Restore even when a hook or object fails, so the camera transform and clip don't leak.
+        self.context.save();
+        let drawn = self.render_world(camera_x, camera_y, zoom);
+        self.context.restore();
+        drawn?;
+
+        // Overlays are drawn in screen space, unaffected by the camera.
+        self.run_render_hooks(RenderPhase::BeforeOverlay)?;
+        let now = self.global_time.get();
+        for text in self.timed_texts.borrow().iter().filter(|text| text.is_active(now)) {
+            text.render(&self.context)?;
+        }
+        self.run_render_hooks(RenderPhase::AfterOverlay)?;
+        Ok(())
+    }
+
+    /// Draw the objects and world-space hooks under the camera transform. Must be inside a
+    /// save/restore pair.
+    fn render_world(&self, camera_x: f64, camera_y: f64, zoom: f64) -> Result<(), JsValue> {
+        let visible = self.viewport.to_world(camera_x, camera_y, zoom);
+        self.apply_clip_region();
+        self.context.scale(zoom, zoom)?;
+        self.context.translate(-camera_x, -camera_y)?;
+        self.run_render_hooks(RenderPhase::BeforeObjects)?;
+        self.context.set_global_composite_operation(&self.composite_operation.borrow())?;
+        self.context.set_filter(&self.render_filter.borrow());
+        self.render_objects(&visible)?;
+        self.context.set_global_composite_operation("source-over")?;
+        self.context.set_filter("none");
+        // The object borrow has been released, so hooks can call back into the engine.
+        self.run_render_hooks(RenderPhase::AfterObjects)?;
+
+        if let Some(heat_map) = self.heat_map.borrow().as_ref() {
+            heat_map.render(&self.context)?;
+        }
+        Ok(())
+    }
+
+    fn render_objects(&self, visible: &AABB) -> Result<(), JsValue> {
+        let objs = self.objects.borrow();
+        let ordered = draw_order(&objs);
+        if self.use_quadtree.get() {
+            let index = SpatialIndex::build(&ordered);
+            let mut visible_ranks = index.query_ranks(visible).into_iter().peekable();
+            for (rank, obj) in ordered.iter().enumerate() {
+                if visible_ranks.next_if_eq(&rank).is_some() {
+                    obj.render(&self.context)?;
+                }
+            }
+            *self.spatial_index.borrow_mut() = Some(index);
+        } else {
+            for obj in ordered.iter() {
+                if !obj.bounds().intersects(visible) {
+                    continue;
+                }
+                obj.render(&self.context)?;
+            }
+        }
+        for obj in self.lazy_objects.borrow().iter() {
+            if obj.bounds().intersects(visible) {
+                obj.render(&self.context);
+            }
+        }
+        Ok(())
+    }
+
+    /// Call the hooks registered for `phase` with the 2D context, in registration order.
+    fn run_render_hooks(&self, phase: RenderPhase) -> Result<(), JsValue> {
+        // Cloned so a hook can register or unregister hooks while running.
+        let hooks: Vec<js_sys::Function> = match self.render_hooks.borrow().get(&phase) {
+            Some(hooks) => hooks.iter().map(|(_, hook)| hook.clone()).collect(),
+            None => return Ok(()),
+        };
+        for hook in hooks {
+            hook.call1(&JsValue::NULL, &self.context)?;
+        }
+        Ok(())
+    }
+
+    /// Restrict drawing to the clip region, if one is set. Must be inside a save/restore pair.
+    fn apply_clip_region(&self) {
+        let clip_region = self.clip_region.borrow();
+        let Some(region) = clip_region.as_ref() else {
+            return;
+        };
+        self.context.begin_path();
+        match region {
+            ClipRegion::Rect(rect) => self.context.rect(rect.min_x(), rect.min_y(), rect.width(), rect.height()),
+            ClipRegion::Polygon(points) => {
+                for (i, (x, y)) in points.iter().enumerate() {
+                    if i == 0 {
+                        self.context.move_to(*x, *y);
+                    } else {
+                        self.context.line_to(*x, *y);
+                    }
+                }
+                self.context.close_path();
             }
-            obj.render(&self.context)?;
         }
+        self.context.clip();
+    }
+
+    /// Only draw objects inside the screen-space rectangle (x, y, w, h). Overlays such as
+    /// timed text are not clipped.
+    #[wasm_bindgen]
+    pub fn set_clip_region(&self, x: f64, y: f64, w: f64, h: f64) {
+        *self.clip_region.borrow_mut() = Some(ClipRegion::Rect(AABB::new(x, y, x + w, y + h)));
+    }
+
+    /// Like `set_clip_region`, but clip to a polygon given as flat `[x0, y0, x1, y1, ...]`
+    /// screen coordinates.
+    #[wasm_bindgen]
+    pub fn set_clip_path_polygon(&self, points: &[f64]) -> Result<(), JsValue> {
+        if points.len() < 6 || !points.len().is_multiple_of(2) {
+            return Err(EngineError::invalid("Clip polygon needs at least three x, y pairs").into());
+        }
+        let points = points.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+        *self.clip_region.borrow_mut() = Some(ClipRegion::Polygon(points));
         Ok(())
     }
 
+    #[wasm_bindgen]
+    pub fn clear_clip_region(&self) {
+        *self.clip_region.borrow_mut() = None;
+    }
+
     fn get_window_inner_size(window: &Window) -> (u32, u32) {
         let width = window
             .inner_width()
@@ -255,19 +1313,24 @@ impl Rust2DEngine {
         (width, height)
     }
 
+    /// Ids of the objects under the screen point (x, y), in draw order, topmost last.
     pub fn hit_indices(&self, x: f64, y: f64) -> Vec<u32> {
+        let Vector2 { x, y } = self.convert_screen_to_world(x, y);
+        if self.use_quadtree.get() {
+            if let Some(index) = self.spatial_index.borrow().as_ref() {
+                return index
+                    .query_ranks(&AABB::new(x, y, x, y))
+                    .into_iter()
+                    .map(|rank| index.ids[rank])
+                    .collect();
+            }
+        }
         let objs = self.objects.borrow();
-        
-        objs.iter()
+
+        draw_order(&objs)
+            .into_iter()
             .filter_map(|obj| {
-                let bbox = AABB::new(
-                    obj.current_x(), 
-                    obj.current_y(), 
-                    obj.current_x() + obj.get_size(),
-                    obj.current_y() + obj.get_size(),
-                );
-                
-                if bbox.contains_point(x, y) {
+                if obj.hit_bounds().contains_point(x, y) {
                     Some(obj.object_id())
                 } else {
                     None
@@ -276,112 +1339,2640 @@ impl Rust2DEngine {
             .collect()
     }
 
+    /// Choose how culling and hit testing find objects: `"flat"` checks every object,
+    /// `"quadtree"` queries a tree rebuilt each render, so hit tests see positions as of
+    /// the last drawn frame.
     #[wasm_bindgen]
-    pub async fn generate_objects(
-        &mut self,
-        total_objects: u32,
-        frames_per_object: u32,
-        size: f64,
-    ) -> Result<(), JsValue> {
-        let (width, height) = Rust2DEngine::get_window_inner_size(&self.window);
-        let width_f32 = width as f32;
-        let height_f32 = height as f32;
-        let size_f32 = size as f32;
-
-        let window = web_sys::window().unwrap();
-        let document = window.document().unwrap();
-        let loading_el = document.get_element_by_id("loading").unwrap();
-
-        let rng = js_sys::Math::random;
+    pub fn set_spatial_index(&self, mode: &str) -> Result<(), JsValue> {
+        let use_quadtree = match mode {
+            "flat" => false,
+            "quadtree" => true,
+            other => {
+                return Err(EngineError::invalid(format!(
+                    "Unknown spatial index '{}', expected 'flat' or 'quadtree'",
+                    other
+                ))
+                .into())
+            }
+        };
+        self.use_quadtree.set(use_quadtree);
+        if !use_quadtree {
+            *self.spatial_index.borrow_mut() = None;
+        }
+        Ok(())
+    }
 
-        for idx in 0..total_objects {
-            {
-                let promise = js_sys::Promise::new(&mut |resolve, _reject| {
-                    self.window
-                        .request_animation_frame(&resolve)
-                        .unwrap();
-                });
-                JsFuture::from(promise).await?;
+    /// Ids of the objects whose centre lies within `radius` of the screen point (x, y),
+    /// nearest first.
+    #[wasm_bindgen]
+    pub fn find_objects_near(&self, x: f64, y: f64, radius: f64) -> Vec<u32> {
+        let radius_sq = radius * radius;
+        let mut near: Vec<(f64, u32)> = self.objects_by_distance(x, y)
+            .into_iter()
+            .filter(|(distance_sq, _)| *distance_sq <= radius_sq)
+            .collect();
+        near.sort_by(|a, b| a.0.total_cmp(&b.0));
+        near.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Id of the object whose centre is closest to the screen point (x, y).
+    #[wasm_bindgen]
+    pub fn find_nearest_object(&self, x: f64, y: f64) -> Option<u32> {
+        self.objects_by_distance(x, y)
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, id)| id)
+    }
+
+    /// Squared distance from the screen point to every object's centre, unsorted.
+    fn objects_by_distance(&self, x: f64, y: f64) -> Vec<(f64, u32)> {
+        let Vector2 { x, y } = self.convert_screen_to_world(x, y);
+        let point = Vector2::new(x, y);
+        self.objects
+            .borrow()
+            .iter()
+            .map(|obj| (obj.center().distance_squared(&point), obj.object_id()))
+            .collect()
+    }
+
+    /// Objects whose hit bounds the ray from the world point (ox, oy) along (dx, dy) meets
+    /// within `max_dist`, nearest first, as `[{ objectId, distance, hitX, hitY }]`. The
+    /// direction needn't be normalised; a ray starting inside an object hits it at distance 0.
+    /// With the quadtree enabled, only objects near the ray in the last drawn frame are tested.
+    #[wasm_bindgen]
+    pub fn raycast(&self, ox: f64, oy: f64, dx: f64, dy: f64, max_dist: f64) -> Result<JsValue, JsValue> {
+        let length = dx.hypot(dy);
+        if length == 0.0 || !length.is_finite() {
+            return Err(EngineError::invalid("ray direction must be a finite, non-zero vector").into());
+        }
+        if max_dist.is_nan() || max_dist < 0.0 {
+            return Err(EngineError::invalid("max_dist must not be negative").into());
+        }
+        let (dx, dy) = (dx / length, dy / length);
+
+        let candidates: Option<HashSet<u32>> = match self.spatial_index.borrow().as_ref() {
+            Some(index) if self.use_quadtree.get() && max_dist.is_finite() => {
+                let (ex, ey) = (ox + dx * max_dist, oy + dy * max_dist);
+                let region = AABB::new(ox.min(ex), oy.min(ey), ox.max(ex), oy.max(ey));
+                Some(index.query_ranks(&region).into_iter().map(|rank| index.ids[rank]).collect())
             }
+            _ => None,
+        };
 
-            let progress_ratio = (idx + 1) as f32 / total_objects as f32;
-            let percentage = progress_ratio * 100.0;
-            let progress_text = format!(
-                "Creating objects: {} / {} ({:.1}%)",
-                idx + 1,
-                total_objects,
-                percentage
-            );
+        let mut hits: Vec<RaycastHit> = self
+            .objects
+            .borrow()
+            .iter()
+            .filter(|obj| candidates.as_ref().is_none_or(|ids| ids.contains(&obj.object_id())))
+            .filter_map(|obj| {
+                let distance = obj.hit_bounds().intersect_ray(ox, oy, dx, dy)?;
+                (distance <= max_dist).then(|| RaycastHit {
+                    object_id: obj.object_id(),
+                    distance,
+                    hit_x: ox + dx * distance,
+                    hit_y: oy + dy * distance,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        Ok(serde_wasm_bindgen::to_value(&hits)?)
+    }
+
+    /// Topmost object under the point. Hits come back in draw order, so the last one is
+    /// on top.
+    #[wasm_bindgen]
+    pub fn object_at_point(&self, x: f64, y: f64) -> Option<u32> {
+        self.hit_indices(x, y).last().copied()
+    }
 
-            loading_el.set_inner_html(&progress_text);
+    /// `take_object_snapshot` of the topmost object under the point, or `null` if there is
+    /// none.
+    #[wasm_bindgen]
+    pub fn get_object_at_point(&self, x: f64, y: f64) -> Result<JsValue, JsValue> {
+        match self.object_at_point(x, y) {
+            Some(object_id) => self.take_object_snapshot(object_id),
+            None => Ok(JsValue::NULL),
+        }
+    }
 
-            let object_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
-            let chunk_size = 10_000.0 + (rng() as f32 * 310.0).floor() * 100.0;
-
-            let color = format!("#{:06x}", (rng() * 0xFFFFFF as f64).floor() as u32);
-            let mut chunks: Vec<KeyframeChunk> = Vec::new();
-
-            let mut current_chunk: Vec<Keyframe> = Vec::new();
-            let mut current_start_time = 0.0f32;
-
-            let mut t = 0.0f32;
-            let x0 = rng() as f32 * (width_f32 - size_f32);
-            let y0 = rng() as f32 * (height_f32 - size_f32);
-            current_chunk.push(Keyframe::new(t, x0, y0));
-
-            for _ in 0..frames_per_object {
-                t += rng() as f32 * 1000.0;
-                let x = rng() as f32 * (width_f32 - size_f32);
-                let y = rng() as f32 * (height_f32 - size_f32);
-                let keyframe = Keyframe::new(t, x, y);
-
-                if t >= current_start_time + chunk_size {
-                    let chunk = KeyframeChunk::new(
-                        &format!("{}_{}", object_id, (current_start_time / chunk_size).floor() as u32),
-                        current_chunk.first().unwrap().time(),
-                        current_chunk.last().unwrap().time(),
-                        current_chunk,
-                    );
-                    chunks.push(chunk);
-
-                    current_chunk = Vec::new();
-                    current_start_time += chunk_size;
+    /// `{ objectId, x, y, width, height, color, currentTime, selected, paused, renderOrder }`
+    /// for one object; `renderOrder` is `undefined` unless set.
+    #[wasm_bindgen]
+    pub fn take_object_snapshot(&self, object_id: u32) -> Result<JsValue, JsValue> {
+        let snapshot = self.with_object(object_id, |obj| ObjectSnapshot::from(obj))?;
+        Ok(serde_wasm_bindgen::to_value(&snapshot)?)
+    }
+
+    /// Like `object_at_point`, but confirms each candidate by drawing it alone to a
+    /// 1x1 offscreen canvas and checking the alpha of the pixel under the point.
+    #[wasm_bindgen]
+    pub fn object_at_point_strict(&self, x: f64, y: f64) -> Result<Option<u32>, JsValue> {
+        let candidates = self.hit_indices(x, y);
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let probe_ctx = self.create_detached_context(1, 1)?;
+        let Vector2 { x, y } = self.convert_screen_to_world(x, y);
+
+        let objs = self.objects.borrow();
+        for id in candidates.iter().rev() {
+            let Some(obj) = objs.iter().find(|obj| obj.object_id() == *id) else {
+                continue;
+            };
+
+            probe_ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)?;
+            probe_ctx.clear_rect(0.0, 0.0, 1.0, 1.0);
+            probe_ctx.translate(-x.floor(), -y.floor())?;
+            obj.render(&probe_ctx)?;
+
+            let pixel = probe_ctx.get_image_data(0.0, 0.0, 1.0, 1.0)?.data();
+            if pixel[3] > 0 {
+                return Ok(Some(*id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// CSS `rgba(r, g, b, a)` of the canvas pixel at screen point (x, y).
+    /// Reading pixels fails if the canvas has been tainted by cross-origin images.
+    #[wasm_bindgen]
+    pub fn pick_color_at(&self, x: f64, y: f64) -> Result<String, JsValue> {
+        let scale = self.render_scale.get();
+        let pixel = self.context.get_image_data((x * scale).floor(), (y * scale).floor(), 1.0, 1.0)?.data();
+        Ok(Self::css_rgba(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64, pixel[3] as f64))
+    }
+
+    #[wasm_bindgen]
+    pub fn pick_color_under_cursor(&self) -> Result<String, JsValue> {
+        let pos = self.input_handler.get_mouse_position();
+        self.pick_color_at(pos.x, pos.y)
+    }
+
+    /// Average colour of the canvas pixels within `radius` of screen point (x, y), as
+    /// `rgba(r, g, b, a)`. Pixels off the canvas read as transparent black. The same
+    /// cross-origin precondition as `pick_color_at` applies.
+    #[wasm_bindgen]
+    pub fn pick_color_average_region(&self, x: f64, y: f64, radius: f64) -> Result<String, JsValue> {
+        if radius < 0.0 {
+            return Err(EngineError::invalid("radius must not be negative").into());
+        }
+        let scale = self.render_scale.get();
+        let (x, y, radius) = (x * scale, y * scale, radius * scale);
+        let left = (x - radius).floor();
+        let top = (y - radius).floor();
+        let side = (radius * 2.0).ceil() + 1.0;
+        let data = self.context.get_image_data(left, top, side, side)?.data();
+
+        let side = side as u32;
+        let mut sum = [0.0f64; 4];
+        let mut count = 0u32;
+        for row in 0..side {
+            for col in 0..side {
+                let dx = left + f64::from(col) + 0.5 - x;
+                let dy = top + f64::from(row) + 0.5 - y;
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+                let offset = (row * side + col) as usize * 4;
+                for (channel, total) in sum.iter_mut().enumerate() {
+                    *total += data[offset + channel] as f64;
                 }
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            // Radius below half a pixel: no pixel centre is inside, use the one under (x, y).
+            let offset = ((y - top) as usize * side as usize + (x - left) as usize) * 4;
+            return Ok(Self::css_rgba(
+                data[offset] as f64,
+                data[offset + 1] as f64,
+                data[offset + 2] as f64,
+                data[offset + 3] as f64,
+            ));
+        }
+        let n = count as f64;
+        Ok(Self::css_rgba(sum[0] / n, sum[1] / n, sum[2] / n, sum[3] / n))
+    }
+
+    /// Format 0-255 channel values as a CSS colour; alpha is converted to the 0-1 range.
+    fn css_rgba(r: f64, g: f64, b: f64, a: f64) -> String {
+        format!("rgba({}, {}, {}, {})", r.round(), g.round(), b.round(), (a / 255.0 * 1000.0).round() / 1000.0)
+    }
+
+    /// Map a canvas position (e.g. from a mouse event) to world space through the current
+    /// camera offset and zoom.
+    #[wasm_bindgen]
+    pub fn convert_screen_to_world(&self, sx: f64, sy: f64) -> Vector2 {
+        screen_to_world(sx, sy, self.camera_offset.get(), self.camera_zoom.get())
+    }
+
+    /// Inverse of `convert_screen_to_world`: where a world position appears on the canvas.
+    #[wasm_bindgen]
+    pub fn convert_world_to_screen(&self, wx: f64, wy: f64) -> Vector2 {
+        world_to_screen(wx, wy, self.camera_offset.get(), self.camera_zoom.get())
+    }
+
+    /// Point the camera at a world-space region, zooming so all of it is visible and
+    /// centring it along the axis with room to spare.
+    fn show_world_region(&self, region: &AABB) {
+        if region.width() <= 0.0 || region.height() <= 0.0 {
+            return;
+        }
+        let zoom = (self.window_width / region.width()).min(self.window_height / region.height());
+        let center_x = region.min_x() + region.width() / 2.0;
+        let center_y = region.min_y() + region.height() / 2.0;
+        self.camera_zoom.set(zoom);
+        self.camera_offset.set((
+            center_x - self.window_width / zoom / 2.0,
+            center_y - self.window_height / zoom / 2.0,
+        ));
+    }
+
+    /// Smoothly move the camera to show the world region (min_x, min_y)-(max_x, max_y) over
+    /// `duration_ms`, with any easing accepted by `Easing::parse`. A
+    /// `viewport_animation_complete` event is dispatched on the canvas when it arrives.
+    #[wasm_bindgen]
+    pub fn animate_viewport(
+        &self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        duration_ms: f64,
+        easing: &str,
+    ) -> Result<(), JsValue> {
+        if max_x <= min_x || max_y <= min_y {
+            return Err(EngineError::invalid("Viewport must have a positive width and height").into());
+        }
+        let easing = Easing::parse(easing).map_err(EngineError::InvalidArgument)?;
+        let (camera_x, camera_y) = self.camera_offset.get();
+        *self.viewport_animation.borrow_mut() = Some(ViewportAnimation {
+            start: self.viewport.to_world(camera_x, camera_y, self.camera_zoom.get()),
+            target: AABB::new(min_x, min_y, max_x, max_y),
+            elapsed: 0.0,
+            duration: duration_ms,
+            easing,
+        });
+        Ok(())
+    }
+
+    fn advance_viewport_animation(&self, delta_time: f64) -> Result<(), JsValue> {
+        let mut animation = self.viewport_animation.borrow_mut();
+        let Some(anim) = animation.as_mut() else {
+            return Ok(());
+        };
+        anim.elapsed += delta_time;
+        if anim.elapsed < anim.duration {
+            let t = anim.easing.apply(anim.elapsed / anim.duration);
+            self.show_world_region(&anim.start.lerp(&anim.target, t));
+            return Ok(());
+        }
+
+        self.show_world_region(&anim.target);
+        *animation = None;
+        drop(animation);
+        let event = web_sys::Event::new("viewport_animation_complete")?;
+        self.canvas.dispatch_event(&event)?;
+        Ok(())
+    }
+
+    /// Events dispatched on the canvas, for `addEventListener`, as
+    /// `[{ name, description, payloadSchema }]`.
+    #[wasm_bindgen]
+    pub fn list_events(&self) -> Result<JsValue, JsValue> {
+        Ok(serde_wasm_bindgen::to_value(EVENT_DOCS)?)
+    }
+
+    /// Fade an object's colour from its current value to `target_color` (`#rgb` or
+    /// `#rrggbb`) over `duration_ms`, with any easing accepted by `Easing::parse`. A
+    /// `color_tween_complete` event carrying the object id is dispatched on the canvas when
+    /// it finishes. Starting a new tween replaces one already running on the object.
+    #[wasm_bindgen]
+    pub fn tween_object_color(
+        &self,
+        object_id: u32,
+        target_color: &str,
+        duration_ms: f64,
+        easing: &str,
+    ) -> Result<(), JsValue> {
+        if duration_ms.is_nan() || duration_ms <= 0.0 || duration_ms.is_infinite() {
+            return Err(EngineError::invalid("Tween duration must be a positive number of ms").into());
+        }
+        let target = Rgb::parse_hex(target_color).map_err(EngineError::InvalidArgument)?;
+        let easing = Easing::parse(easing).map_err(EngineError::InvalidArgument)?;
+        let current = self.with_object(object_id, |obj| obj.color().to_string())?;
+        let start = Rgb::parse_hex(&current).map_err(|_| {
+            EngineError::invalid(format!(
+                "Object {} has colour '{}', only hex colours can be tweened",
+                object_id, current
+            ))
+        })?;
+        self.color_tweens.borrow_mut().insert(object_id, ColorTween {
+            start,
+            target,
+            elapsed: 0.0,
+            duration: duration_ms,
+            easing,
+        });
+        Ok(())
+    }
+
+    /// Step every colour tween, returning the ids of objects whose tween finished. Tweens on
+    /// objects that were removed are dropped silently.
+    fn advance_color_tweens(&self, objs: &mut ObjectPool<SquareObject>, delta_time: f64) -> Vec<u32> {
+        let mut tweens = self.color_tweens.borrow_mut();
+        if tweens.is_empty() {
+            return Vec::new();
+        }
+        let mut finished = Vec::new();
+        tweens.retain(|&object_id, tween| {
+            let Some(obj) = objs.iter_mut().find(|obj| obj.object_id() == object_id) else {
+                return false;
+            };
+            tween.elapsed += delta_time;
+            let t = tween.easing.apply(tween.elapsed / tween.duration);
+            obj.set_color(&tween.start.lerp(&tween.target, t).to_hex());
+            if tween.elapsed < tween.duration {
+                return true;
+            }
+            finished.push(object_id);
+            false
+        });
+        finished
+    }
+
+    /// 2D context of a canvas that is never attached to the document.
+    fn create_detached_context(&self, width: u32, height: u32) -> Result<CanvasRenderingContext2d, JsValue> {
+        let document = self.window.document().ok_or(EngineError::DocumentNotFound)?;
+        let canvas = document
+            .create_element("canvas")?
+            .dyn_into::<HtmlCanvasElement>()?;
+        canvas.set_width(width);
+        canvas.set_height(height);
+        canvas
+            .get_context("2d")?
+            .ok_or(EngineError::ContextCreationFailed)?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(JsValue::from)
+    }
+
+    /// Create `total_objects` random squares. `progress_callback`, if given, is called after
+    /// each one with `{ created, total, percentage, objectId }`.
+    // Positions and times are computed in f64 like the rest of the engine and narrowed
+    // to the f32 storage format only when each keyframe is built.
+    #[wasm_bindgen]
+    pub async fn generate_objects(
+        &mut self,
+        total_objects: u32,
+        frames_per_object: u32,
+        size: f64,
+        progress_callback: Option<js_sys::Function>,
+    ) -> Result<(), JsValue> {
+        let (width, height) = Rust2DEngine::get_window_inner_size(&self.window);
+        let span_x = f64::from(width) - size;
+        let span_y = f64::from(height) - size;
+
+        for idx in 0..total_objects {
+            self.next_animation_frame().await?;
+
+            let object_id = self.spawn_random_object(frames_per_object, size, size, span_x, span_y).await;
+
+            if let Some(callback) = &progress_callback {
+                let created = idx + 1;
+                let progress = GenerateProgress {
+                    created,
+                    total: total_objects,
+                    percentage: f64::from(created) / f64::from(total_objects) * 100.0,
+                    object_id,
+                };
+                callback.call1(&JsValue::NULL, &serde_wasm_bindgen::to_value(&progress)?)?;
+            }
+        }
+
+        self.fetch_data().await?;
+        Ok(())
+    }
+
+    /// Create a `width` x `height` object in a random colour, moving between random points
+    /// in `[0, span_x] x [0, span_y]` with one keyframe every 0-1000 ms.
+    async fn spawn_random_object(&self, frames: u32, width: f64, height: f64, span_x: f64, span_y: f64) -> u32 {
+        let rng = js_sys::Math::random;
+        let object_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
+        // Whole multiples of 100 ms below 41 000, so exactly representable as f32.
+        let chunk_size = (10_000.0 + (rng() * 310.0).floor() * 100.0) as f32;
+
+        let color = format!("#{:06x}", (rng() * f64::from(0xFFFFFF)).floor() as u32);
+        let keyframes = random_keyframes(frames, span_x, span_y, rng);
+
+        let chunks = keyframe::split_into_chunks(&object_id.to_string(), keyframes, chunk_size);
+
+        let store = SquareObject::create_store(
+            object_id,
+            chunks,
+            chunk_size,
+            Arc::clone(&self.keyframe_db)
+        ).await;
+
+        self.spawn_object(object_id, width, &color, store, 0.0);
+        if width != height {
+            let _ = self.with_object_mut(object_id, |obj| obj.set_dimensions(width, height));
+        }
+        object_id
+    }
+
+    /// Like `generate_objects`, but for `width` x `height` rectangles, without progress
+    /// reporting. Resolves with the new object ids.
+    #[wasm_bindgen]
+    pub fn generate_rects(&self, count: u32, frames: u32, width: f64, height: f64) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            let (window_width, window_height) = Rust2DEngine::get_window_inner_size(&engine.window);
+            let span_x = (f64::from(window_width) - width).max(0.0);
+            let span_y = (f64::from(window_height) - height).max(0.0);
 
-                current_chunk.push(keyframe);
+            let mut ids = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                ids.push(engine.spawn_random_object(frames, width, height, span_x, span_y).await);
             }
+            engine.fetch_data().await?;
+            Ok(serde_wasm_bindgen::to_value(&ids)?)
+        })
+    }
 
-            if !current_chunk.is_empty() {
-                let chunk = KeyframeChunk::new(
-                    &format!("{}_{}", object_id, (current_start_time / chunk_size).floor() as u32),
-                    current_chunk.first().unwrap().time(),
-                    current_chunk.last().unwrap().time(),
-                    current_chunk,
-                );
-                chunks.push(chunk);
+    /// Animate an object's dimensions with a separate track of `{ time, width, height }`
+    /// keyframes, played in step with its position track.
+    #[wasm_bindgen]
+    pub fn set_object_size_track(&self, object_id: u32, keyframes: JsValue) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let samples: Vec<SizeKeyframe> = serde_wasm_bindgen::from_value(keyframes)?;
+            if samples.is_empty() {
+                return Err(EngineError::invalid("a size track needs at least one keyframe").into());
             }
+            engine.with_object(object_id, |_| ())?;
+
+            let mut keyframes: Vec<Keyframe> = samples
+                .iter()
+                .map(|k| Keyframe::new(k.time as f32, k.width as f32, k.height as f32))
+                .collect();
+            keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+            let track_id = Rust2DEngine::size_track_id(object_id);
+            engine.keyframe_db
+                .delete_object_chunks(&track_id)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to replace size track of {}", object_id), e))?;
+            let chunks = keyframe::split_into_chunks(&track_id, keyframes, SHARED_TRACK_CHUNK_SIZE);
+            let total_duration = chunks
+                .iter()
+                .map(|chunk| chunk.end_time())
+                .fold(0.0, f32::max);
+            engine.keyframe_db
+                .save_chunks(chunks)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to save size track of {}", object_id), e))?;
+
+            let store = KeyframeStore::new(
+                track_id,
+                SHARED_TRACK_CHUNK_SIZE,
+                total_duration.into(),
+                Arc::clone(&engine.keyframe_db),
+            );
+            let time = engine.with_object(object_id, |obj| obj.current_time())?;
+            let _ = store.fetch_data(time).await;
+            engine.with_object_mut(object_id, |obj| obj.set_size_store(Some(store)))?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Database key prefix of an object's size track.
+    fn size_track_id(object_id: u32) -> String {
+        format!("size_{}", object_id)
+    }
+
+    /// Serialise every object, including its full keyframe track, to a JSON scene.
+    #[wasm_bindgen]
+    pub async fn export_scene(&self) -> Result<String, JsValue> {
+        let scene = SceneV1 {
+            schema_version: CURRENT_SCENE_VERSION,
+            objects: self.scene_objects().await?,
+        };
+        serde_json::to_string(&scene)
+            .map_err(|e| EngineError::Encoding { context: "Scene serialisation", message: e.to_string() }.into())
+    }
+
+    /// Like `export_scene`, but resolves with an `ArrayBuffer` in the compact binary layout
+    /// described in `binary_scene`. Every object colour must be a hex colour, and keyframe
+    /// easing is not kept.
+    #[wasm_bindgen]
+    pub fn serialize_to_arraybuffer(&self) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let objects = engine.scene_objects().await?;
+            let bytes = binary_scene::encode(&objects)
+                .map_err(|message| EngineError::Encoding { context: "Binary scene serialisation", message })?;
+            Ok(js_sys::Uint8Array::from(bytes.as_slice()).buffer().into())
+        })
+    }
 
-            let square = SquareObject::new(
+    /// Every object with its full keyframe track, as written by the scene exports.
+    async fn scene_objects(&self) -> Result<Vec<SceneObjectV1>, JsValue> {
+        let sources: Vec<_> = self.objects
+            .borrow()
+            .iter()
+            .map(|obj| (obj.object_id(), obj.get_size(), obj.color().to_string(), obj.keyframe_store().clone()))
+            .collect();
+
+        let mut objects = Vec::with_capacity(sources.len());
+        for (object_id, size, color, store) in sources {
+            let keyframes = store
+                .load_all_keyframes()
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to load keyframes for {}", object_id), e))?;
+            objects.push(SceneObjectV1 {
                 object_id,
                 size,
-                &color,
+                color,
+                chunk_size: store.chunk_size(),
+                keyframes,
+            });
+        }
+        Ok(objects)
+    }
+
+    /// Load a scene produced by `export_scene`, migrating older schema versions.
+    /// Imported objects receive fresh ids and are appended to the current scene.
+    #[wasm_bindgen]
+    pub async fn import_scene(&mut self, json: &str) -> Result<Vec<u32>, JsValue> {
+        let scene = SceneDeserialiser::from_json(json)
+            .map_err(EngineError::InvalidArgument)?
+            .into_latest();
+        self.import_scene_objects(scene.objects).await
+    }
+
+    /// Load a scene produced by `serialize_to_arraybuffer`. Imported objects receive fresh
+    /// ids and are appended to the current scene.
+    #[wasm_bindgen]
+    pub async fn deserialize_from_arraybuffer(&mut self, data: js_sys::ArrayBuffer) -> Result<Vec<u32>, JsValue> {
+        let bytes = js_sys::Uint8Array::new(&data).to_vec();
+        let objects = binary_scene::decode(&bytes).map_err(EngineError::InvalidArgument)?;
+        self.import_scene_objects(objects).await
+    }
+
+    async fn import_scene_objects(&mut self, objects: Vec<SceneObjectV2>) -> Result<Vec<u32>, JsValue> {
+        let new_ids = self.spawn_scene_objects(objects).await?;
+
+        let mut missing = Vec::new();
+        for object_id in &new_ids {
+            let stored = self
+                .keyframe_db
+                .object_exists(&object_id.to_string())
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to check stored chunks for {}", object_id), e))?;
+            if !stored {
+                missing.push(object_id.to_string());
+            }
+        }
+        if !missing.is_empty() {
+            web_sys::console::warn_1(&JsValue::from_str(&format!(
+                "Scene import was partial: no keyframes were stored for objects {}",
+                missing.join(", ")
+            )));
+        }
+
+        self.fetch_data().await?;
+        Ok(new_ids)
+    }
+
+    /// Ids of every object and track with keyframe chunks in IndexedDB, including
+    /// shared and size tracks.
+    #[wasm_bindgen]
+    pub async fn list_stored_object_ids(&self) -> Result<Vec<String>, JsValue> {
+        self.keyframe_db
+            .list_all_object_ids()
+            .await
+            .map_err(|e| EngineError::storage("Failed to list stored objects", e).into())
+    }
+
+    /// Whether IndexedDB holds at least one keyframe chunk for `object_id`.
+    #[wasm_bindgen]
+    pub async fn stored_object_exists(&self, object_id: &str) -> Result<bool, JsValue> {
+        self.keyframe_db
+            .object_exists(object_id)
+            .await
+            .map_err(|e| EngineError::storage(format!("Failed to look up stored object {}", object_id), e).into())
+    }
+
+    /// Create objects from a hand-written JSON scene:
+    /// `{ "version": 1, "objects": [{ "size", "color", "keyframes": [{ "time", "x", "y" }],
+    /// "chunk_size"?, "loop_mode"? }] }`. Resolves with the new object ids.
+    #[wasm_bindgen]
+    pub fn load_objects_from_json(&self, json: &str) -> js_sys::Promise {
+        let parsed = SceneSpec::from_json(json).and_then(SceneSpec::into_scene);
+        let mut engine = self.share();
+        future_to_promise(async move {
+            let scene = parsed.map_err(EngineError::InvalidArgument)?;
+            let new_ids = engine.spawn_scene_objects(scene.objects).await?;
+            engine.fetch_data().await?;
+            Ok(serde_wasm_bindgen::to_value(&new_ids)?)
+        })
+    }
+
+    /// Create one object from an array of `{ time, x, y }` keyframes, in any order, and
+    /// register it under `name`. Resolves with the new object's id; rejects if the name is
+    /// empty or already taken.
+    #[wasm_bindgen]
+    pub fn create_named_object(&self, name: String, size: f64, color: String, keyframes: JsValue) -> js_sys::Promise {
+        let parsed = serde_wasm_bindgen::from_value::<Vec<KeyframeSpec>>(keyframes)
+            .map_err(|e| format!("Invalid keyframes: {}", e))
+            .and_then(|keyframes| {
+                SceneSpec {
+                    version: CURRENT_SPEC_VERSION,
+                    objects: vec![SceneObjectSpec { size, color, keyframes, chunk_size: None, loop_mode: None }],
+                }
+                .into_scene()
+            });
+        let mut engine = self.share();
+        future_to_promise(async move {
+            engine.check_name_available(&name, None)?;
+            let scene = parsed.map_err(EngineError::InvalidArgument)?;
+            let object_id = engine.spawn_scene_objects(scene.objects).await?[0];
+            // Another call may have claimed the name while the chunks were being saved.
+            engine.check_name_available(&name, None)?;
+            engine.with_object_mut(object_id, |obj| obj.set_name(Some(name.clone())))?;
+            engine.name_registry.borrow_mut().insert(name, object_id);
+            engine.fetch_data().await?;
+            Ok(JsValue::from(object_id))
+        })
+    }
+
+    /// Id of the object registered under `name`, if any.
+    #[wasm_bindgen]
+    pub fn find_object_by_name(&self, name: &str) -> Option<u32> {
+        self.name_registry.borrow().get(name).copied()
+    }
+
+    /// Give an object a new unique name, freeing its old one. Fails if another object
+    /// already has the name.
+    #[wasm_bindgen]
+    pub fn set_object_name(&self, object_id: u32, name: &str) -> Result<(), JsValue> {
+        self.check_name_available(name, Some(object_id))?;
+        let old_name = self.with_object_mut(object_id, |obj| {
+            let old_name = obj.name().map(str::to_string);
+            obj.set_name(Some(name.to_string()));
+            old_name
+        })?;
+        let mut registry = self.name_registry.borrow_mut();
+        if let Some(old_name) = old_name {
+            registry.remove(&old_name);
+        }
+        registry.insert(name.to_string(), object_id);
+        Ok(())
+    }
+
+    /// Name the object was registered under, if any.
+    #[wasm_bindgen]
+    pub fn get_object_name(&self, object_id: u32) -> Result<Option<String>, JsValue> {
+        self.with_object(object_id, |obj| obj.name().map(str::to_string))
+    }
+
+    /// `name` may be given to `owner` (or to a new object if `None`): it isn't empty and
+    /// no other object holds it.
+    fn check_name_available(&self, name: &str, owner: Option<u32>) -> Result<(), EngineError> {
+        if name.is_empty() {
+            return Err(EngineError::invalid("object name must not be empty"));
+        }
+        match self.name_registry.borrow().get(name) {
+            Some(&id) if Some(id) != owner => {
+                Err(EngineError::invalid(format!("name '{}' is already used by object {}", name, id)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Persist each object's keyframes under a fresh id and add it to the scene.
+    async fn spawn_scene_objects(&self, objects: Vec<SceneObjectV2>) -> Result<Vec<u32>, JsValue> {
+        let mut new_ids = Vec::with_capacity(objects.len());
+        for obj in objects {
+            let object_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
+            let chunks = keyframe::split_into_chunks(&object_id.to_string(), obj.keyframes, obj.chunk_size);
+            let store = SquareObject::create_store(
+                object_id,
                 chunks,
-                chunk_size,
+                obj.chunk_size,
                 Arc::clone(&self.keyframe_db)
             ).await;
 
-            self.objects.borrow_mut().push(square);
+            self.spawn_object(object_id, obj.size, &obj.color, store, 0.0);
+            new_ids.push(object_id);
         }
+        Ok(new_ids)
+    }
 
-        loading_el.set_inner_html("Preprocessing...");
+    /// Store a keyframe track (an array of `{ time, x, y }`) once so that any number of
+    /// objects can play it via `add_instanced_object`. Resolves with the track id; rejects
+    /// a track without at least two keyframes at different times.
+    #[wasm_bindgen]
+    pub async fn create_shared_track(&self, keyframes: JsValue) -> Result<String, JsValue> {
+        let mut keyframes: Vec<Keyframe> = serde_wasm_bindgen::from_value(keyframes)?;
+        keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+        match (keyframes.first(), keyframes.last()) {
+            (Some(first), Some(last)) if last.time() > first.time() => {}
+            _ => {
+                return Err(EngineError::invalid(
+                    "a shared track needs at least two keyframes spanning a positive time",
+                )
+                .into())
+            }
+        }
 
-        let engine = Rc::new(RefCell::new(self));
-        {
-            let engine_clone = engine.clone();
-            engine_clone.borrow_mut().fetch_data().await?;
+        let track_id = format!("track_{}", NEXT_TRACK_INDEX.fetch_add(1, Ordering::SeqCst));
+        let chunks = keyframe::split_into_chunks(&track_id, keyframes, SHARED_TRACK_CHUNK_SIZE);
+        let total_duration = chunks
+            .iter()
+            .map(|chunk| chunk.end_time())
+            .fold(0.0, f32::max);
+        self.keyframe_db
+            .save_chunks(chunks)
+            .await
+            .map_err(|e| EngineError::storage(format!("Failed to save track {}", track_id), e))?;
+
+        let store = KeyframeStore::new(
+            track_id.clone(),
+            SHARED_TRACK_CHUNK_SIZE,
+            total_duration.into(),
+            Arc::clone(&self.keyframe_db),
+        );
+        self.shared_stores.borrow_mut().insert(track_id.clone(), store);
+        Ok(track_id)
+    }
+
+    /// Extents of the square as `{ minX, minY, maxX, maxY, width, height }`: either where
+    /// it is now, or, with `over_duration`, everywhere it goes over its whole track.
+    #[wasm_bindgen]
+    pub fn compute_object_bounding_rect(&self, object_id: u32, over_duration: bool) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let (x, y, width, height, store) = engine.with_object(object_id, |obj| {
+                (obj.current_x(), obj.current_y(), obj.width(), obj.height(), obj.keyframe_store().clone())
+            })?;
+
+            let bounds = if over_duration {
+                let keyframes = store
+                    .load_all_keyframes()
+                    .await
+                    .map_err(|e| EngineError::storage(format!("Failed to load keyframes for {}", object_id), e))?;
+                AABB::from_points(keyframes.iter().map(|k| (k.x() as f64, k.y() as f64)))
+                    .ok_or(EngineError::NoKeyframes(object_id))?
+            } else {
+                AABB::new(x, y, x, y)
+            };
+            // Keyframes place the top-left corner, so grow by the object's current dimensions.
+            let bounds = AABB::new(bounds.min_x(), bounds.min_y(), bounds.max_x() + width, bounds.max_y() + height);
+
+            Ok(serde_wasm_bindgen::to_value(&BoundingRect::from(&bounds))?)
+        })
+    }
+
+    /// Copy an object's size, colour and full keyframe track to a new object that starts
+    /// `time_offset` ms into the animation (wrapped into the track length). Resolves with
+    /// the new object's id.
+    #[wasm_bindgen]
+    pub fn duplicate_object(&self, object_id: u32, time_offset: f64) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let (width, height) = engine.with_object(object_id, |obj| (obj.width(), obj.height()))?;
+            let new_id = engine.copy_object_track(object_id, time_offset).await?;
+            engine.with_object_mut(new_id, |obj| obj.set_dimensions(width, height))?;
+            Ok(JsValue::from(new_id))
+        })
+    }
+
+    /// Like `duplicate_object`, but also copies the source's size track, hit padding, chunk
+    /// eviction and physics settings. The clone starts at the source's current time unless
+    /// `time_offset` is given. Resolves with the new object's id.
+    #[wasm_bindgen]
+    pub fn clone_object(&self, source_id: u32, time_offset: Option<f64>) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let (settings, current_time) = engine
+                .with_object(source_id, |obj| (obj.settings(), obj.current_time()))
+                .map_err(|_| EngineError::invalid(format!("Cannot clone object {}: it does not exist", source_id)))?;
+
+            let new_id = engine.copy_object_track(source_id, time_offset.unwrap_or(current_time)).await?;
+            engine.with_object_mut(new_id, |obj| obj.apply_settings(settings))?;
+            Ok(JsValue::from(new_id))
+        })
+    }
+
+    /// Length in pixels of the object's whole path, estimated from `samples` (at least 2)
+    /// positions at uniform times along the track.
+    #[wasm_bindgen]
+    pub async fn get_object_path_length(&self, object_id: u32, samples: u32) -> Result<f64, JsValue> {
+        if samples < 2 {
+            return Err(EngineError::invalid("Path length needs at least 2 samples").into());
+        }
+        let store = self.object_store(object_id)?;
+        let keyframes = store
+            .load_all_keyframes()
+            .await
+            .map_err(|e| EngineError::storage(format!("Failed to load keyframes for {}", object_id), e))?;
+        if keyframes.is_empty() {
+            return Err(EngineError::NoKeyframes(object_id).into());
+        }
+        Ok(keyframe::arc_length(&keyframes, 0.0, store.total_duration(), samples))
+    }
+
+    /// Save a copy of an object's keyframes under a new id and spawn an object playing it
+    /// with the source's colour.
+    async fn copy_object_track(&self, object_id: u32, time_offset: f64) -> Result<u32, JsValue> {
+        let (size, color, store) = self.with_object(object_id, |obj| {
+            (obj.get_size(), obj.color().to_string(), obj.keyframe_store().clone())
+        })?;
+        let keyframes = store
+            .load_all_keyframes()
+            .await
+            .map_err(|e| EngineError::storage(format!("Failed to load keyframes for {}", object_id), e))?;
+
+        let new_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
+        let chunks = keyframe::split_into_chunks(&new_id.to_string(), keyframes, store.chunk_size());
+        let new_store = SquareObject::create_store(
+            new_id,
+            chunks,
+            store.chunk_size(),
+            Arc::clone(&self.keyframe_db)
+        ).await;
+
+        self.spawn_object(new_id, size, &color, new_store, time_offset);
+        Ok(new_id)
+    }
+
+    /// Add an object that plays a shared track `time_offset` ms out of phase. Instances
+    /// share the track's keyframes and chunk cache instead of storing their own copy.
+    #[wasm_bindgen]
+    pub fn add_instanced_object(
+        &self,
+        track_id: &str,
+        size: f64,
+        color: &str,
+        time_offset: f64,
+    ) -> Result<u32, JsValue> {
+        let store = self.shared_stores
+            .borrow()
+            .get(track_id)
+            .cloned()
+            .ok_or_else(|| EngineError::SharedTrackNotFound(track_id.to_string()))?;
+        store.grow_cache_for_instance();
+
+        let object_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
+        self.spawn_object(object_id, size, color, store, time_offset);
+        Ok(object_id)
+    }
+
+    /// Add a square driven by `pattern_fn(time_ms) => ({ x, y })` instead of keyframes.
+    /// Nothing is stored in IndexedDB; the function is called every update. Lazy objects
+    /// are drawn above keyframed ones and are not hit-tested.
+    #[wasm_bindgen]
+    pub fn add_lazy_object(&self, pattern_fn: js_sys::Function, size: f64, color: &str) -> Result<u32, JsValue> {
+        let object_id = NEXT_SQUARE_INDEX.fetch_add(1, Ordering::SeqCst);
+        let obj = LazySquareObject::new(object_id, size, color, pattern_fn)?;
+        self.lazy_objects.borrow_mut().push(obj);
+        Ok(object_id)
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_lazy_object(&self, object_id: u32) {
+        self.lazy_objects.borrow_mut().retain(|obj| obj.object_id() != object_id);
+    }
+
+    /// Join two objects with a `"fixed_distance"` or `"spring_damper"` constraint. `params`
+    /// may set `target` (distance in pixels, defaulting to the current one), `stiffness`
+    /// and `damping`.
+    #[wasm_bindgen]
+    pub fn add_constraint(&self, obj_a: u32, obj_b: u32, kind: &str, params: JsValue) -> Result<(), JsValue> {
+        if obj_a == obj_b {
+            return Err(EngineError::invalid("Cannot constrain an object to itself").into());
         }
+        let params: ConstraintParams = if params.is_undefined() || params.is_null() {
+            ConstraintParams::default()
+        } else {
+            serde_wasm_bindgen::from_value(params)?
+        };
+        let center_a = self.with_object(obj_a, |obj| obj.center())?;
+        let center_b = self.with_object(obj_b, |obj| obj.center())?;
 
+        let constraint = Constraint::parse(kind, params, center_a.distance(&center_b))
+            .map_err(EngineError::InvalidArgument)?;
+        self.constraints.borrow_mut().push((obj_a, obj_b, constraint));
         Ok(())
     }
 
+    /// Remove every constraint between the two objects, in either order. Returns whether
+    /// any were found.
+    #[wasm_bindgen]
+    pub fn remove_constraint(&self, obj_a: u32, obj_b: u32) -> bool {
+        let removed = {
+            let mut constraints = self.constraints.borrow_mut();
+            let before = constraints.len();
+            constraints.retain(|(a, b, _)| !((*a == obj_a && *b == obj_b) || (*a == obj_b && *b == obj_a)));
+            constraints.len() != before
+        };
+        if removed {
+            self.stop_unsprung(&[obj_a, obj_b]);
+        }
+        removed
+    }
+
+    /// Bring those of `ids` that no spring drives any more to rest, so they don't keep
+    /// drifting at the last velocity a spring gave them.
+    fn stop_unsprung(&self, ids: &[u32]) {
+        let ids = constraint::unsprung(&self.constraints.borrow(), ids);
+        for obj in self.objects.borrow_mut().iter_mut().filter(|obj| ids.contains(&obj.object_id())) {
+            obj.stop_constraint_motion();
+        }
+    }
+
+    /// Every constraint as `{ objectA, objectB, kind, ...settings }`.
+    #[wasm_bindgen]
+    pub fn list_constraints(&self) -> Result<JsValue, JsValue> {
+        let constraints = self.constraints.borrow();
+        let infos: Vec<ConstraintInfo> = constraints
+            .iter()
+            .map(|(object_a, object_b, constraint)| ConstraintInfo { object_a: *object_a, object_b: *object_b, constraint })
+            .collect();
+        Ok(infos.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+    }
+
+    /// Call `callback(object_id, x, y)` after any update that leaves the object more than
+    /// `threshold` pixels from where it was last reported. Returns an id for `unwatch_object`.
+    #[wasm_bindgen]
+    pub fn watch_object(&self, object_id: u32, threshold: f64, callback: js_sys::Function) -> Result<u32, JsValue> {
+        let (last_x, last_y) = self.with_object(object_id, |obj| (obj.current_x(), obj.current_y()))?;
+        let id = NEXT_WATCHER_ID.fetch_add(1, Ordering::SeqCst);
+        self.watchers.borrow_mut().push(ObjectWatcher {
+            id,
+            object_id,
+            last_x,
+            last_y,
+            threshold,
+            callback,
+        });
+        Ok(id)
+    }
+
+    #[wasm_bindgen]
+    pub fn unwatch_object(&self, watcher_id: u32) {
+        self.watchers.borrow_mut().retain(|watcher| watcher.id != watcher_id);
+    }
+
+    /// Call `callback(ctx)` with the canvas 2D context at `phase` of every render:
+    /// `"before_background"`, `"after_background"`, `"before_objects"`, `"after_objects"`,
+    /// `"before_overlay"` or `"after_overlay"`. Returns an id for `unregister_render_hook`.
+    #[wasm_bindgen]
+    pub fn register_render_hook(&self, phase: &str, callback: js_sys::Function) -> Result<u32, JsValue> {
+        let phase = RenderPhase::parse(phase).map_err(EngineError::InvalidArgument)?;
+        let id = NEXT_RENDER_HOOK_ID.fetch_add(1, Ordering::SeqCst);
+        self.render_hooks.borrow_mut().entry(phase).or_default().push((id, callback));
+        Ok(id)
+    }
+
+    #[wasm_bindgen]
+    pub fn unregister_render_hook(&self, hook_id: u32) {
+        for hooks in self.render_hooks.borrow_mut().values_mut() {
+            hooks.retain(|(id, _)| *id != hook_id);
+        }
+    }
+
+    /// Add an object, recycling a released one from the pool when available.
+    fn spawn_object(&self, object_id: u32, size: f64, color: &str, store: KeyframeStore, time_offset: f64) {
+        let mut objects = self.objects.borrow_mut();
+        let handle = match objects.acquire() {
+            Some(handle) => {
+                if let Some(obj) = objects.get_mut(&handle) {
+                    obj.reinit(object_id, size, color, store, time_offset);
+                }
+                handle
+            }
+            None => objects.insert(SquareObject::with_store(object_id, size, color, store, time_offset)),
+        };
+        if let Some(obj) = objects.get_mut(&handle) {
+            obj.set_highlight_color(&self.selection_color.borrow());
+            obj.set_spawn_seq(NEXT_SPAWN_SEQ.fetch_add(1, Ordering::SeqCst));
+        }
+    }
+
+    /// Add a single keyframe to a live object, e.g. while recording a path. Times inside
+    /// the current track are inserted in order; later times extend the track, by at most
+    /// 1000 chunks at a time. Objects playing a shared track can't be given keyframes on
+    /// their own.
+    #[wasm_bindgen]
+    pub async fn add_keyframe_to_object(&self, object_id: u32, time: f64, x: f64, y: f64) -> Result<(), JsValue> {
+        let mut store = self.owned_object_store(object_id, "given keyframes")?;
+        validate_keyframe(time, x, y, store.chunk_size().into(), store.chunk_count())
+            .map_err(EngineError::InvalidArgument)?;
+
+        store
+            .add_keyframe(time, x, y)
+            .await
+            .map_err(|e| EngineError::storage("Failed to add keyframe", e))?;
+
+        self.replace_object_store(object_id, store);
+        Ok(())
+    }
+
+    /// Clone of an object's store, for async work that must not hold the object list borrowed.
+    fn object_store(&self, object_id: u32) -> Result<KeyframeStore, JsValue> {
+        self.objects
+            .borrow()
+            .iter()
+            .find(|obj| obj.object_id() == object_id)
+            .map(|obj| obj.keyframe_store().clone())
+            .ok_or_else(|| EngineError::ObjectNotFound(object_id).into())
+    }
+
+    /// Like `object_store`, but rejects objects playing a shared track: changing the track
+    /// would change every instance, and only this object's store would see the new duration.
+    fn owned_object_store(&self, object_id: u32, action: &'static str) -> Result<KeyframeStore, JsValue> {
+        let store = self.object_store(object_id)?;
+        if store.object_id() != object_id.to_string() {
+            return Err(EngineError::SharedTrack { object_id, track_id: store.object_id().to_string(), action }.into());
+        }
+        Ok(store)
+    }
+
+    /// Grow the object's hit-test box by `padding` pixels on each side; drawing is unaffected.
+    #[wasm_bindgen]
+    pub fn set_object_hit_padding(&self, object_id: u32, padding: f64) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_hit_padding(padding))
+    }
+
+    /// Grow the object's hit-test box by `percentage` of its size on each side.
+    #[wasm_bindgen]
+    pub fn set_object_hit_padding_percentage(&self, object_id: u32, percentage: f64) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_hit_padding_percentage(percentage))
+    }
+
+    /// Current velocity of the object as `{x, y}` in pixels per ms.
+    #[wasm_bindgen]
+    pub fn get_object_velocity(&self, object_id: u32) -> Result<JsValue, JsValue> {
+        let velocity = self.with_object(object_id, |obj| obj.get_velocity_at(obj.current_time()))?;
+        Ok(serde_wasm_bindgen::to_value(&velocity)?)
+    }
+
+    /// Resolve with the object's interpolated `{ x, y, time }` at any point of its track,
+    /// not just the current one. Times outside the track are clamped to its ends.
+    #[wasm_bindgen]
+    pub fn get_keyframe_at_time(&self, object_id: u32, time: f64) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let store = engine.with_object(object_id, |obj| obj.keyframe_store().clone())?;
+            let time = time.clamp(0.0, store.total_duration());
+            let pos = store
+                .position_at(time)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to load keyframes for {}", object_id), e))?;
+            Ok(serde_wasm_bindgen::to_value(&KeyframeSample { x: pos.x, y: pos.y, time })?)
+        })
+    }
+
+    /// Chunk indices of the object's track that failed their checksum and were deleted.
+    #[wasm_bindgen]
+    pub fn get_corrupted_chunks(&self, object_id: u32) -> Result<Vec<u32>, JsValue> {
+        self.with_object(object_id, |obj| obj.keyframe_store().corrupted_chunk_ids())
+    }
+
+    fn with_object<R>(&self, object_id: u32, f: impl FnOnce(&SquareObject) -> R) -> Result<R, JsValue> {
+        self.objects
+            .borrow()
+            .iter()
+            .find(|obj| obj.object_id() == object_id)
+            .map(f)
+            .ok_or_else(|| EngineError::ObjectNotFound(object_id).into())
+    }
+
+    fn with_object_mut<R>(&self, object_id: u32, f: impl FnOnce(&mut SquareObject) -> R) -> Result<R, JsValue> {
+        self.objects
+            .borrow_mut()
+            .iter_mut()
+            .find(|obj| obj.object_id() == object_id)
+            .map(f)
+            .ok_or_else(|| EngineError::ObjectNotFound(object_id).into())
+    }
+
+    fn replace_object_store(&self, object_id: u32, store: KeyframeStore) {
+        if let Some(obj) = self.objects.borrow_mut().iter_mut().find(|obj| obj.object_id() == object_id) {
+            obj.set_keyframe_store(store);
+        }
+    }
+
+    /// Start capturing mouse and keyboard input with timestamps.
+    #[wasm_bindgen]
+    pub fn start_recording(&self) {
+        self.input_handler.start_recording();
+    }
+
+    /// Stop capturing and return the recorded events for a later `start_replay`.
+    #[wasm_bindgen]
+    pub fn stop_recording(&self) -> Result<JsValue, JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.input_handler.stop_recording())?)
+    }
+
+    /// Drive input from a recording returned by `stop_recording`; live input is ignored
+    /// until the replay finishes.
+    #[wasm_bindgen]
+    pub fn start_replay(&self, data: JsValue) -> Result<(), JsValue> {
+        let events = serde_wasm_bindgen::from_value(data)?;
+        self.input_handler.start_replay(events);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn create_keyframe_editor(&self, object_id: u32) -> KeyframeEditor {
+        KeyframeEditor::new(object_id)
+    }
+
+    /// Apply an editor's pending changes to its object and reload the affected chunks.
+    /// Objects playing a shared track can't be edited on their own.
+    #[wasm_bindgen]
+    pub async fn commit_keyframe_editor(&mut self, editor: KeyframeEditor) -> Result<(), JsValue> {
+        let object_id = editor.object_id();
+        let mut store = self.owned_object_store(object_id, "edited")?;
+
+        editor
+            .commit(&self.keyframe_db, &mut store)
+            .await
+            .map_err(|e| EngineError::storage("Keyframe edit failed", e))?;
+
+        self.replace_object_store(object_id, store);
+
+        self.fetch_data().await
+    }
+
+    /// Re-save an object's keyframes in chunks of `new_chunk_size` ms. Objects playing a
+    /// shared track can't be rechunked on their own.
+    #[wasm_bindgen]
+    pub fn rechunk_object(&self, object_id: u32, new_chunk_size: f64) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            if !new_chunk_size.is_finite() || new_chunk_size <= 0.0 {
+                return Err(EngineError::invalid("Chunk size must be a positive number").into());
+            }
+            let store = engine.owned_object_store(object_id, "rechunked")?;
+
+            engine
+                .keyframe_db
+                .migrate_chunk_size(store.object_id(), store.chunk_size().into(), new_chunk_size)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to rechunk object {}", object_id), e))?;
+
+            store.invalidate();
+            let rechunked = KeyframeStore::new(
+                store.object_id().to_string(),
+                new_chunk_size as f32,
+                store.total_duration(),
+                engine.keyframe_db.clone(),
+            );
+            engine.replace_object_store(object_id, rechunked);
+            engine.fetch_data().await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Replace every chunk of an object's track with `keyframes_per_chunk` evenly spaced
+    /// keyframes sampled from the current curve, so lookups no longer depend on how
+    /// unevenly the original keyframes were spread.
+    #[wasm_bindgen]
+    pub fn resample_object(&self, object_id: u32, keyframes_per_chunk: u32) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            if keyframes_per_chunk < 2 {
+                return Err(EngineError::invalid("Resampling needs at least 2 keyframes per chunk").into());
+            }
+            let store = engine.owned_object_store(object_id, "resampled")?;
+
+            let resample_err = |e: DbError| EngineError::storage(format!("Failed to resample object {}", object_id), e);
+            let chunks = engine
+                .keyframe_db
+                .bulk_load_chunks(store.object_id(), &store.intact_chunk_ids())
+                .await
+                .map_err(resample_err)?;
+            let resampled = chunks.iter().map(|chunk| chunk.resample_uniform(keyframes_per_chunk)).collect();
+            engine.keyframe_db.save_chunks(resampled).await.map_err(resample_err)?;
+
+            store.invalidate();
+            engine.fetch_data().await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Replace an object's animation with constant-speed motion through `path`, an array
+    /// of `[x, y]` waypoints, taking `duration_ms` per pass. `loop_mode` must be `"loop"`,
+    /// the engine's only playback mode. Keyframes are written at 60 Hz, so a pass can last
+    /// at most ten minutes, and the object restarts from the first waypoint. An object
+    /// playing a shared track gets a track of its own.
+    #[wasm_bindgen]
+    pub fn animate_object_along_path(
+        &self,
+        object_id: u32,
+        path: JsValue,
+        duration_ms: f64,
+        loop_mode: &str,
+    ) -> js_sys::Promise {
+        let mut engine = self.share();
+        let path: Result<Vec<(f64, f64)>, _> = serde_wasm_bindgen::from_value(path);
+        let loop_mode = loop_mode.to_string();
+        future_to_promise(async move {
+            if loop_mode != "loop" {
+                return Err(EngineError::invalid(format!("Unsupported loop mode '{}', only 'loop' is available", loop_mode)).into());
+            }
+            let steps = path_keyframe_steps(duration_ms).map_err(EngineError::InvalidArgument)?;
+            let path = path?;
+            if path.len() < 2 {
+                return Err(EngineError::invalid("A path needs at least 2 waypoints").into());
+            }
+            if path.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                return Err(EngineError::invalid("Path waypoints must be finite").into());
+            }
+
+            let last = u32::try_from(path.len() - 1)
+                .map(f64::from)
+                .map_err(|_| EngineError::invalid("Too many path waypoints"))?;
+            let waypoints: Vec<Keyframe> = (0u32..)
+                .zip(&path)
+                .map(|(i, (x, y))| Keyframe::new((duration_ms * f64::from(i) / last) as f32, *x as f32, *y as f32))
+                .collect();
+            let timed = keyframe::reparametrize_by_arc_length(&waypoints);
+            let keyframes: Vec<Keyframe> = (0..=steps)
+                .map(|i| {
+                    let t = (f64::from(i) * PATH_KEYFRAME_STEP_MS).min(duration_ms);
+                    let pos = keyframe::track_position(&timed, t);
+                    Keyframe::new(t as f32, pos.x as f32, pos.y as f32)
+                })
+                .collect();
+
+            let chunk_size = engine.object_store(object_id)?.chunk_size();
+            let track_id = object_id.to_string();
+            let path_err = |e: DbError| EngineError::storage(format!("Failed to save path for object {}", object_id), e);
+            engine
+                .keyframe_db
+                .delete_object_chunks(&track_id)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to clear keyframes of object {}", object_id), e))?;
+            let chunks = keyframe::split_into_chunks(&track_id, keyframes, chunk_size);
+            engine.keyframe_db.save_chunks(chunks).await.map_err(path_err)?;
+
+            let store = KeyframeStore::new(track_id, chunk_size, duration_ms, Arc::clone(&engine.keyframe_db));
+            engine.replace_object_store(object_id, store);
+            engine.with_object_mut(object_id, |obj| obj.seek(0.0))?;
+            engine.fetch_data().await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Set the CSS cursor shown over the canvas, e.g. `"pointer"` or `"grab"`.
+    #[wasm_bindgen]
+    pub fn set_canvas_cursor(&self, cursor: &str) -> Result<(), JsValue> {
+        self.canvas.style().set_property("cursor", cursor)
+    }
+
+    /// When enabled, each update switches the cursor to `"pointer"` while the mouse is over
+    /// an object and back to `"default"` otherwise, overriding `set_canvas_cursor`.
+    #[wasm_bindgen]
+    pub fn set_auto_cursor(&self, enabled: bool) -> Result<(), JsValue> {
+        self.auto_cursor.set(enabled);
+        if !enabled {
+            self.set_canvas_cursor("default")?;
+        }
+        Ok(())
+    }
+
+    /// Whether `key` (a `KeyboardEvent.key` value such as `"a"` or `"ArrowUp"`) went down
+    /// between the previous frame and this one. A press and release within one frame is
+    /// not seen.
+    #[wasm_bindgen]
+    pub fn is_key_just_pressed(&self, key: &str) -> bool {
+        self.input_handler.is_key_just_pressed(key)
+    }
+
+    /// Whether `key` was released between the previous frame and this one.
+    #[wasm_bindgen]
+    pub fn is_key_just_released(&self, key: &str) -> bool {
+        self.input_handler.is_key_just_released(key)
+    }
+
+    /// Value of `axis` on gamepad `gamepad_index` in [-1, 1], with small deflections
+    /// treated as 0. Updated once per frame.
+    #[wasm_bindgen]
+    pub fn get_gamepad_axis(&self, gamepad_index: u32, axis: u32) -> f64 {
+        self.input_handler.get_gamepad_axis(gamepad_index, axis)
+    }
+
+    #[wasm_bindgen]
+    pub fn is_gamepad_button_pressed(&self, gamepad_index: u32, button: u32) -> bool {
+        self.input_handler.is_gamepad_button_pressed(gamepad_index, button)
+    }
+
+    #[wasm_bindgen]
+    pub fn gamepad_count(&self) -> u32 {
+        self.input_handler.gamepad_count()
+    }
+
+    /// Pan the camera with the first gamepad's left stick.
+    #[wasm_bindgen]
+    pub fn set_gamepad_camera_control(&self, enabled: bool) {
+        self.gamepad_camera_control.set(enabled);
+    }
+
+    /// Choose how saves react to a full IndexedDB: `"fail_fast"` (the default) rejects them,
+    /// `"clear_on_full"` clears every stored chunk and retries.
+    #[wasm_bindgen]
+    pub fn set_db_eviction_policy(&self, policy: &str) -> Result<(), JsValue> {
+        let policy = EvictionPolicy::parse(policy).map_err(EngineError::InvalidArgument)?;
+        self.keyframe_db.set_eviction_policy(policy);
+        Ok(())
+    }
+
+    /// Render at `scale` times the window resolution (clamped to [0.1, 2.0]) while keeping the
+    /// canvas at window size on screen, so the browser up- or downscales the result. World
+    /// coordinates stay in CSS pixels; only the canvas backing resolution changes.
+    #[wasm_bindgen]
+    pub fn set_render_scale(&self, scale: f64) -> Result<(), JsValue> {
+        if !scale.is_finite() {
+            return Err(EngineError::invalid("render scale must be a finite number").into());
+        }
+        let scale = scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE);
+        self.render_scale.set(scale);
+
+        let style = self.canvas.style();
+        style.set_property("width", &format!("{}px", self.window_width))?;
+        style.set_property("height", &format!("{}px", self.window_height))?;
+        self.canvas.set_width((self.window_width * scale).round() as u32);
+        self.canvas.set_height((self.window_height * scale).round() as u32);
+        Ok(())
+    }
+
+    /// Backing resolution of the canvas as `{ width, height }`.
+    #[wasm_bindgen]
+    pub fn get_render_resolution(&self) -> Result<JsValue, JsValue> {
+        let resolution = RenderResolution {
+            width: self.canvas.width(),
+            height: self.canvas.height(),
+        };
+        Ok(serde_wasm_bindgen::to_value(&resolution)?)
+    }
+
+    /// Shove an object: it moves at `(fx, fy)` pixels per ms, slowed by the physics friction,
+    /// for `duration_ms`, then returns to its keyframed path. The force must be finite and
+    /// the duration a non-negative number.
+    #[wasm_bindgen]
+    pub fn apply_force_to_object(&self, object_id: u32, fx: f64, fy: f64, duration_ms: f64) -> Result<(), JsValue> {
+        validate_force(fx, fy, duration_ms).map_err(EngineError::InvalidArgument)?;
+        let friction = self.physics_friction.get();
+        self.with_object_mut(object_id, |obj| obj.apply_force(fx, fy, duration_ms, friction))
+    }
+
+    /// Fraction of a shoved object's velocity lost per ms, from 0 (none) to 1. Also applies
+    /// to objects already in motion.
+    #[wasm_bindgen]
+    pub fn set_physics_friction(&self, friction: f64) {
+        let friction = friction.clamp(0.0, 1.0);
+        self.physics_friction.set(friction);
+        for obj in self.objects.borrow_mut().iter_mut() {
+            obj.set_physics_friction(friction);
+        }
+    }
+
+    /// Draw objects with a lower `order` first, so higher orders appear on top. Objects
+    /// without an order are drawn last, in insertion order; ties also keep insertion order.
+    #[wasm_bindgen]
+    pub fn set_object_render_order(&self, object_id: u32, order: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_render_order(order))
+    }
+
+    /// The object's render order, or `undefined` if it was never set.
+    #[wasm_bindgen]
+    pub fn get_object_render_order(&self, object_id: u32) -> Result<Option<u32>, JsValue> {
+        self.with_object(object_id, |obj| {
+            Some(obj.render_order()).filter(|order| *order != squre_object::DEFAULT_RENDER_ORDER)
+        })
+    }
+
+    /// Every object id in the order `render` draws them: by render order, then insertion
+    /// order. O(n log n) in the number of objects.
+    #[wasm_bindgen]
+    pub fn get_render_order(&self) -> Vec<u32> {
+        draw_order(&self.objects.borrow())
+            .iter()
+            .map(|obj| obj.object_id())
+            .collect()
+    }
+
+    /// Apply many property changes in one call. `updates` is an array of
+    /// `{ objectId, color?, size?, paused?, renderOrder?, filter? }`. An entry with an
+    /// unknown object or an invalid value is skipped as a whole and reported in the
+    /// returned `[{ objectId, message }]`; the other entries still apply.
+    #[wasm_bindgen]
+    pub fn batch_set_properties(&self, updates: JsValue) -> Result<JsValue, JsValue> {
+        let updates: Vec<PropertyUpdate> = serde_wasm_bindgen::from_value(updates)?;
+        let mut errors = Vec::new();
+        let mut objs = self.objects.borrow_mut();
+        let mut color_tweens = self.color_tweens.borrow_mut();
+        for update in updates {
+            let object_id = update.object_id;
+            let result = Self::validate_property_update(&update).and_then(|()| {
+                objs.iter_mut()
+                    .find(|obj| obj.object_id() == object_id)
+                    .ok_or(EngineError::ObjectNotFound(object_id))
+            });
+            let obj = match result {
+                Ok(obj) => obj,
+                Err(e) => {
+                    errors.push(PropertyUpdateError { object_id, message: e.to_string() });
+                    continue;
+                }
+            };
+            if let Some(color) = &update.color {
+                color_tweens.remove(&object_id);
+                obj.set_color(color);
+            }
+            if let Some(size) = update.size {
+                obj.set_dimensions(size, size);
+            }
+            if let Some(paused) = update.paused {
+                obj.set_paused(paused);
+            }
+            if let Some(order) = update.render_order {
+                obj.set_render_order(order);
+            }
+            if let Some(filter) = &update.filter {
+                obj.set_filter(filter);
+            }
+        }
+        Ok(serde_wasm_bindgen::to_value(&errors)?)
+    }
+
+    fn validate_property_update(update: &PropertyUpdate) -> Result<(), EngineError> {
+        if let Some(size) = update.size.filter(|size| !size.is_finite() || *size <= 0.0) {
+            return Err(EngineError::invalid(format!("Size must be a positive number, got {}", size)));
+        }
+        if let Some(filter) = &update.filter {
+            validate_filter(filter).map_err(EngineError::InvalidArgument)?;
+        }
+        Ok(())
+    }
+
+    /// Freeze an object where it is; the rest of the scene keeps playing.
+    #[wasm_bindgen]
+    pub fn pause_object(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_paused(true))
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_object(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_paused(false))
+    }
+
+    #[wasm_bindgen]
+    pub fn is_object_paused(&self, object_id: u32) -> Result<bool, JsValue> {
+        self.with_object(object_id, |obj| obj.is_paused())
+    }
+
+    /// Pause every object in one call, instead of one `pause_object` call per object.
+    #[wasm_bindgen]
+    pub fn pause_all(&self) {
+        self.set_all_paused(true);
+    }
+
+    #[wasm_bindgen]
+    pub fn resume_all(&self) {
+        self.set_all_paused(false);
+    }
+
+    fn set_all_paused(&self, paused: bool) {
+        for obj in self.objects.borrow_mut().iter_mut() {
+            obj.set_paused(paused);
+        }
+    }
+
+    /// Constant acceleration, in pixels per ms², for every physics-enabled object.
+    #[wasm_bindgen]
+    pub fn set_gravity(&self, gx: f64, gy: f64) {
+        *self.gravity.borrow_mut() = Vector2::new(gx, gy);
+    }
+
+    /// Fraction of its speed a physics-enabled object keeps when it bounces off a canvas
+    /// edge, from 0 to 1.
+    #[wasm_bindgen]
+    pub fn set_bounce_coefficient(&self, bounce: f64) {
+        self.bounce_coefficient.set(bounce.clamp(0.0, 1.0));
+    }
+
+    /// Detach an object from its keyframes and move it only by forces, gravity and
+    /// bounces off the canvas edges.
+    #[wasm_bindgen]
+    pub fn enable_object_physics(&self, object_id: u32) -> Result<(), JsValue> {
+        let friction = self.physics_friction.get();
+        self.with_object_mut(object_id, |obj| obj.enable_physics(friction))
+    }
+
+    /// Stop a physics-enabled object and put it back on its keyframed path.
+    #[wasm_bindgen]
+    pub fn disable_object_physics(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.disable_physics())
+    }
+
+    /// Move an object directly, detaching it from its keyframes until
+    /// `clear_position_override` is called.
+    #[wasm_bindgen]
+    pub fn set_object_position(&self, object_id: u32, x: f64, y: f64) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_position(x, y))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_position_override(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.clear_position_override())
+    }
+
+    /// Arrange objects, in the order given, in a `"row"`, `"column"`, `"circle"` or
+    /// `"grid"` of centres. `params` may set `originX`/`originY` (the first centre, or the
+    /// circle's centre), `spacing` between neighbours, `radius` and `columns`. Like
+    /// `set_object_position`, this detaches the objects from their keyframes.
+    #[wasm_bindgen]
+    pub fn align_objects(&self, ids: &[u32], alignment: &str, params: JsValue) -> Result<(), JsValue> {
+        let params: LayoutParams = if params.is_undefined() || params.is_null() {
+            LayoutParams::default()
+        } else {
+            serde_wasm_bindgen::from_value(params)?
+        };
+        let alignment = Alignment::parse(alignment, params).map_err(EngineError::InvalidArgument)?;
+
+        let mut objs = self.objects.borrow_mut();
+        if let Some(&missing) = ids.iter().find(|id| !objs.iter().any(|obj| obj.object_id() == **id)) {
+            return Err(EngineError::ObjectNotFound(missing).into());
+        }
+        for (&object_id, (cx, cy)) in ids.iter().zip(alignment.positions(ids.len())) {
+            if let Some(obj) = objs.iter_mut().find(|obj| obj.object_id() == object_id) {
+                obj.set_position(cx - obj.width() / 2.0, cy - obj.height() / 2.0);
+            }
+        }
+        Ok(())
+    }
+
+    /// Make an object follow the mouse, keeping its current offset from the pointer,
+    /// until `unbind_mouse_drag` or the next mouse button release.
+    #[wasm_bindgen]
+    pub fn bind_object_to_mouse(&self, object_id: u32) -> Result<(), JsValue> {
+        let pos = self.input_handler.get_mouse_position();
+        let Vector2 { x: mouse_x, y: mouse_y } = self.convert_screen_to_world(pos.x, pos.y);
+        let offset = self.with_object(object_id, |obj| {
+            Vector2::new(obj.current_x() - mouse_x, obj.current_y() - mouse_y)
+        })?;
+        self.unbind_mouse_drag();
+        *self.drag_offset.borrow_mut() = offset;
+        self.dragged_object.set(Some(object_id));
+        Ok(())
+    }
+
+    /// Release the dragged object, if any, back to its keyframes.
+    #[wasm_bindgen]
+    pub fn unbind_mouse_drag(&self) {
+        if let Some(object_id) = self.dragged_object.take() {
+            // The object may have been removed while it was being dragged.
+            let _ = self.clear_position_override(object_id);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn get_dragged_object(&self) -> Option<u32> {
+        self.dragged_object.get()
+    }
+
+    /// Start a drag when a button goes down over an object, end it when the button is
+    /// released, and move the dragged object to the pointer in between.
+    fn update_drag(&self, mouse_pressed: bool, mouse_was_pressed: bool) {
+        let pos = self.input_handler.get_mouse_position();
+        if mouse_pressed && !mouse_was_pressed && self.dragged_object.get().is_none() {
+            if let Some(object_id) = self.object_at_point(pos.x, pos.y) {
+                let _ = self.bind_object_to_mouse(object_id);
+            }
+        } else if !mouse_pressed && mouse_was_pressed {
+            self.unbind_mouse_drag();
+        }
+
+        let Some(object_id) = self.dragged_object.get() else {
+            return;
+        };
+        let Vector2 { x: mouse_x, y: mouse_y } = self.convert_screen_to_world(pos.x, pos.y);
+        let offset = self.drag_offset.borrow().clone();
+        if self.set_object_position(object_id, mouse_x + offset.x, mouse_y + offset.y).is_err() {
+            self.dragged_object.set(None);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn select_object(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_selected(true))
+    }
+
+    #[wasm_bindgen]
+    pub fn deselect_object(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_selected(false))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_selected_objects(&self) -> Vec<u32> {
+        self.objects
+            .borrow()
+            .iter()
+            .filter(|obj| obj.is_selected())
+            .map(|obj| obj.object_id())
+            .collect()
+    }
+
+    /// Write a `<ul>` listing every object (id, colour, current time, paused state) into the
+    /// element with id `element_id`. Selected objects' entries get the `selected` class.
+    #[wasm_bindgen]
+    pub fn render_scene_tree_to_element(&self, element_id: &str) -> Result<(), JsValue> {
+        let document = self.window.document().ok_or(EngineError::DocumentNotFound)?;
+        let element = document
+            .get_element_by_id(element_id)
+            .ok_or_else(|| EngineError::ElementNotFound(element_id.to_string()))?;
+        let rows: Vec<SceneTreeRow> = self
+            .objects
+            .borrow()
+            .iter()
+            .map(|obj| SceneTreeRow {
+                object_id: obj.object_id(),
+                color: obj.color().to_string(),
+                current_time: obj.current_time(),
+                paused: obj.is_paused(),
+                selected: obj.is_selected(),
+            })
+            .collect();
+        scene_tree::render_rows(&document, &element, &rows)
+    }
+
+    /// Keep the scene tree in `element_id` up to date, refreshing it every second. Clicking
+    /// an entry selects that object and centres the camera on it.
+    #[wasm_bindgen]
+    pub fn enable_scene_tree_panel(&self, element_id: &str) -> Result<(), JsValue> {
+        self.disable_scene_tree_panel();
+        self.render_scene_tree_to_element(element_id)?;
+        let element = self
+            .window
+            .document()
+            .and_then(|document| document.get_element_by_id(element_id))
+            .ok_or_else(|| EngineError::ElementNotFound(element_id.to_string()))?;
+
+        let refresher = self.share();
+        let refresh_id = element_id.to_string();
+        let selector = self.share();
+        let panel = ScenePanel::new(
+            (*self.window).clone(),
+            element,
+            move || {
+                if let Err(e) = refresher.render_scene_tree_to_element(&refresh_id) {
+                    web_sys::console::error_1(&e);
+                }
+            },
+            move |object_id| {
+                if selector.select_object(object_id).is_ok() {
+                    selector.center_camera_on_object(object_id);
+                }
+            },
+        )?;
+        *self.scene_panel.borrow_mut() = Some(panel);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn disable_scene_tree_panel(&self) {
+        self.scene_panel.borrow_mut().take();
+    }
+
+    /// Pan, keeping the zoom, so the object's centre is in the middle of the canvas.
+    fn center_camera_on_object(&self, object_id: u32) {
+        let Ok(center) = self.with_object(object_id, |obj| obj.center()) else {
+            return;
+        };
+        let zoom = self.camera_zoom.get();
+        self.camera_offset.set((
+            center.x - self.window_width / zoom / 2.0,
+            center.y - self.window_height / zoom / 2.0,
+        ));
+    }
+
+    /// Draw `image` behind the scene, fitted with `mode` (`"cover"`, `"contain"`, `"tile"` or
+    /// `"stretch"`). It scrolls by `parallax_factor` times the camera movement, default 0.
+    #[wasm_bindgen]
+    pub fn set_background_image(
+        &self,
+        image: web_sys::HtmlImageElement,
+        mode: &str,
+        parallax_factor: Option<f64>,
+    ) -> Result<(), JsValue> {
+        let mode = BackgroundMode::parse(mode).map_err(EngineError::InvalidArgument)?;
+        *self.background.borrow_mut() = Some(Background::new(image, mode, parallax_factor.unwrap_or(0.0)));
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_background_image(&self) {
+        *self.background.borrow_mut() = None;
+    }
+
+    /// Blend mode used to draw every object, e.g. `"multiply"` or `"screen"`; any
+    /// `globalCompositeOperation` value. Overlays are always drawn with `"source-over"`.
+    #[wasm_bindgen]
+    pub fn set_composite_operation(&self, op: &str) -> Result<(), JsValue> {
+        if !COMPOSITE_OPERATIONS.contains(&op) {
+            return Err(EngineError::invalid(format!("Unknown composite operation '{}'", op)).into());
+        }
+        *self.composite_operation.borrow_mut() = op.to_string();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_composite_operation(&self) -> String {
+        self.composite_operation.borrow().clone()
+    }
+
+    /// CSS filter applied to every object, e.g. `"blur(4px)"` or
+    /// `"grayscale(1) contrast(1.2)"`. Backgrounds and overlays are drawn unfiltered.
+    #[wasm_bindgen]
+    pub fn set_render_filter(&self, filter: &str) -> Result<(), JsValue> {
+        validate_filter(filter).map_err(EngineError::InvalidArgument)?;
+        *self.render_filter.borrow_mut() = filter.to_string();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_render_filter(&self) {
+        *self.render_filter.borrow_mut() = "none".to_string();
+    }
+
+    /// CSS filter for a single object, applied on top of the scene-wide render filter.
+    /// Pass `"none"` to remove it.
+    #[wasm_bindgen]
+    pub fn set_object_filter(&self, object_id: u32, filter: &str) -> Result<(), JsValue> {
+        validate_filter(filter).map_err(EngineError::InvalidArgument)?;
+        self.with_object_mut(object_id, |obj| obj.set_filter(filter))
+    }
+
+    /// Fill an object with a left-to-right linear gradient across its width. `stops` is an
+    /// array of `{ offset, color }` with offsets in [0, 1].
+    #[wasm_bindgen]
+    pub fn set_object_linear_gradient(&self, object_id: u32, stops: JsValue) -> Result<(), JsValue> {
+        let stops: Vec<ColorStop> = serde_wasm_bindgen::from_value(stops)?;
+        let width = self.with_object(object_id, |obj| obj.width())?;
+        self.set_object_gradient(object_id, FillStyle::LinearGradient { x0: 0.0, y0: 0.0, x1: width, y1: 0.0, stops })
+    }
+
+    /// Fill an object with a radial gradient centred on (cx, cy), relative to the object's
+    /// top-left corner, reaching the last stop at radius `cr`.
+    #[wasm_bindgen]
+    pub fn set_object_radial_gradient(
+        &self,
+        object_id: u32,
+        cx: f64,
+        cy: f64,
+        cr: f64,
+        stops: JsValue,
+    ) -> Result<(), JsValue> {
+        let stops: Vec<ColorStop> = serde_wasm_bindgen::from_value(stops)?;
+        self.set_object_gradient(object_id, FillStyle::RadialGradient { cx, cy, cr, stops })
+    }
+
+    /// Go back to filling the object with its flat colour.
+    #[wasm_bindgen]
+    pub fn clear_object_gradient(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object_mut(object_id, |obj| obj.set_gradient(None))
+    }
+
+    fn set_object_gradient(&self, object_id: u32, gradient: FillStyle) -> Result<(), JsValue> {
+        gradient.validate(&self.context).map_err(EngineError::InvalidArgument)?;
+        self.with_object_mut(object_id, |obj| obj.set_gradient(Some(gradient)))
+    }
+
+    /// CSS colour of the glow drawn around selected objects, current and future.
+    #[wasm_bindgen]
+    pub fn set_selection_color(&self, color: &str) {
+        *self.selection_color.borrow_mut() = color.to_string();
+        for obj in self.objects.borrow_mut().iter_mut() {
+            obj.set_highlight_color(color);
+        }
+    }
+
+    /// Show `text` at screen point (x, y) while the engine's elapsed animation time, in ms,
+    /// is within `[start, end]`. `font` and `color` take CSS values. Returns the text's id.
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_timed_text(
+        &self,
+        start: f64,
+        end: f64,
+        text: &str,
+        x: f64,
+        y: f64,
+        font: &str,
+        color: &str,
+    ) -> u32 {
+        let id = NEXT_TIMED_TEXT_ID.fetch_add(1, Ordering::SeqCst);
+        self.timed_texts.borrow_mut().push(TimedText {
+            id,
+            start_time: start,
+            end_time: end,
+            text: text.to_string(),
+            x,
+            y,
+            font: font.to_string(),
+            color: color.to_string(),
+        });
+        id
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_timed_text(&self, id: u32) {
+        self.timed_texts.borrow_mut().retain(|text| text.id != id);
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_timed_texts(&self) {
+        self.timed_texts.borrow_mut().clear();
+    }
+
+    /// Size of `text` drawn in `font` (a CSS font), as
+    /// `{ width, actualBoundingBoxAscent, actualBoundingBoxDescent, height }` in pixels.
+    #[wasm_bindgen]
+    pub fn measure_text(&self, text: &str, font: &str) -> Result<JsValue, JsValue> {
+        self.context.save();
+        self.context.set_font(font);
+        let metrics = self.context.measure_text(text);
+        self.context.restore();
+        let metrics = metrics?;
+        let ascent = metrics.actual_bounding_box_ascent();
+        let descent = metrics.actual_bounding_box_descent();
+        Ok(serde_wasm_bindgen::to_value(&TextMeasurement {
+            width: metrics.width(),
+            actual_bounding_box_ascent: ascent,
+            actual_bounding_box_descent: descent,
+            height: ascent + descent,
+        })?)
+    }
+
+    /// Draw `text` once at screen point (x, y), squeezed to `max_width` if given. The next
+    /// frame clears it, so call this from a render hook, or use `add_timed_text` for text
+    /// that should stay up.
+    #[wasm_bindgen]
+    pub fn draw_text_at(
+        &self,
+        text: &str,
+        x: f64,
+        y: f64,
+        font: &str,
+        color: &str,
+        max_width: Option<f64>,
+    ) -> Result<(), JsValue> {
+        self.context.save();
+        self.context.set_font(font);
+        self.context.set_fill_style_str(color);
+        let result = match max_width {
+            Some(max_width) => self.context.fill_text_with_max_width(text, x, y, max_width),
+            None => self.context.fill_text(text, x, y),
+        };
+        self.context.restore();
+        result
+    }
+
+    /// Start or stop timing the fetch, update and render phases of the live loop.
+    #[wasm_bindgen]
+    pub fn toggle_profiler(&self, enabled: bool) {
+        self.profiler.borrow_mut().set_enabled(enabled);
+    }
+
+    /// Average ms per phase over recent frames, e.g. `{ update: 0.4, render: 2.1, fetch: 0.9 }`.
+    #[wasm_bindgen]
+    pub fn get_profiler_data(&self) -> Result<JsValue, JsValue> {
+        let averages = self.profiler.borrow().averages();
+        Ok(averages.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+    }
+
+    /// Load chunks for playback in `worker`, which must run `chunk_worker.js`, instead of on
+    /// the main thread. Call after the engine is constructed, since construction resets
+    /// the database the worker connects to.
+    #[wasm_bindgen]
+    pub fn enable_fetch_worker(&self, worker: web_sys::Worker) {
+        self.keyframe_db.set_fetch_worker(Some(ChunkWorker::new(worker)));
+    }
+
+    /// Terminate the fetch worker and load chunks on the main thread again.
+    #[wasm_bindgen]
+    pub fn disable_fetch_worker(&self) {
+        self.keyframe_db.set_fetch_worker(None);
+    }
+
+    /// Choose how chunks are written from now on: `"none"` or `"delta"` (quantised delta
+    /// encoding, accurate to 0.05 ms and 0.1 px). Existing chunks load either way.
+    #[wasm_bindgen]
+    pub fn set_db_compression(&self, level: &str) -> Result<(), JsValue> {
+        let level = CompressionLevel::parse(level).map_err(EngineError::InvalidArgument)?;
+        self.keyframe_db.set_compression(level);
+        Ok(())
+    }
+
+    /// Resolve with `{ usageBytes, totalKeyframes }`: the origin's storage usage as estimated
+    /// by the browser, and the number of keyframes stored across all chunks.
+    #[wasm_bindgen]
+    pub fn get_storage_stats(&self) -> js_sys::Promise {
+        let keyframe_db = self.keyframe_db.clone();
+        future_to_promise(async move {
+            let usage_bytes = KeyframeDatabase::estimate_storage_usage().await?;
+            let total_keyframes = keyframe_db
+                .count_total_keyframes()
+                .await
+                .map_err(|e| EngineError::storage("Failed to count keyframes", e))?;
+            Ok(serde_wasm_bindgen::to_value(&StorageStats { usage_bytes, total_keyframes })?)
+        })
+    }
+
+    /// Configure how often each existing object sweeps chunks more than `keep_window` ms from
+    /// its playhead out of its cache. An interval of 0 frames disables the sweep.
+    #[wasm_bindgen]
+    pub fn set_chunk_eviction(&self, interval_frames: u32, keep_window: f64) {
+        for obj in self.objects.borrow_mut().iter_mut() {
+            obj.set_chunk_eviction(interval_frames, keep_window);
+        }
+    }
+
+    /// Times the object's position lookup found its chunk not yet loaded.
+    #[wasm_bindgen]
+    pub fn get_position_miss_count(&self, object_id: u32) -> Result<u64, JsValue> {
+        self.with_object(object_id, |obj| obj.keyframe_store().position_miss_count())
+    }
+
+    /// Total chunks evicted from object caches, by the LRU or the periodic sweep.
+    #[wasm_bindgen]
+    pub fn cache_evictions(&self) -> u64 {
+        self.objects
+            .borrow()
+            .iter()
+            .map(|obj| obj.keyframe_store().cache_evictions())
+            .sum()
+    }
+
+    /// Chunk cache counters for the object's track as
+    /// `{ hits, misses, evictions, hitRate, avgLoadMs }`. Objects playing a shared track
+    /// report the track's combined figures.
+    #[wasm_bindgen]
+    pub fn get_cache_metrics(&self, object_id: u32) -> Result<JsValue, JsValue> {
+        let metrics = self.with_object(object_id, |obj| obj.keyframe_store().cache_metrics())?;
+        Ok(serde_wasm_bindgen::to_value(&CacheMetricsReport::from(metrics))?)
+    }
+
+    #[wasm_bindgen]
+    pub fn reset_cache_metrics(&self, object_id: u32) -> Result<(), JsValue> {
+        self.with_object(object_id, |obj| obj.keyframe_store().reset_cache_metrics())
+    }
+
+    /// Log each track's cache metrics once every `CACHE_METRICS_LOG_INTERVAL_MS`.
+    fn log_cache_metrics(&self) {
+        let now = self.global_time.get();
+        if now - self.last_cache_metrics_log.get() < CACHE_METRICS_LOG_INTERVAL_MS {
+            return;
+        }
+        self.last_cache_metrics_log.set(now);
+        let mut logged = HashSet::new();
+        for obj in self.objects.borrow().iter() {
+            let store = obj.keyframe_store();
+            if !logged.insert(store.object_id().to_string()) {
+                continue;
+            }
+            let metrics = store.cache_metrics();
+            web_sys::console::log_1(&JsValue::from_str(&format!(
+                "chunk cache {}: {} hits, {} misses ({:.1}% hit), {} evictions, {:.2}ms avg load",
+                store.object_id(),
+                metrics.hits,
+                metrics.misses,
+                metrics.hit_rate() * 100.0,
+                metrics.evictions,
+                metrics.avg_load_ms(),
+            )));
+        }
+    }
+
+    /// Start accumulating object occupancy per `cell_size` grid cell and draw it as an overlay.
+    #[wasm_bindgen]
+    pub fn enable_heatmap(&self, cell_size: f64) -> Result<(), JsValue> {
+        if cell_size <= 0.0 {
+            return Err(EngineError::invalid("heatmap cell size must be positive").into());
+        }
+        *self.heat_map.borrow_mut() = Some(HeatMap::new(self.window_width, self.window_height, cell_size));
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn disable_heatmap(&self) {
+        *self.heat_map.borrow_mut() = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn reset_heatmap(&self) {
+        if let Some(heat_map) = self.heat_map.borrow_mut().as_mut() {
+            heat_map.reset();
+        }
+    }
+
+    /// Occupancy counts as a row-major 2D array, or `null` when the heatmap is disabled.
+    #[wasm_bindgen]
+    pub fn get_heatmap_data(&self) -> Result<JsValue, JsValue> {
+        match self.heat_map.borrow().as_ref() {
+            Some(heat_map) => Ok(serde_wasm_bindgen::to_value(heat_map.grid())?),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    /// Render `frames` frames spaced `1000 / fps` ms apart to a detached canvas and resolve
+    /// with the encoded GIF as a `Uint8Array`. Each frame seeks the objects ahead of their
+    /// current times, so physics, the camera and tweens are not stepped and no events or
+    /// watchers fire. The live loop is suspended while exporting and every object is
+    /// returned to its previous time afterwards.
+    /// `progress_callback`, if given, is called with `(frames_done, frames)` after each frame.
+    #[wasm_bindgen]
+    pub fn export_as_gif(
+        &self,
+        frames: u32,
+        fps: f64,
+        progress_callback: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            if frames == 0 || fps <= 0.0 {
+                return Err(EngineError::invalid("GIF export needs at least one frame and a positive fps").into());
+            }
+
+            let width = engine.window_width as u16;
+            let height = engine.window_height as u16;
+            engine.context = engine.create_detached_context(width as u32, height as u32)?;
+
+            let saved_times = engine.object_times();
+            engine.headless.set(true);
+            let result = engine
+                .encode_gif(&saved_times, frames, fps, width, height, progress_callback.as_ref())
+                .await;
+            engine.restore_object_times(saved_times);
+            engine.headless.set(false);
+
+            let bytes = result?;
+            Ok(js_sys::Uint8Array::from(&bytes[..]).into())
+        })
+    }
+
+    /// Render every frame from `start_time` to `end_time` (inclusive, simulation ms) at
+    /// `fps` to a detached canvas and resolve with the frames as an array of PNG data URLs.
+    /// At most 300 frames are exported. Objects are returned to their previous times afterwards.
+    /// `progress_callback`, if given, is called with `(frames_done, frames)` after each frame.
+    #[wasm_bindgen]
+    pub fn export_frame_sequence(
+        &self,
+        start_time: f64,
+        end_time: f64,
+        fps: f64,
+        progress_callback: Option<js_sys::Function>,
+    ) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            if fps <= 0.0 || end_time < start_time {
+                return Err(EngineError::invalid("Frame export needs a positive fps and end_time >= start_time").into());
+            }
+            let frames = ((end_time - start_time) * fps / 1000.0).floor() as u64 + 1;
+            if frames > u64::from(MAX_EXPORTED_FRAMES) {
+                return Err(EngineError::invalid(format!(
+                    "Frame export of {} frames exceeds the limit of {}",
+                    frames, MAX_EXPORTED_FRAMES
+                ))
+                .into());
+            }
+
+            engine.context = engine.create_detached_context(engine.window_width as u32, engine.window_height as u32)?;
+
+            let saved_times = engine.object_times();
+            engine.headless.set(true);
+            let result = engine
+                .capture_frames(start_time, fps, frames as u32, progress_callback.as_ref())
+                .await;
+            engine.restore_object_times(saved_times);
+            engine.headless.set(false);
+
+            let urls = result?;
+            Ok(urls.into_iter().map(JsValue::from).collect::<js_sys::Array>().into())
+        })
+    }
+
+    async fn capture_frames(
+        &mut self,
+        start_time: f64,
+        fps: f64,
+        frames: u32,
+        progress_callback: Option<&js_sys::Function>,
+    ) -> Result<Vec<String>, JsValue> {
+        let canvas = self
+            .context
+            .canvas()
+            .ok_or(EngineError::CanvasNotFound)?;
+        let frame_step = 1000.0 / fps;
+
+        let mut urls = Vec::with_capacity(frames as usize);
+        for frame_idx in 0..frames {
+            let t = start_time + f64::from(frame_idx) * frame_step;
+            self.seek_all(t);
+            self.fetch_data().await?;
+            // Seek again now that the chunks are cached, so positions are interpolated.
+            self.seek_all(t);
+            self.render()?;
+            urls.push(canvas.to_data_url_with_type("image/png")?);
+
+            if let Some(callback) = progress_callback {
+                callback.call2(
+                    &JsValue::NULL,
+                    &JsValue::from(frame_idx + 1),
+                    &JsValue::from(frames),
+                )?;
+            }
+        }
+        Ok(urls)
+    }
+
+    /// Generate `n` objects, animate them for `duration_ms` of real time and resolve with
+    /// `{ objects, frames_rendered, avg_fps, min_fps, max_fps, dropped_frames }`.
+    /// The regular loop (and its FPS / hit readouts) is suspended during the run, and
+    /// `clear_all_objects` is called afterwards, so run it against an otherwise empty engine.
+    #[wasm_bindgen]
+    pub fn stress_test(&self, n: u32, duration_ms: f64) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            engine.headless.set(true);
+            let result = engine.run_stress_test(n, duration_ms).await;
+            engine.headless.set(false);
+            let cleared = engine.clear_all_objects().await;
+
+            let stats = result?;
+            cleared?;
+            Ok(serde_wasm_bindgen::to_value(&stats)?)
+        })
+    }
+
+    async fn run_stress_test(&mut self, n: u32, duration_ms: f64) -> Result<StressTestResult, JsValue> {
+        self.generate_objects(n, STRESS_TEST_KEYFRAMES, STRESS_TEST_SIZE, None).await?;
+
+        let frame_budget = 1000.0 / 60.0;
+        let start = self.next_animation_frame().await?;
+        let mut last = start;
+        let mut frames_rendered: u32 = 0;
+        let mut min_delta = f64::MAX;
+        let mut max_delta = 0.0f64;
+        let mut dropped_frames = 0;
+        while last - start < duration_ms {
+            let now = self.next_animation_frame().await?;
+            let delta = now - last;
+            last = now;
+
+            self.fetch_data().await?;
+            self.update(delta)?;
+            self.render()?;
+
+            frames_rendered += 1;
+            min_delta = min_delta.min(delta);
+            max_delta = max_delta.max(delta);
+            if delta > frame_budget * DROPPED_FRAME_FACTOR {
+                dropped_frames += 1;
+            }
+        }
+
+        let fps_for = |delta: f64| if delta > 0.0 { 1000.0 / delta } else { 0.0 };
+        let elapsed = last - start;
+        Ok(StressTestResult {
+            objects: n,
+            frames_rendered,
+            avg_fps: if elapsed > 0.0 { f64::from(frames_rendered) * 1000.0 / elapsed } else { 0.0 },
+            min_fps: fps_for(max_delta),
+            max_fps: if frames_rendered > 0 { fps_for(min_delta) } else { 0.0 },
+            dropped_frames,
+        })
+    }
+
+    /// Drop the constraints, watchers, colour tweens, names and mouse drag of the objects
+    /// `removed` picks out, once they have left the pool. Objects left without a spring
+    /// stop their constraint motion.
+    fn forget_objects(&self, removed: impl Fn(u32) -> bool) {
+        let partners: Vec<u32> = self
+            .constraints
+            .borrow()
+            .iter()
+            .filter_map(|(a, b, _)| match (removed(*a), removed(*b)) {
+                (true, false) => Some(*b),
+                (false, true) => Some(*a),
+                _ => None,
+            })
+            .collect();
+        self.constraints.borrow_mut().retain(|(a, b, _)| !removed(*a) && !removed(*b));
+        self.stop_unsprung(&partners);
+        self.watchers.borrow_mut().retain(|watcher| !removed(watcher.object_id));
+        self.color_tweens.borrow_mut().retain(|id, _| !removed(*id));
+        self.name_registry.borrow_mut().retain(|_, id| !removed(*id));
+        if self.dragged_object.get().is_some_and(&removed) {
+            self.unbind_mouse_drag();
+        }
+        // Rebuilt on the next render; until then it would still report the objects.
+        *self.spatial_index.borrow_mut() = None;
+    }
+
+    /// Remove one object, with its constraints, watchers and colour tween, and delete its
+    /// keyframe and size track chunks. A shared track it was playing is kept. Rejects with
+    /// `ObjectNotFound` if there is no such object.
+    #[wasm_bindgen]
+    pub fn remove_object(&self, object_id: u32) -> js_sys::Promise {
+        let engine = self.share();
+        future_to_promise(async move {
+            let owned_tracks: Vec<String> = {
+                let mut objects = engine.objects.borrow_mut();
+                let obj = objects
+                    .iter()
+                    .find(|obj| obj.object_id() == object_id)
+                    .ok_or(EngineError::ObjectNotFound(object_id))?;
+                let store = obj.keyframe_store();
+                let own_track = (store.object_id() == object_id.to_string()).then(|| {
+                    store.invalidate();
+                    object_id.to_string()
+                });
+                let size_track = obj.size_store().map(|store| store.object_id().to_string());
+                objects.release_where(|obj| obj.object_id() == object_id);
+                own_track.into_iter().chain(size_track).collect()
+            };
+            engine.forget_objects(|id| id == object_id);
+
+            for track_id in owned_tracks {
+                engine
+                    .keyframe_db
+                    .delete_object_chunks(&track_id)
+                    .await
+                    .map_err(|e| EngineError::storage(format!("Failed to delete chunks for {}", track_id), e))?;
+            }
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Remove every object, with its constraints, watchers and colour tween, and delete its
+    /// keyframe chunks. Shared tracks are kept.
+    #[wasm_bindgen]
+    pub async fn clear_all_objects(&self) -> Result<(), JsValue> {
+        let owned_tracks: Vec<String> = {
+            let mut objects = self.objects.borrow_mut();
+            let owned_tracks = objects
+                .iter()
+                .filter(|obj| obj.keyframe_store().object_id() == obj.object_id().to_string())
+                .map(|obj| {
+                    obj.keyframe_store().invalidate();
+                    obj.object_id().to_string()
+                })
+                .chain(objects.iter().filter_map(|obj| obj.size_store().map(|store| store.object_id().to_string())))
+                .collect();
+            objects.release_all();
+            owned_tracks
+        };
+        self.lazy_objects.borrow_mut().clear();
+        self.forget_objects(|_| true);
+        for object_id in owned_tracks {
+            self.keyframe_db
+                .delete_object_chunks(&object_id)
+                .await
+                .map_err(|e| EngineError::storage(format!("Failed to delete chunks for {}", object_id), e))?;
+        }
+        Ok(())
+    }
+
+    /// Draw the scene as it looks at simulation time `t`, then put every object back at its
+    /// own time. Chunks for `t` are loaded first, hence the Promise. A running loop will
+    /// draw over the result on its next frame, so call `stop` first to keep it on screen.
+    #[wasm_bindgen]
+    pub fn freeze_at_time(&self, t: f64) -> js_sys::Promise {
+        let mut engine = self.share();
+        future_to_promise(async move {
+            let saved_times = engine.object_times();
+
+            engine.seek_all(t);
+            engine.fetch_data().await?;
+            // Seek again now that the chunks are cached, so positions are interpolated.
+            engine.seek_all(t);
+            let result = engine.render();
+
+            engine.restore_object_times(saved_times);
+            result.map(|_| JsValue::UNDEFINED)
+        })
+    }
+
+    fn seek_all(&self, t: f64) {
+        for obj in self.objects.borrow_mut().iter_mut() {
+            obj.seek(t);
+        }
+    }
+
+    /// Seek every object `elapsed` ms past its time in `start_times`, as `update` would
+    /// move it without stepping anything else. Paused objects, and objects added since
+    /// `start_times` was taken, stay where they are.
+    fn seek_all_from(&self, start_times: &HashMap<u32, f64>, elapsed: f64) {
+        for obj in self.objects.borrow_mut().iter_mut().filter(|obj| !obj.is_paused()) {
+            if let Some(&time) = start_times.get(&obj.object_id()) {
+                obj.seek(time + elapsed);
+            }
+        }
+    }
+
+    /// Every object's time, keyed by id: objects can be added or removed while an export
+    /// awaits, which moves them around the pool.
+    fn object_times(&self) -> HashMap<u32, f64> {
+        self.objects.borrow().iter().map(|obj| (obj.object_id(), obj.current_time())).collect()
+    }
+
+    /// Seek objects back to the times saved by `object_times`. Objects added since keep
+    /// their own time.
+    fn restore_object_times(&self, times: HashMap<u32, f64>) {
+        for obj in self.objects.borrow_mut().iter_mut() {
+            if let Some(&time) = times.get(&obj.object_id()) {
+                obj.seek(time);
+            }
+        }
+    }
+
+    /// Wrap GIF bytes from `export_as_gif` in a Blob and return an object URL for it.
+    #[wasm_bindgen]
+    pub fn create_gif_blob_url(bytes: &js_sys::Uint8Array) -> Result<String, JsValue> {
+        let parts = js_sys::Array::of1(bytes);
+        let options = web_sys::BlobPropertyBag::new();
+        options.set_type("image/gif");
+        let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+        web_sys::Url::create_object_url_with_blob(&blob)
+    }
+
+    async fn encode_gif(
+        &mut self,
+        start_times: &HashMap<u32, f64>,
+        frames: u32,
+        fps: f64,
+        width: u16,
+        height: u16,
+        progress_callback: Option<&js_sys::Function>,
+    ) -> Result<Vec<u8>, JsValue> {
+        let frame_step = 1000.0 / fps;
+        let delay = (100.0 / fps).round() as u16;
+        let gif_err = |e: gif::EncodingError| EngineError::Encoding { context: "GIF encoding", message: e.to_string() };
+
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[]).map_err(gif_err)?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_err)?;
+
+            for frame_idx in 0..frames {
+                let elapsed = f64::from(frame_idx) * frame_step;
+                self.seek_all_from(start_times, elapsed);
+                self.fetch_data().await?;
+                // Seek again now that the chunks are cached, so positions are interpolated.
+                self.seek_all_from(start_times, elapsed);
+                self.render()?;
+
+                let mut pixels = self.context
+                    .get_image_data(0.0, 0.0, width as f64, height as f64)?
+                    .data()
+                    .0;
+                let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+                frame.delay = delay;
+                encoder.write_frame(&frame).map_err(gif_err)?;
+
+                if let Some(callback) = progress_callback {
+                    callback.call2(
+                        &JsValue::NULL,
+                        &JsValue::from(frame_idx + 1),
+                        &JsValue::from(frames),
+                    )?;
+                }
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyframe::KeyframeChunk;
+
+    /// Deterministic stand-in for `Math.random`, cycling through fixed fractions.
+    fn fixed_rng(values: &'static [f64]) -> impl FnMut() -> f64 {
+        let mut i = 0;
+        move || {
+            let value = values[i % values.len()];
+            i += 1;
+            value
+        }
+    }
+
+    #[test]
+    fn generated_keyframes_keep_sub_pixel_positions() {
+        let span = 1000.0;
+        let keyframes = random_keyframes(3, span, span, fixed_rng(&[0.12345, 0.5, 0.987_654_3, 0.000_25]));
+
+        assert_eq!(keyframes.len(), 4);
+        assert_eq!(keyframes[0].time(), 0.0);
+        assert_eq!(keyframes[0].x(), 123.45);
+        assert_eq!(keyframes[0].y(), 500.0);
+        for k in &keyframes {
+            // Each coordinate is the f64 product narrowed to f32 once, with no rounding on the way.
+            let expected = [0.12345, 0.5, 0.987_654_3, 0.000_25].map(|r| (r * span) as f32);
+            assert!(expected.contains(&k.x()), "{} lost precision", k.x());
+            assert!(expected.contains(&k.y()), "{} lost precision", k.y());
+        }
+    }
+
+    #[test]
+    fn generated_keyframes_survive_chunking_and_storage() {
+        let keyframes = random_keyframes(200, 1920.0, 1080.0, fixed_rng(&[0.314_159, 0.271_828, 0.141_421, 0.577_215]));
+        let chunks = keyframe::split_into_chunks("gen", keyframes.clone(), 10_000.0);
+
+        let stored: Vec<KeyframeChunk> = chunks
+            .iter()
+            .map(|chunk| serde_json::from_str(&serde_json::to_string(chunk).unwrap()).unwrap())
+            .collect();
+        let restored: Vec<Keyframe> = stored.iter().flat_map(|chunk| chunk.keyframes().iter().cloned()).collect();
+
+        assert_eq!(restored, keyframes);
+        assert!(restored.iter().any(|k| k.x().fract() != 0.0));
+    }
+
+    /// Render tasks pushed when animation frames arrive at `display_hz` for one second.
+    fn frames_rendered(target_fps: Option<f64>, display_hz: f64) -> u32 {
+        let mut last_frame_time = 0.0;
+        let mut rendered = 0;
+        for frame in 1..=display_hz as u32 {
+            let timestamp = f64::from(frame) * 1000.0 / display_hz;
+            if frame_due(target_fps, timestamp - last_frame_time) {
+                last_frame_time = timestamp;
+                rendered += 1;
+            }
+        }
+        rendered
+    }
+
+    #[test]
+    fn uncapped_frames_are_always_due() {
+        assert!(frame_due(None, 0.0));
+        assert!(frame_due(None, 1.0));
+        assert_eq!(frames_rendered(None, 144.0), 144);
+    }
+
+    #[test]
+    fn frames_below_the_target_interval_are_gated() {
+        assert!(!frame_due(Some(30.0), 1000.0 / 60.0));
+        assert!(!frame_due(Some(30.0), 1000.0 / 30.0 - FRAME_LIMIT_SLACK_MS - 0.1));
+        assert!(frame_due(Some(30.0), 1000.0 / 30.0));
+        assert!(frame_due(Some(30.0), 100.0));
+    }
+
+    #[test]
+    fn frames_within_the_slack_still_render() {
+        assert!(frame_due(Some(60.0), 1000.0 / 60.0 - FRAME_LIMIT_SLACK_MS / 2.0));
+        assert_eq!(frames_rendered(Some(60.0), 60.0), 60);
+    }
+
+    #[test]
+    fn cap_limits_renders_on_fast_displays() {
+        assert_eq!(frames_rendered(Some(30.0), 60.0), 30);
+        let rendered = frames_rendered(Some(30.0), 144.0);
+        assert!((28..=30).contains(&rendered), "rendered {} frames", rendered);
+    }
+
+    #[test]
+    fn forces_with_a_finite_non_negative_duration_are_accepted() {
+        assert!(validate_force(1.0, -2.0, 250.0).is_ok());
+        assert!(validate_force(0.0, 0.0, 0.0).is_ok());
+    }
+
+    #[test]
+    fn forces_with_a_bad_duration_are_rejected() {
+        for duration_ms in [-1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(validate_force(1.0, 1.0, duration_ms).is_err(), "{} accepted", duration_ms);
+        }
+    }
+
+    #[test]
+    fn non_finite_forces_are_rejected() {
+        assert!(validate_force(f64::NAN, 0.0, 100.0).is_err());
+        assert!(validate_force(0.0, f64::INFINITY, 100.0).is_err());
+    }
+
+    #[test]
+    fn keyframes_inside_or_just_past_the_track_are_accepted() {
+        assert!(validate_keyframe(0.0, 1.0, 2.0, 100.0, 5).is_ok());
+        assert!(validate_keyframe(450.0, -1.0, 2.0, 100.0, 5).is_ok());
+        let furthest = f64::from(5 + MAX_KEYFRAME_GAP_CHUNKS) * 100.0;
+        assert!(validate_keyframe(furthest, 0.0, 0.0, 100.0, 5).is_ok());
+    }
+
+    #[test]
+    fn keyframes_too_far_past_the_track_are_rejected() {
+        let too_far = f64::from(6 + MAX_KEYFRAME_GAP_CHUNKS) * 100.0;
+        assert!(validate_keyframe(too_far, 0.0, 0.0, 100.0, 5).is_err());
+        assert!(validate_keyframe(f64::MAX, 0.0, 0.0, 100.0, 5).is_err());
+    }
+
+    #[test]
+    fn keyframes_with_bad_times_or_positions_are_rejected() {
+        for time in [-1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(validate_keyframe(time, 0.0, 0.0, 100.0, 1).is_err(), "{} accepted", time);
+        }
+        assert!(validate_keyframe(10.0, f64::NAN, 0.0, 100.0, 1).is_err());
+        assert!(validate_keyframe(10.0, 0.0, f64::INFINITY, 100.0, 1).is_err());
+    }
+
+    #[test]
+    fn path_steps_cover_the_duration_at_60_hz() {
+        assert_eq!(path_keyframe_steps(1000.0), Ok(60));
+        assert_eq!(path_keyframe_steps(1.0), Ok(1));
+    }
+
+    #[test]
+    fn path_steps_are_capped() {
+        let longest = f64::from(MAX_PATH_KEYFRAMES - 1) * PATH_KEYFRAME_STEP_MS;
+        assert_eq!(path_keyframe_steps(longest), Ok(MAX_PATH_KEYFRAMES - 1));
+        assert!(path_keyframe_steps(longest + 1.0).is_err());
+        assert!(path_keyframe_steps(1e300).is_err());
+    }
+
+    #[test]
+    fn path_durations_must_be_positive_and_finite() {
+        for duration_ms in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+            assert!(path_keyframe_steps(duration_ms).is_err(), "{} accepted", duration_ms);
+        }
+    }
+
+    /// Camera offsets and zooms covering identity, pans, zooming in and out.
+    const CAMERAS: [((f64, f64), f64); 5] = [
+        ((0.0, 0.0), 1.0),
+        ((250.0, -80.0), 1.0),
+        ((0.0, 0.0), 2.5),
+        ((-1_000.5, 333.25), 0.4),
+        ((12.0, 34.0), 7.0),
+    ];
+
+    fn assert_close(actual: &Vector2, expected: &Vector2) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-9 && (actual.y - expected.y).abs() < 1e-9,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn screen_to_world_with_a_known_camera() {
+        assert_eq!(screen_to_world(100.0, 50.0, (10.0, 20.0), 2.0), Vector2::new(60.0, 45.0));
+        assert_eq!(world_to_screen(60.0, 45.0, (10.0, 20.0), 2.0), Vector2::new(100.0, 50.0));
+    }
+
+    #[test]
+    fn screen_to_world_and_back_is_the_identity() {
+        for (camera, zoom) in CAMERAS {
+            for (sx, sy) in [(0.0, 0.0), (800.0, 600.0), (-15.5, 1234.75)] {
+                let world = screen_to_world(sx, sy, camera, zoom);
+                assert_close(&world_to_screen(world.x, world.y, camera, zoom), &Vector2::new(sx, sy));
+            }
+        }
+    }
+
+    #[test]
+    fn world_to_screen_and_back_is_the_identity() {
+        for (camera, zoom) in CAMERAS {
+            for (wx, wy) in [(0.0, 0.0), (-500.0, 42.0), (1e6, -1e6)] {
+                let screen = world_to_screen(wx, wy, camera, zoom);
+                let back = screen_to_world(screen.x, screen.y, camera, zoom);
+                assert!((back.x - wx).abs() < 1e-6 && (back.y - wy).abs() < 1e-6, "{:?} != ({}, {})", back, wx, wy);
+            }
+        }
+    }
+
+    #[test]
+    fn point_conversion_matches_aabb_conversion() {
+        for (camera, zoom) in CAMERAS {
+            let world = AABB::new(10.0, 20.0, 30.0, 40.0).to_world(camera.0, camera.1, zoom);
+            assert_close(&screen_to_world(10.0, 20.0, camera, zoom), &Vector2::new(world.min_x(), world.min_y()));
+            assert_close(&screen_to_world(30.0, 40.0, camera, zoom), &Vector2::new(world.max_x(), world.max_y()));
+        }
+    }
+
+    #[test]
+    fn generated_times_increase() {
+        let keyframes = random_keyframes(50, 10.0, 10.0, fixed_rng(&[0.9, 0.1, 0.4]));
+        assert!(keyframes.windows(2).all(|pair| pair[0].time() <= pair[1].time()));
+    }
 }
@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use thiserror::Error;
+use wasm_bindgen::JsValue;
+
+/// Errors raised by `Rust2DEngine`. They reach JS as strings via the `JsValue` conversion.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum EngineError {
+    #[error("no global `window`")]
+    WindowNotFound,
+    #[error("no `document`")]
+    DocumentNotFound,
+    #[error("canvas not found")]
+    CanvasNotFound,
+    #[error("failed to get 2d context")]
+    ContextCreationFailed,
+    #[error("KeyframeDatabase init failed: {0}")]
+    DatabaseInitFailed(String),
+    #[error("engine is already running")]
+    AlreadyRunning,
+    #[error("Object {0} not found")]
+    ObjectNotFound(u32),
+    #[error("Object {0} has no keyframes")]
+    NoKeyframes(u32),
+    #[error("Shared track {0} not found")]
+    SharedTrackNotFound(String),
+    #[error("Object {object_id} plays shared track {track_id} and can't be {action} on its own")]
+    SharedTrack { object_id: u32, track_id: String, action: &'static str },
+    #[error("Element #{0} not found")]
+    ElementNotFound(String),
+    /// A parameter was out of range or an option string wasn't recognised.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// An IndexedDB operation failed; `context` says what the engine was doing.
+    #[error("{context}: {message}")]
+    Storage { context: String, message: String },
+    #[error("{context} failed: {message}")]
+    Encoding { context: &'static str, message: String },
+}
+
+impl EngineError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        EngineError::InvalidArgument(message.into())
+    }
+
+    pub fn storage(context: impl Into<String>, error: impl Display) -> Self {
+        EngineError::Storage {
+            context: context.into(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<EngineError> for JsValue {
+    fn from(error: EngineError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_for_each_variant() {
+        let cases = [
+            (EngineError::WindowNotFound, "no global `window`"),
+            (EngineError::DocumentNotFound, "no `document`"),
+            (EngineError::CanvasNotFound, "canvas not found"),
+            (EngineError::ContextCreationFailed, "failed to get 2d context"),
+            (EngineError::DatabaseInitFailed("blocked".into()), "KeyframeDatabase init failed: blocked"),
+            (EngineError::AlreadyRunning, "engine is already running"),
+            (EngineError::ObjectNotFound(7), "Object 7 not found"),
+            (EngineError::NoKeyframes(3), "Object 3 has no keyframes"),
+            (EngineError::SharedTrackNotFound("walk".into()), "Shared track walk not found"),
+            (
+                EngineError::SharedTrack { object_id: 2, track_id: "walk".into(), action: "rechunked" },
+                "Object 2 plays shared track walk and can't be rechunked on its own",
+            ),
+            (EngineError::ElementNotFound("canvas".into()), "Element #canvas not found"),
+            (EngineError::InvalidArgument("zoom must be positive".into()), "zoom must be positive"),
+            (
+                EngineError::Storage { context: "Saving chunk 4".into(), message: "QuotaExceededError".into() },
+                "Saving chunk 4: QuotaExceededError",
+            ),
+            (
+                EngineError::Encoding { context: "Scene export", message: "bad float".into() },
+                "Scene export failed: bad float",
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn invalid_wraps_the_message() {
+        assert_eq!(EngineError::invalid("bad phase"), EngineError::InvalidArgument("bad phase".into()));
+    }
+
+    #[test]
+    fn storage_keeps_the_context_and_formats_the_error() {
+        let error = EngineError::storage("Loading obj_1", 42);
+        assert_eq!(error, EngineError::Storage { context: "Loading obj_1".into(), message: "42".into() });
+        assert_eq!(error.to_string(), "Loading obj_1: 42");
+    }
+}
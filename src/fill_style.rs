@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasGradient, CanvasRenderingContext2d};
+
+/// One colour stop of a gradient; `offset` runs from 0 at the start to 1 at the end.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    pub offset: f64,
+    pub color: String,
+}
+
+/// A gradient fill. Coordinates are in object-local space, with (0, 0) at the object's
+/// top-left corner, so the gradient moves with the object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FillStyle {
+    LinearGradient { x0: f64, y0: f64, x1: f64, y1: f64, stops: Vec<ColorStop> },
+    /// Fades from the first stop at the centre to the last at radius `cr`.
+    RadialGradient { cx: f64, cy: f64, cr: f64, stops: Vec<ColorStop> },
+}
+
+impl FillStyle {
+    /// Check the stops and coordinates, and that the browser accepts every stop colour.
+    pub fn validate(&self, context: &CanvasRenderingContext2d) -> Result<(), String> {
+        let (stops, coords) = match self {
+            FillStyle::LinearGradient { x0, y0, x1, y1, stops } => (stops, vec![*x0, *y0, *x1, *y1]),
+            FillStyle::RadialGradient { cx, cy, cr, stops } => {
+                if cr.is_nan() || *cr <= 0.0 {
+                    return Err(format!("Gradient radius must be positive, got {}", cr));
+                }
+                (stops, vec![*cx, *cy, *cr])
+            }
+        };
+        if coords.iter().any(|c| !c.is_finite()) {
+            return Err("Gradient coordinates must be finite".to_string());
+        }
+        if stops.is_empty() {
+            return Err("A gradient needs at least one colour stop".to_string());
+        }
+        let probe = context.create_linear_gradient(0.0, 0.0, 1.0, 0.0);
+        for stop in stops {
+            if !(0.0..=1.0).contains(&stop.offset) {
+                return Err(format!("Colour stop offset must be within [0, 1], got {}", stop.offset));
+            }
+            if probe.add_color_stop(stop.offset as f32, &stop.color).is_err() {
+                return Err(format!("Invalid colour stop colour '{}'", stop.color));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the gradient for an object whose top-left corner is at (x, y).
+    pub fn create(&self, context: &CanvasRenderingContext2d, x: f64, y: f64) -> Result<CanvasGradient, JsValue> {
+        let (gradient, stops) = match self {
+            FillStyle::LinearGradient { x0, y0, x1, y1, stops } => {
+                (context.create_linear_gradient(x + x0, y + y0, x + x1, y + y1), stops)
+            }
+            FillStyle::RadialGradient { cx, cy, cr, stops } => {
+                (context.create_radial_gradient(x + cx, y + cy, 0.0, x + cx, y + cy, *cr)?, stops)
+            }
+        };
+        for stop in stops {
+            gradient.add_color_stop(stop.offset as f32, &stop.color)?;
+        }
+        Ok(gradient)
+    }
+}
@@ -0,0 +1,77 @@
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+const OVERLAY_ALPHA: f64 = 0.4;
+
+/// Per-cell occupancy counts accumulated over update ticks.
+pub struct HeatMap {
+    grid: Vec<Vec<u32>>,
+    cell_size: f64,
+    max_count: u32,
+}
+
+impl HeatMap {
+    pub fn new(width: f64, height: f64, cell_size: f64) -> Self {
+        let cols = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+        Self {
+            grid: vec![vec![0; cols]; rows],
+            cell_size,
+            max_count: 0,
+        }
+    }
+
+    /// Count one occupancy sample for the cell containing (x, y); points off the grid are ignored.
+    pub fn record(&mut self, x: f64, y: f64) {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        let row = (y / self.cell_size) as usize;
+        let col = (x / self.cell_size) as usize;
+        if let Some(cell) = self.grid.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *cell += 1;
+            self.max_count = self.max_count.max(*cell);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for row in self.grid.iter_mut() {
+            row.fill(0);
+        }
+        self.max_count = 0;
+    }
+
+    pub fn grid(&self) -> &Vec<Vec<u32>> {
+        &self.grid
+    }
+
+    /// Draw every non-empty cell, shading from blue (rare) to red (most occupied).
+    pub fn render(&self, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        if self.max_count == 0 {
+            return Ok(());
+        }
+
+        for (row, cells) in self.grid.iter().enumerate() {
+            for (col, &count) in cells.iter().enumerate() {
+                if count == 0 {
+                    continue;
+                }
+                let ratio = count as f64 / self.max_count as f64;
+                let color = format!(
+                    "rgba({}, 0, {}, {})",
+                    (255.0 * ratio).round(),
+                    (255.0 * (1.0 - ratio)).round(),
+                    OVERLAY_ALPHA
+                );
+                context.set_fill_style_str(&color);
+                context.fill_rect(
+                    col as f64 * self.cell_size,
+                    row as f64 * self.cell_size,
+                    self.cell_size,
+                    self.cell_size,
+                );
+            }
+        }
+        Ok(())
+    }
+}
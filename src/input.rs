@@ -1,85 +1,437 @@
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
-use web_sys::{MouseEvent, HtmlCanvasElement};
+use web_sys::{Gamepad, GamepadButton, KeyboardEvent, MouseEvent, HtmlCanvasElement, PointerEvent, Window};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 
-pub struct InputHandler {
+type MouseListener = Closure<dyn FnMut(MouseEvent)>;
+type KeyListener = Closure<dyn FnMut(KeyboardEvent)>;
+type PointerListener = Closure<dyn FnMut(PointerEvent)>;
+
+/// Axis readings closer to rest than this are treated as 0 to hide stick drift.
+const GAMEPAD_DEAD_ZONE: f64 = 0.1;
+
+/// Snapshot of one gamepad, taken by `InputHandler::poll_gamepad`.
+#[derive(Clone, Debug, Default)]
+pub struct GamepadState {
+    pub axes: Vec<f64>,
+    pub buttons: Vec<bool>,
+}
+
+impl GamepadState {
+    fn from_gamepad(gamepad: &Gamepad) -> Self {
+        let axes = gamepad
+            .axes()
+            .iter()
+            .map(|axis| axis.as_f64().unwrap_or(0.0))
+            .collect();
+        let buttons = gamepad
+            .buttons()
+            .iter()
+            .map(|button| button.dyn_into::<GamepadButton>().is_ok_and(|b| b.pressed()))
+            .collect();
+        Self { axes, buttons }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum InputEvent {
+    MouseMove(f64, f64),
+    MouseDown(usize),
+    MouseUp(usize),
+    KeyDown(String),
+    KeyUp(String),
+}
+
+/// Captures timestamped input events and plays them back on the same timeline.
+#[derive(Default)]
+pub struct InputRecorder {
+    events: Vec<(f64, InputEvent)>,
+    recording: bool,
+    replaying: bool,
+    replay_index: usize,
+    start_time: f64,
+}
+
+impl InputRecorder {
+    pub fn start_recording(&mut self, now: f64) {
+        self.events.clear();
+        self.recording = true;
+        self.replaying = false;
+        self.start_time = now;
+    }
+
+    pub fn stop_recording(&mut self) -> Vec<(f64, InputEvent)> {
+        self.recording = false;
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn start_replay(&mut self, events: Vec<(f64, InputEvent)>, now: f64) {
+        self.events = events;
+        self.recording = false;
+        self.replaying = true;
+        self.replay_index = 0;
+        self.start_time = now;
+    }
+
+    fn record(&mut self, event: InputEvent, now: f64) {
+        self.events.push((now - self.start_time, event));
+    }
+
+    /// Events whose timestamp has been reached by `now`; ends the replay after the last one.
+    fn due_events(&mut self, now: f64) -> Vec<InputEvent> {
+        if !self.replaying {
+            return Vec::new();
+        }
+        let elapsed = now - self.start_time;
+        let mut due = Vec::new();
+        while let Some((time, event)) = self.events.get(self.replay_index) {
+            if *time > elapsed {
+                break;
+            }
+            due.push(event.clone());
+            self.replay_index += 1;
+        }
+        if self.replay_index >= self.events.len() {
+            self.replaying = false;
+        }
+        due
+    }
+}
+
+/// Input state shared between the handler and its event listeners.
+#[derive(Clone)]
+struct InputState {
     mouse_position: Rc<RefCell<crate::math::Vector2>>,
     mouse_buttons: Rc<RefCell<Vec<bool>>>,
+    keys: Rc<RefCell<HashSet<String>>>,
+    recorder: Rc<RefCell<InputRecorder>>,
+}
+
+impl InputState {
+    /// Entry point for live browser events. They are recorded while recording and
+    /// ignored while a replay is driving the input.
+    fn dispatch(&self, event: InputEvent) {
+        {
+            let mut recorder = self.recorder.borrow_mut();
+            if recorder.replaying {
+                return;
+            }
+            if recorder.recording {
+                recorder.record(event.clone(), now());
+            }
+        }
+        self.apply(&event);
+    }
+
+    /// Release every held mouse button, e.g. when the canvas loses pointer capture and the
+    /// matching `mouseup` may never arrive.
+    fn release_mouse_buttons(&self) {
+        let pressed: Vec<usize> = self
+            .mouse_buttons
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, pressed)| **pressed)
+            .map(|(button, _)| button)
+            .collect();
+        for button in pressed {
+            self.dispatch(InputEvent::MouseUp(button));
+        }
+    }
+
+    fn apply(&self, event: &InputEvent) {
+        match event {
+            InputEvent::MouseMove(x, y) => {
+                *self.mouse_position.borrow_mut() = crate::math::Vector2::new(*x, *y);
+            }
+            InputEvent::MouseDown(button) => {
+                if *button < 3 {
+                    self.mouse_buttons.borrow_mut()[*button] = true;
+                }
+            }
+            InputEvent::MouseUp(button) => {
+                if *button < 3 {
+                    self.mouse_buttons.borrow_mut()[*button] = false;
+                }
+            }
+            InputEvent::KeyDown(key) => {
+                self.keys.borrow_mut().insert(key.clone());
+            }
+            InputEvent::KeyUp(key) => {
+                self.keys.borrow_mut().remove(key);
+            }
+        }
+    }
+}
+
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0)
+}
+
+pub struct InputHandler {
+    canvas: HtmlCanvasElement,
+    window: Window,
+    listeners: Vec<(String, MouseListener)>,
+    key_listeners: Vec<(String, KeyListener)>,
+    pointer_listeners: Vec<(String, PointerListener)>,
+    state: InputState,
+    gamepads: RefCell<Vec<Option<GamepadState>>>,
+    /// Keys held at the previous and the latest `tick`, for edge detection.
+    prev_keys: RefCell<HashSet<String>>,
+    frame_keys: RefCell<HashSet<String>>,
 }
 
 impl InputHandler {
     pub fn new(canvas: &HtmlCanvasElement) -> Result<Self, JsValue> {
-        let mouse_position = Rc::new(RefCell::new(crate::math::Vector2::new(0.0, 0.0)));
-        let mouse_buttons = Rc::new(RefCell::new(vec![false, false, false]));
-        
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window`"))?;
+        let state = InputState {
+            mouse_position: Rc::new(RefCell::new(crate::math::Vector2::new(0.0, 0.0))),
+            mouse_buttons: Rc::new(RefCell::new(vec![false, false, false])),
+            keys: Rc::new(RefCell::new(HashSet::new())),
+            recorder: Rc::new(RefCell::new(InputRecorder::default())),
+        };
+        let mut listeners: Vec<(String, MouseListener)> = Vec::new();
+        let mut key_listeners: Vec<(String, KeyListener)> = Vec::new();
+        let mut pointer_listeners: Vec<(String, PointerListener)> = Vec::new();
+
         {
-            let mouse_position_clone = Rc::clone(&mouse_position);
-            
+            let input = state.clone();
+
             let mousemove_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
                 // Get canvas rect using canvas.getBoundingClientRect()
                 let target = event.target().unwrap();
                 let canvas = target.dyn_ref::<HtmlCanvasElement>().unwrap();
-                
+
                 let rect = canvas.get_bounding_client_rect();
-                
+
                 let x = event.client_x() as f64 - rect.left();
                 let y = event.client_y() as f64 - rect.top();
-                
-                *mouse_position_clone.borrow_mut() = crate::math::Vector2::new(x, y);
+
+                input.dispatch(InputEvent::MouseMove(x, y));
             }) as Box<dyn FnMut(_)>);
-            
+
             canvas.add_event_listener_with_callback(
                 "mousemove",
                 mousemove_callback.as_ref().unchecked_ref(),
             )?;
-            mousemove_callback.forget();
-            
-            let buttons = Rc::clone(&mouse_buttons);
+            listeners.push(("mousemove".to_string(), mousemove_callback));
+
+            let input = state.clone();
             let mousedown_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
-                let button = event.button() as usize;
-                if button < 3 {
-                    buttons.borrow_mut()[button] = true;
-                }
+                input.dispatch(InputEvent::MouseDown(event.button() as usize));
             }) as Box<dyn FnMut(_)>);
-            
+
             canvas.add_event_listener_with_callback(
                 "mousedown",
                 mousedown_callback.as_ref().unchecked_ref(),
             )?;
-            mousedown_callback.forget();
-            
-            let buttons = Rc::clone(&mouse_buttons);
+            listeners.push(("mousedown".to_string(), mousedown_callback));
+
+            let input = state.clone();
             let mouseup_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
-                let button = event.button() as usize;
-                if button < 3 {
-                    buttons.borrow_mut()[button] = false;
-                }
+                input.dispatch(InputEvent::MouseUp(event.button() as usize));
             }) as Box<dyn FnMut(_)>);
-            
+
             canvas.add_event_listener_with_callback(
                 "mouseup",
                 mouseup_callback.as_ref().unchecked_ref(),
             )?;
-            mouseup_callback.forget();
+            listeners.push(("mouseup".to_string(), mouseup_callback));
+
+            // Capturing the pointer keeps move and up events coming to the canvas while a
+            // drag leaves it, so a release outside the canvas isn't missed.
+            let target = canvas.clone();
+            let pointerdown_callback = Closure::wrap(Box::new(move |event: PointerEvent| {
+                let _ = target.set_pointer_capture(event.pointer_id());
+            }) as Box<dyn FnMut(_)>);
+
+            canvas.add_event_listener_with_callback(
+                "pointerdown",
+                pointerdown_callback.as_ref().unchecked_ref(),
+            )?;
+            pointer_listeners.push(("pointerdown".to_string(), pointerdown_callback));
+
+            let target = canvas.clone();
+            let pointerup_callback = Closure::wrap(Box::new(move |event: PointerEvent| {
+                if target.has_pointer_capture(event.pointer_id()) {
+                    let _ = target.release_pointer_capture(event.pointer_id());
+                }
+            }) as Box<dyn FnMut(_)>);
+
+            canvas.add_event_listener_with_callback(
+                "pointerup",
+                pointerup_callback.as_ref().unchecked_ref(),
+            )?;
+            pointer_listeners.push(("pointerup".to_string(), pointerup_callback));
+
+            // Capture can also be lost without a release, e.g. when the browser cancels the
+            // pointer; treat that as letting go of every button so a drag can't get stuck.
+            let input = state.clone();
+            let lostpointercapture_callback = Closure::wrap(Box::new(move |_event: PointerEvent| {
+                input.release_mouse_buttons();
+            }) as Box<dyn FnMut(_)>);
+
+            canvas.add_event_listener_with_callback(
+                "lostpointercapture",
+                lostpointercapture_callback.as_ref().unchecked_ref(),
+            )?;
+            pointer_listeners.push(("lostpointercapture".to_string(), lostpointercapture_callback));
+
+            // The canvas never has keyboard focus, so key events are taken from the window.
+            let input = state.clone();
+            let keydown_callback = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+                input.dispatch(InputEvent::KeyDown(event.key()));
+            }) as Box<dyn FnMut(_)>);
+
+            window.add_event_listener_with_callback(
+                "keydown",
+                keydown_callback.as_ref().unchecked_ref(),
+            )?;
+            key_listeners.push(("keydown".to_string(), keydown_callback));
+
+            let input = state.clone();
+            let keyup_callback = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+                input.dispatch(InputEvent::KeyUp(event.key()));
+            }) as Box<dyn FnMut(_)>);
+
+            window.add_event_listener_with_callback(
+                "keyup",
+                keyup_callback.as_ref().unchecked_ref(),
+            )?;
+            key_listeners.push(("keyup".to_string(), keyup_callback));
         }
-        
+
         Ok(InputHandler {
-            mouse_position,
-            mouse_buttons,
+            canvas: canvas.clone(),
+            window,
+            listeners,
+            key_listeners,
+            pointer_listeners,
+            state,
+            gamepads: RefCell::new(Vec::new()),
+            prev_keys: RefCell::new(HashSet::new()),
+            frame_keys: RefCell::new(HashSet::new()),
         })
     }
-    
+
     pub fn get_mouse_position(&self) -> crate::math::Vector2 {
-        let position = self.mouse_position.borrow();
+        let position = self.state.mouse_position.borrow();
         crate::math::Vector2::new(position.x, position.y)
     }
-    
+
     pub fn is_mouse_button_pressed(&self, button: usize) -> bool {
         if button < 3 {
-            self.mouse_buttons.borrow()[button]
+            self.state.mouse_buttons.borrow()[button]
         } else {
             false
         }
     }
-}
\ No newline at end of file
+
+    pub fn start_recording(&self) {
+        self.state.recorder.borrow_mut().start_recording(now());
+    }
+
+    pub fn stop_recording(&self) -> Vec<(f64, InputEvent)> {
+        self.state.recorder.borrow_mut().stop_recording()
+    }
+
+    pub fn start_replay(&self, events: Vec<(f64, InputEvent)>) {
+        self.state.recorder.borrow_mut().start_replay(events, now());
+    }
+
+    /// Refresh the gamepad snapshots. Browsers only expose gamepad state by polling,
+    /// so this is called once per frame.
+    pub fn poll_gamepad(&self) -> Result<(), JsValue> {
+        let pads = self.window.navigator().get_gamepads()?;
+        let states = pads
+            .iter()
+            .map(|pad| {
+                pad.dyn_into::<Gamepad>()
+                    .ok()
+                    .filter(|gamepad| gamepad.connected())
+                    .map(|gamepad| GamepadState::from_gamepad(&gamepad))
+            })
+            .collect();
+        *self.gamepads.borrow_mut() = states;
+        Ok(())
+    }
+
+    /// Axis value in [-1, 1] with the dead zone applied; 0 for unknown pads or axes.
+    pub fn get_gamepad_axis(&self, gamepad_index: u32, axis: u32) -> f64 {
+        let value = self
+            .gamepads
+            .borrow()
+            .get(gamepad_index as usize)
+            .and_then(Option::as_ref)
+            .and_then(|pad| pad.axes.get(axis as usize).copied())
+            .unwrap_or(0.0);
+        if value.abs() < GAMEPAD_DEAD_ZONE {
+            0.0
+        } else {
+            value
+        }
+    }
+
+    pub fn is_gamepad_button_pressed(&self, gamepad_index: u32, button: u32) -> bool {
+        self.gamepads
+            .borrow()
+            .get(gamepad_index as usize)
+            .and_then(Option::as_ref)
+            .and_then(|pad| pad.buttons.get(button as usize).copied())
+            .unwrap_or(false)
+    }
+
+    /// Number of connected gamepads.
+    pub fn gamepad_count(&self) -> u32 {
+        self.gamepads.borrow().iter().flatten().count() as u32
+    }
+
+    /// Inject every replayed event that is due by `now` and take the frame's key snapshot.
+    /// Called once per frame.
+    pub fn tick(&self, now: f64) {
+        let due = self.state.recorder.borrow_mut().due_events(now);
+        for event in &due {
+            self.state.apply(event);
+        }
+        let current = self.state.keys.borrow().clone();
+        *self.prev_keys.borrow_mut() = std::mem::replace(&mut *self.frame_keys.borrow_mut(), current);
+    }
+
+    /// Whether `key` went down since the previous frame. Stays true for the whole frame.
+    pub fn is_key_just_pressed(&self, key: &str) -> bool {
+        self.frame_keys.borrow().contains(key) && !self.prev_keys.borrow().contains(key)
+    }
+
+    /// Whether `key` was released since the previous frame. Stays true for the whole frame.
+    pub fn is_key_just_released(&self, key: &str) -> bool {
+        self.prev_keys.borrow().contains(key) && !self.frame_keys.borrow().contains(key)
+    }
+}
+
+impl Drop for InputHandler {
+    fn drop(&mut self) {
+        for (name, listener) in self.listeners.drain(..) {
+            let _ = self.canvas.remove_event_listener_with_callback(
+                &name,
+                listener.as_ref().unchecked_ref(),
+            );
+        }
+        for (name, listener) in self.pointer_listeners.drain(..) {
+            let _ = self.canvas.remove_event_listener_with_callback(
+                &name,
+                listener.as_ref().unchecked_ref(),
+            );
+        }
+        for (name, listener) in self.key_listeners.drain(..) {
+            let _ = self.window.remove_event_listener_with_callback(
+                &name,
+                listener.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
@@ -1,22 +1,121 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
+use crate::easing::Easing;
 use crate::math::Vector2;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Keyframe {
     time: f32,
     x: f32,
     y: f32,
+    /// Easing of the transition from this keyframe to the next one.
+    #[serde(default = "Easing::default")]
+    easing: Easing,
 }
 
 impl Keyframe {
     pub fn new(time: f32, x: f32, y: f32) -> Keyframe {
-        Keyframe { time, x, y }
+        Keyframe::new_with_easing(time, x, y, Easing::Linear)
+    }
+
+    pub fn new_with_easing(time: f32, x: f32, y: f32, easing: Easing) -> Keyframe {
+        Keyframe { time, x, y, easing }
     }
 
     pub fn time(&self) -> f32 { self.time }
     pub fn x(&self) -> f32 { self.x }
     pub fn y(&self) -> f32 { self.y }
+    pub fn easing(&self) -> Easing { self.easing }
+
+    /// Move the keyframe in time. The owning chunk must be re-sorted afterwards, see
+    /// `KeyframeChunk::ensure_sorted`.
+    pub fn set_time(&mut self, t: f64) -> Result<(), String> {
+        let time = t as f32;
+        if !time.is_finite() || time < 0.0 {
+            return Err(format!("Keyframe time must be a finite non-negative number, got {}", t));
+        }
+        self.time = time;
+        Ok(())
+    }
+
+    pub fn set_position(&mut self, x: f64, y: f64) -> Result<(), String> {
+        let (new_x, new_y) = (x as f32, y as f32);
+        if !new_x.is_finite() || !new_y.is_finite() {
+            return Err(format!("Keyframe position must be finite, got ({}, {})", x, y));
+        }
+        self.x = new_x;
+        self.y = new_y;
+        Ok(())
+    }
+
+    pub fn set_x(&mut self, x: f64) -> Result<(), String> {
+        self.set_position(x, self.y.into())
+    }
+
+    pub fn set_y(&mut self, y: f64) -> Result<(), String> {
+        self.set_position(self.x.into(), y)
+    }
+
+    /// The keyframe a fraction `t` (clamped to [0, 1]) of the way from `a` to `b`. Time is
+    /// interpolated linearly and position along `a`'s easing, matching what playback shows
+    /// at that time. The result keeps `a`'s easing.
+    pub fn interpolated_between(a: &Keyframe, b: &Keyframe, t: f64) -> Keyframe {
+        let t = t.clamp(0.0, 1.0);
+        let eased = a.easing.apply(t) as f32;
+        let t = t as f32;
+        Keyframe {
+            time: a.time + (b.time - a.time) * t,
+            x: a.x + (b.x - a.x) * eased,
+            y: a.y + (b.y - a.y) * eased,
+            easing: a.easing,
+        }
+    }
+
+    /// `interpolated_between(a, b, 0.5)`.
+    pub fn midpoint(a: &Keyframe, b: &Keyframe) -> Keyframe {
+        Keyframe::interpolated_between(a, b, 0.5)
+    }
+
+    /// Velocity, in pixels per ms, at `time` on the eased transition from this keyframe to `next`.
+    pub fn velocity_to(&self, next: &Keyframe, time: f32) -> Vector2 {
+        let span = next.time - self.time;
+        if span <= 0.0 {
+            return Vector2::new(0.0, 0.0);
+        }
+        let slope = self.easing.derivative(((time - self.time) / span) as f64) as f32;
+        Vector2::new(
+            ((next.x - self.x) / span * slope).into(),
+            ((next.y - self.y) / span * slope).into(),
+        )
+    }
+}
+
+/// Why `KeyframeChunk::validate` rejected a chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// `object_chunk_id` is not of the form `{id}_{chunk}`.
+    MalformedId(String),
+    InvertedRange { start_time: f32, end_time: f32 },
+    NonFiniteValue { index: usize },
+    OutOfRange { index: usize, time: f32 },
+    Unsorted { index: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MalformedId(id) => write!(f, "chunk id '{}' is not of the form {{id}}_{{chunk}}", id),
+            ValidationError::InvertedRange { start_time, end_time } => {
+                write!(f, "chunk starts at {} after it ends at {}", start_time, end_time)
+            }
+            ValidationError::NonFiniteValue { index } => write!(f, "keyframe {} has a non-finite value", index),
+            ValidationError::OutOfRange { index, time } => {
+                write!(f, "keyframe {} at {} lies outside the chunk's time range", index, time)
+            }
+            ValidationError::Unsorted { index } => write!(f, "keyframe {} is earlier than the one before it", index),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,6 +124,16 @@ pub struct KeyframeChunk {
     start_time: f32,
     end_time: f32,
     keyframes: Vec<Keyframe>,
+    /// Keyframes are evenly spaced from `start_time` to `end_time`, see `bake_to_fixed_timestep`.
+    #[serde(default)]
+    baked: bool,
+    /// Adler-32 of the keyframes, set by `seal` when the chunk is saved. 0 for chunks
+    /// written before checksums existed, which are not verified.
+    #[serde(default)]
+    checksum: u32,
+    /// Keyframes were changed in place and may be out of order; see `ensure_sorted`.
+    #[serde(skip)]
+    dirty: bool,
 }
 
 impl KeyframeChunk {
@@ -34,19 +143,36 @@ impl KeyframeChunk {
             start_time,
             end_time,
             keyframes: keyframes,
+            baked: false,
+            checksum: 0,
+            dirty: false,
         }
     }
 
-    // pub fn add_keyframe(&mut self, time: f32, x: f32, y: f32) {
-    //     if time >= self.start_time && time <= self.end_time {
-    //         self.keyframes.push(Keyframe { time, x, y });
-    //     }
-    // }
+    /// Chunk `chunk_idx` of a track with no keyframes in its time range. Tracks are stored
+    /// as chunks `0..chunk_count` without holes, so a gap between keyframes wider than a
+    /// chunk is bridged with these. The range is empty and grows with `add_keyframe`.
+    pub fn placeholder(object_id: &str, chunk_idx: u32, chunk_size: f32) -> Self {
+        let start_time = chunk_idx as f32 * chunk_size;
+        KeyframeChunk::new(&format!("{}_{}", object_id, chunk_idx), start_time, start_time, Vec::new())
+    }
+
+    /// Insert a keyframe in time order, widening the chunk's time range if needed.
+    pub fn add_keyframe(&mut self, time: f32, x: f32, y: f32) {
+        let idx = self.keyframes.partition_point(|k| k.time() <= time);
+        self.keyframes.insert(idx, Keyframe::new(time, x, y));
+        self.baked = false;
+        self.start_time = self.start_time.min(time);
+        self.end_time = self.end_time.max(time);
+    }
 
     pub fn interpolate(&self, time: f32) -> Vector2 {
         if self.keyframes.is_empty() {
             return Vector2::new(0.0, 0.0);
         }
+        if self.baked && self.keyframes.len() > 1 {
+            return self.interpolate_baked(time);
+        }
 
         // Clamp time within chunk bounds
         let t = if time < self.start_time {
@@ -70,7 +196,7 @@ impl KeyframeChunk {
                 // found the interval [prev, next]
                 let span = next.time() - prev.time();
                 let ratio = if span > 0.0 {
-                    (t - prev.time()) / span
+                    prev.easing().apply(((t - prev.time()) / span) as f64) as f32
                 } else {
                     0.0
                 };
@@ -86,6 +212,172 @@ impl KeyframeChunk {
         Vector2::new(last.x().into(), last.y().into())
     }
 
+    /// Derivative of the linear interpolation at `time`. Outside the chunk's keyframes the
+    /// segment is formed with the neighbouring chunks' boundary keyframes, when given.
+    pub fn velocity(&self, time: f32, prev: Option<&Keyframe>, next: Option<&Keyframe>) -> Vector2 {
+        let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+            return Vector2::new(0.0, 0.0);
+        };
+
+        if time < first.time() {
+            return prev.map_or(Vector2::new(0.0, 0.0), |p| p.velocity_to(first, time));
+        }
+        if time >= last.time() {
+            return next.map_or(Vector2::new(0.0, 0.0), |n| last.velocity_to(n, time));
+        }
+
+        let idx = self.keyframes.partition_point(|k| k.time() <= time);
+        self.keyframes[idx - 1].velocity_to(&self.keyframes[idx], time)
+    }
+
+    /// Resample the chunk every `dt` ms from its start. `ceil((end - start) / dt)` steps are
+    /// taken, plus the starting sample, so the last keyframe lands on or just past the
+    /// original end and holds its final position.
+    pub fn bake_to_fixed_timestep(&self, dt: f64) -> KeyframeChunk {
+        assert!(dt > 0.0, "bake timestep must be positive");
+        let start = self.start_time as f64;
+        let steps = ((self.end_time as f64 - start) / dt).ceil() as usize;
+
+        let keyframes = (0..=steps)
+            .map(|i| {
+                let time = (start + i as f64 * dt) as f32;
+                let pos = self.interpolate(time);
+                Keyframe::new(time, pos.x as f32, pos.y as f32)
+            })
+            .collect();
+
+        KeyframeChunk {
+            object_chunk_id: self.object_chunk_id.clone(),
+            start_time: self.start_time,
+            end_time: (start + steps as f64 * dt) as f32,
+            keyframes,
+            baked: true,
+            checksum: 0,
+            dirty: false,
+        }
+    }
+
+    /// Resample the chunk to exactly `n` keyframes evenly spaced from its start to its end,
+    /// sampling the current curve, easing included. The result is baked.
+    pub fn resample_uniform(&self, n: u32) -> KeyframeChunk {
+        assert!(n >= 2, "resampling needs at least two keyframes");
+        let start = self.start_time as f64;
+        let step = (self.end_time as f64 - start) / (n - 1) as f64;
+
+        let keyframes = (0..n)
+            .map(|i| {
+                let time = if i == n - 1 { self.end_time } else { (start + i as f64 * step) as f32 };
+                let pos = self.interpolate(time);
+                Keyframe::new(time, pos.x as f32, pos.y as f32)
+            })
+            .collect();
+
+        KeyframeChunk {
+            object_chunk_id: self.object_chunk_id.clone(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            keyframes,
+            baked: true,
+            checksum: 0,
+            dirty: false,
+        }
+    }
+
+    pub fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    /// Mean gap between consecutive keyframes in ms; 0 with fewer than two keyframes.
+    pub fn average_keyframe_spacing(&self) -> f64 {
+        match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) if self.keyframes.len() > 1 => {
+                (last.time() - first.time()) as f64 / (self.keyframes.len() - 1) as f64
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Check the chunk's structural invariants: a well-formed id, an ordered time range, and
+    /// finite, time-sorted keyframes that all lie within that range.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let well_formed_id = self
+            .object_chunk_id
+            .rsplit_once('_')
+            .is_some_and(|(object_id, chunk)| !object_id.is_empty() && chunk.parse::<u32>().is_ok());
+        if !well_formed_id {
+            return Err(ValidationError::MalformedId(self.object_chunk_id.clone()));
+        }
+        if self.start_time.is_nan() || self.end_time.is_nan() || self.start_time > self.end_time {
+            return Err(ValidationError::InvertedRange {
+                start_time: self.start_time,
+                end_time: self.end_time,
+            });
+        }
+
+        let mut prev_time = f32::NEG_INFINITY;
+        for (index, k) in self.keyframes.iter().enumerate() {
+            if !(k.time.is_finite() && k.x.is_finite() && k.y.is_finite()) {
+                return Err(ValidationError::NonFiniteValue { index });
+            }
+            if k.time < self.start_time || k.time > self.end_time {
+                return Err(ValidationError::OutOfRange { index, time: k.time });
+            }
+            if k.time < prev_time {
+                return Err(ValidationError::Unsorted { index });
+            }
+            prev_time = k.time;
+        }
+        Ok(())
+    }
+
+    /// Adler-32 over the little-endian bytes of each keyframe's time, x, y and easing.
+    pub fn compute_checksum(&self) -> u32 {
+        const MOD_ADLER: u32 = 65_521;
+        let (mut a, mut b) = (1u32, 0u32);
+        for k in &self.keyframes {
+            let bytes = k.time.to_le_bytes()
+                .into_iter()
+                .chain(k.x.to_le_bytes())
+                .chain(k.y.to_le_bytes())
+                .chain([k.easing as u8]);
+            for byte in bytes {
+                a = (a + byte as u32) % MOD_ADLER;
+                b = (b + a) % MOD_ADLER;
+            }
+        }
+        (b << 16) | a
+    }
+
+    /// Store the current checksum in the chunk, ready to be saved.
+    pub fn seal(&mut self) {
+        self.checksum = self.compute_checksum();
+    }
+
+    /// False if the chunk carries a checksum that no longer matches its keyframes.
+    pub fn verify_checksum(&self) -> bool {
+        self.checksum == 0 || self.checksum == self.compute_checksum()
+    }
+
+    pub fn is_baked(&self) -> bool {
+        self.baked
+    }
+
+    /// Constant-time lookup for evenly spaced keyframes.
+    fn interpolate_baked(&self, time: f32) -> Vector2 {
+        let last = self.keyframes.len() - 1;
+        let step = (self.end_time - self.start_time) / last as f32;
+        let offset = (time.clamp(self.start_time, self.end_time) - self.start_time) / step;
+        let idx = (offset.floor() as usize).min(last - 1);
+        let ratio = offset - idx as f32;
+
+        let prev = &self.keyframes[idx];
+        let next = &self.keyframes[idx + 1];
+        Vector2::new(
+            (prev.x() + ratio * (next.x() - prev.x())).into(),
+            (prev.y() + ratio * (next.y() - prev.y())).into(),
+        )
+    }
+
     // pub fn log_contents(&self) {
     //     let header = format!(
     //         "KeyframeChunk [{}] (start: {:.2}, end: {:.2}, total: {})",
@@ -108,7 +400,788 @@ impl KeyframeChunk {
     //     }
     // }
 
+    pub fn object_chunk_id(&self) -> &str {
+        &self.object_chunk_id
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.start_time
+    }
+
     pub fn end_time(&self) -> f32 {
         self.end_time
     }
+
+    /// `(start_time, end_time)` in ms.
+    pub fn time_range(&self) -> (f64, f64) {
+        (self.start_time.into(), self.end_time.into())
+    }
+
+    /// Whether `t` lies within the chunk's time range, ends included.
+    pub fn contains_time(&self, t: f64) -> bool {
+        let (start, end) = self.time_range();
+        t >= start && t <= end
+    }
+
+    pub fn duration(&self) -> f64 {
+        f64::from(self.end_time - self.start_time)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn keyframe_at_index(&self, i: usize) -> Option<&Keyframe> {
+        self.keyframes.get(i)
+    }
+
+    pub fn first_keyframe(&self) -> Option<&Keyframe> {
+        self.keyframes.first()
+    }
+
+    pub fn last_keyframe(&self) -> Option<&Keyframe> {
+        self.keyframes.last()
+    }
+
+    /// Keyframes for editing in place. The chunk is marked dirty, so call `ensure_sorted`
+    /// before interpolating or saving it.
+    pub fn keyframes_mut(&mut self) -> &mut [Keyframe] {
+        self.mark_dirty();
+        &mut self.keyframes
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Restore time order after in-place edits and widen the chunk's time range to cover
+    /// keyframes moved outside it, as `add_keyframe` does. A resampled chunk is no longer
+    /// evenly spaced, so it stops being treated as baked.
+    pub fn ensure_sorted(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+        if let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) {
+            self.start_time = self.start_time.min(first.time());
+            self.end_time = self.end_time.max(last.time());
+        }
+        self.baked = false;
+        self.dirty = false;
+    }
+
+    /// Mirror every keyframe time within `[start_time, end_time]`, so the chunk plays backwards.
+    pub fn reverse(&self) -> KeyframeChunk {
+        let mirror = self.start_time + self.end_time;
+        // Each transition now leaves from its original end, so it takes the easing of the
+        // keyframe before it, mirrored in time.
+        let mut keyframes: Vec<Keyframe> = self.keyframes
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, k)| {
+                let easing = i
+                    .checked_sub(1)
+                    .map_or(Easing::Linear, |prev| self.keyframes[prev].easing().reversed());
+                Keyframe::new_with_easing(mirror - k.time(), k.x(), k.y(), easing)
+            })
+            .collect();
+        keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+        KeyframeChunk::new(&self.object_chunk_id, self.start_time, self.end_time, keyframes)
+    }
+
+    /// Multiply every time, including the chunk bounds, by `factor` (expected to be positive).
+    pub fn time_scale(&self, factor: f64) -> KeyframeChunk {
+        let scale = |t: f32| (t as f64 * factor) as f32;
+        let keyframes = self.keyframes
+            .iter()
+            .map(|k| Keyframe::new_with_easing(scale(k.time()), k.x(), k.y(), k.easing()))
+            .collect();
+
+        KeyframeChunk::new(&self.object_chunk_id, scale(self.start_time), scale(self.end_time), keyframes)
+    }
+
+    /// Merge time-ordered chunks into one, keeping the first chunk's id. Each chunk
+    /// must start exactly where the previous one ends.
+    pub fn concatenate(chunks: &[KeyframeChunk]) -> Result<KeyframeChunk, String> {
+        let (first, rest) = chunks
+            .split_first()
+            .ok_or_else(|| "Cannot concatenate an empty list of chunks".to_string())?;
+
+        let mut keyframes = first.keyframes.clone();
+        let mut prev = first;
+        for chunk in rest {
+            if chunk.start_time > prev.end_time {
+                return Err(format!(
+                    "Gap between {} (ends {:.2}) and {} (starts {:.2})",
+                    prev.object_chunk_id, prev.end_time, chunk.object_chunk_id, chunk.start_time
+                ));
+            }
+            if chunk.start_time < prev.end_time {
+                return Err(format!(
+                    "Chunk {} starts at {:.2}, before {} ends at {:.2}",
+                    chunk.object_chunk_id, chunk.start_time, prev.object_chunk_id, prev.end_time
+                ));
+            }
+            keyframes.extend_from_slice(&chunk.keyframes);
+            prev = chunk;
+        }
+
+        Ok(KeyframeChunk::new(&first.object_chunk_id, first.start_time, prev.end_time, keyframes))
+    }
+
+    /// Store the first keyframe as-is and every later one as an `i16`-quantised step from
+    /// the previous one: `DELTA_TIME_QUANTUM` ms and `DELTA_POSITION_QUANTUM` px per unit.
+    /// Lossy within half a quantum; fails if a step is too large to fit in an `i16`.
+    pub fn delta_encode(&self) -> Result<DeltaEncodedChunk, String> {
+        let first = self.keyframes.first().cloned();
+        let mut deltas = Vec::with_capacity(self.keyframes.len().saturating_sub(1));
+
+        if let Some(first) = &first {
+            // Quantise against the reconstructed values, so rounding errors don't accumulate.
+            let (mut time, mut x, mut y) = (first.time, first.x, first.y);
+            for k in &self.keyframes[1..] {
+                let dt = DeltaEncodedChunk::quantise(k.time - time, DELTA_TIME_QUANTUM)
+                    .ok_or_else(|| format!("{}: time step at {:.2} is too large to delta-encode", self.object_chunk_id, k.time))?;
+                let dx = DeltaEncodedChunk::quantise(k.x - x, DELTA_POSITION_QUANTUM)
+                    .ok_or_else(|| format!("{}: x step at {:.2} is too large to delta-encode", self.object_chunk_id, k.time))?;
+                let dy = DeltaEncodedChunk::quantise(k.y - y, DELTA_POSITION_QUANTUM)
+                    .ok_or_else(|| format!("{}: y step at {:.2} is too large to delta-encode", self.object_chunk_id, k.time))?;
+                time += dt as f32 * DELTA_TIME_QUANTUM;
+                x += dx as f32 * DELTA_POSITION_QUANTUM;
+                y += dy as f32 * DELTA_POSITION_QUANTUM;
+                deltas.push((dt, dx, dy));
+            }
+        }
+
+        let easings = if self.keyframes.iter().all(|k| k.easing == Easing::Linear) {
+            Vec::new()
+        } else {
+            self.keyframes.iter().map(|k| k.easing).collect()
+        };
+
+        Ok(DeltaEncodedChunk {
+            object_chunk_id: self.object_chunk_id.clone(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            first,
+            deltas,
+            easings,
+            baked: self.baked,
+            checksum: 0,
+        })
+    }
+}
+
+pub const DELTA_TIME_QUANTUM: f32 = 0.05;
+pub const DELTA_POSITION_QUANTUM: f32 = 0.1;
+
+/// Compact storage form of a `KeyframeChunk`, see `KeyframeChunk::delta_encode`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeltaEncodedChunk {
+    object_chunk_id: String,
+    start_time: f32,
+    end_time: f32,
+    first: Option<Keyframe>,
+    deltas: Vec<(i16, i16, i16)>,
+    /// Per-keyframe easing; left empty when every keyframe is linear.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    easings: Vec<Easing>,
+    #[serde(default)]
+    baked: bool,
+    /// Checksum of the decoded keyframes, see `KeyframeChunk::seal`.
+    #[serde(default)]
+    checksum: u32,
+}
+
+impl DeltaEncodedChunk {
+    /// Record the checksum of what `decode` will produce, since quantisation means that
+    /// can differ slightly from the chunk that was encoded.
+    pub fn seal(&mut self) {
+        self.checksum = self.decode().compute_checksum();
+    }
+
+    pub fn decode(&self) -> KeyframeChunk {
+        let mut keyframes = Vec::with_capacity(self.deltas.len() + 1);
+        if let Some(first) = &self.first {
+            let (mut time, mut x, mut y) = (first.time, first.x, first.y);
+            keyframes.push(Keyframe::new(time, x, y));
+            for &(dt, dx, dy) in &self.deltas {
+                time += dt as f32 * DELTA_TIME_QUANTUM;
+                x += dx as f32 * DELTA_POSITION_QUANTUM;
+                y += dy as f32 * DELTA_POSITION_QUANTUM;
+                keyframes.push(Keyframe::new(time, x, y));
+            }
+        }
+        for (keyframe, easing) in keyframes.iter_mut().zip(&self.easings) {
+            keyframe.easing = *easing;
+        }
+
+        KeyframeChunk {
+            object_chunk_id: self.object_chunk_id.clone(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            keyframes,
+            baked: self.baked,
+            checksum: self.checksum,
+            dirty: false,
+        }
+    }
+
+    fn quantise(delta: f32, quantum: f32) -> Option<i16> {
+        let steps = (delta / quantum).round();
+        if steps >= i16::MIN as f32 && steps <= i16::MAX as f32 {
+            Some(steps as i16)
+        } else {
+            None
+        }
+    }
+}
+
+/// Split a time-sorted keyframe track into chunks keyed `{object_id}_{chunk_idx}`, where
+/// chunk `chunk_idx` holds the keyframes in `[chunk_idx * chunk_size, (chunk_idx + 1) * chunk_size)`.
+/// Chunks with no keyframes up to the last one are emitted as placeholders, so the ids run
+/// from 0 without holes.
+pub fn split_into_chunks(object_id: &str, keyframes: Vec<Keyframe>, chunk_size: f32) -> Vec<KeyframeChunk> {
+    let finish = |chunk_idx: u32, keyframes: Vec<Keyframe>| match (keyframes.first(), keyframes.last()) {
+        (Some(first), Some(last)) => {
+            let (start_time, end_time) = (first.time(), last.time());
+            KeyframeChunk::new(&format!("{}_{}", object_id, chunk_idx), start_time, end_time, keyframes)
+        }
+        _ => KeyframeChunk::placeholder(object_id, chunk_idx, chunk_size),
+    };
+
+    let mut chunks: Vec<KeyframeChunk> = Vec::new();
+    let mut current_chunk: Vec<Keyframe> = Vec::new();
+    let mut current_idx = 0u32;
+
+    for keyframe in keyframes {
+        // Same arithmetic as `KeyframeStore`, so lookups land in the chunk holding the keyframe.
+        let chunk_idx = (f64::from(keyframe.time()) / f64::from(chunk_size)).floor() as u32;
+        if chunk_idx > current_idx {
+            chunks.push(finish(current_idx, std::mem::take(&mut current_chunk)));
+            chunks.extend((current_idx + 1..chunk_idx).map(|idx| KeyframeChunk::placeholder(object_id, idx, chunk_size)));
+            current_idx = chunk_idx;
+        }
+
+        current_chunk.push(keyframe);
+    }
+
+    if !current_chunk.is_empty() {
+        chunks.push(finish(current_idx, current_chunk));
+    }
+
+    chunks
+}
+
+/// Position on a time-sorted keyframe track at `time`, clamped to the track's ends.
+pub fn track_position(keyframes: &[Keyframe], time: f64) -> Vector2 {
+    let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+        return Vector2::new(0.0, 0.0);
+    };
+    let idx = keyframes.partition_point(|k| f64::from(k.time) <= time);
+    let k = if idx == 0 {
+        first.clone()
+    } else if idx == keyframes.len() {
+        last.clone()
+    } else {
+        let (a, b) = (&keyframes[idx - 1], &keyframes[idx]);
+        let span = f64::from(b.time - a.time);
+        let t = if span > 0.0 { (time - f64::from(a.time)) / span } else { 1.0 };
+        Keyframe::interpolated_between(a, b, t)
+    };
+    Vector2::new(k.x.into(), k.y.into())
+}
+
+/// Length of the path travelled between `from_time` and `to_time`, summing the distances
+/// between `samples` positions taken at uniform times. Samples cut across corners at
+/// keyframes that fall between them, so more samples give a closer estimate.
+/// Panics if `samples` is less than 2.
+pub fn arc_length(keyframes: &[Keyframe], from_time: f64, to_time: f64, samples: u32) -> f64 {
+    assert!(samples >= 2, "arc_length needs at least 2 samples");
+    let step = (to_time - from_time) / f64::from(samples - 1);
+    let mut prev = track_position(keyframes, from_time);
+    let mut length = 0.0;
+    for i in 1..samples {
+        let pos = track_position(keyframes, from_time + step * f64::from(i));
+        length += prev.distance(&pos);
+        prev = pos;
+    }
+    length
+}
+
+/// The same path and duration with keyframe times moved so the object covers equal
+/// distances in equal times. Segments become linear, since easing would vary the speed.
+/// A track that never moves is returned unchanged.
+pub fn reparametrize_by_arc_length(keyframes: &[Keyframe]) -> Vec<Keyframe> {
+    let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+        return Vec::new();
+    };
+    let mut cumulative = Vec::with_capacity(keyframes.len());
+    let mut total = 0.0f64;
+    cumulative.push(0.0);
+    for pair in keyframes.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        total += Vector2::new(a.x.into(), a.y.into()).distance(&Vector2::new(b.x.into(), b.y.into()));
+        cumulative.push(total);
+    }
+    if total == 0.0 {
+        return keyframes.to_vec();
+    }
+
+    let start = f64::from(first.time);
+    let duration = f64::from(last.time) - start;
+    keyframes
+        .iter()
+        .zip(cumulative)
+        .map(|(k, distance)| {
+            Keyframe::new((start + duration * distance / total) as f32, k.x, k.y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &str, start: f32, end: f32, keyframes: &[(f32, f32, f32)]) -> KeyframeChunk {
+        let keyframes = keyframes.iter().map(|&(t, x, y)| Keyframe::new(t, x, y)).collect();
+        KeyframeChunk::new(id, start, end, keyframes)
+    }
+
+    fn times(chunk: &KeyframeChunk) -> Vec<f32> {
+        chunk.keyframes().iter().map(Keyframe::time).collect()
+    }
+
+    #[test]
+    fn reverse_mirrors_times_and_keeps_positions() {
+        let original = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0), (30.0, 10.0, 5.0), (100.0, 50.0, 20.0)]);
+        let reversed = original.reverse();
+
+        assert_eq!(times(&reversed), vec![0.0, 70.0, 100.0]);
+        assert_eq!(reversed.keyframes()[0].x(), 50.0);
+        assert_eq!(reversed.keyframes()[1].x(), 10.0);
+        assert_eq!(reversed.keyframes()[2].x(), 0.0);
+        assert_eq!(reversed.time_range(), original.time_range());
+    }
+
+    #[test]
+    fn reverse_plays_the_path_backwards() {
+        let original = chunk("obj_0", 100.0, 200.0, &[(100.0, 0.0, 0.0), (200.0, 100.0, 40.0)]);
+        let reversed = original.reverse();
+
+        assert_eq!(reversed.interpolate(125.0), original.interpolate(175.0));
+        assert_eq!(reversed.interpolate(100.0), original.interpolate(200.0));
+    }
+
+    #[test]
+    fn reverse_twice_restores_the_chunk() {
+        let original = chunk("obj_0", 0.0, 90.0, &[(0.0, 1.0, 2.0), (20.0, 3.0, 4.0), (90.0, 5.0, 6.0)]);
+        assert_eq!(original.reverse().reverse().keyframes(), original.keyframes());
+    }
+
+    #[test]
+    fn reverse_takes_easing_from_the_preceding_keyframe() {
+        let original = KeyframeChunk::new("obj_0", 0.0, 100.0, vec![
+            Keyframe::new_with_easing(0.0, 0.0, 0.0, Easing::EaseIn),
+            Keyframe::new(100.0, 10.0, 0.0),
+        ]);
+        let reversed = original.reverse();
+        assert_eq!(reversed.keyframes()[0].easing(), Easing::EaseOut);
+        assert_eq!(reversed.keyframes()[1].easing(), Easing::Linear);
+    }
+
+    #[test]
+    fn time_scale_multiplies_times_and_bounds() {
+        let original = chunk("obj_0", 10.0, 50.0, &[(10.0, 1.0, 1.0), (50.0, 2.0, 2.0)]);
+        let scaled = original.time_scale(2.5);
+
+        assert_eq!(times(&scaled), vec![25.0, 125.0]);
+        assert_eq!(scaled.time_range(), (25.0, 125.0));
+        assert_eq!(scaled.keyframes()[1].x(), 2.0);
+    }
+
+    #[test]
+    fn time_scale_by_one_is_identity() {
+        let original = chunk("obj_0", 0.0, 40.0, &[(0.0, 1.0, 1.0), (40.0, 2.0, 2.0)]);
+        let scaled = original.time_scale(1.0);
+        assert_eq!(scaled.keyframes(), original.keyframes());
+        assert_eq!(scaled.time_range(), original.time_range());
+    }
+
+    #[test]
+    fn concatenate_joins_adjacent_chunks() {
+        let a = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0), (50.0, 5.0, 5.0)]);
+        let b = chunk("obj_1", 100.0, 200.0, &[(100.0, 10.0, 10.0), (200.0, 20.0, 20.0)]);
+        let merged = KeyframeChunk::concatenate(&[a, b]).unwrap();
+
+        assert_eq!(merged.object_chunk_id(), "obj_0");
+        assert_eq!(merged.time_range(), (0.0, 200.0));
+        assert_eq!(times(&merged), vec![0.0, 50.0, 100.0, 200.0]);
+    }
+
+    #[test]
+    fn concatenate_single_chunk_returns_a_copy() {
+        let a = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0), (100.0, 1.0, 1.0)]);
+        let merged = KeyframeChunk::concatenate(std::slice::from_ref(&a)).unwrap();
+        assert_eq!(merged.keyframes(), a.keyframes());
+    }
+
+    #[test]
+    fn concatenate_rejects_a_gap() {
+        let a = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0)]);
+        let b = chunk("obj_1", 101.0, 200.0, &[(101.0, 1.0, 1.0)]);
+        let err = KeyframeChunk::concatenate(&[a, b]).unwrap_err();
+        assert!(err.contains("Gap"), "{}", err);
+    }
+
+    #[test]
+    fn concatenate_rejects_an_overlap() {
+        let a = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0)]);
+        let b = chunk("obj_1", 90.0, 200.0, &[(90.0, 1.0, 1.0)]);
+        let err = KeyframeChunk::concatenate(&[a, b]).unwrap_err();
+        assert!(err.contains("before"), "{}", err);
+    }
+
+    #[test]
+    fn placeholder_is_an_empty_valid_chunk_at_its_boundary() {
+        let placeholder = KeyframeChunk::placeholder("obj", 3, 100.0);
+        assert_eq!(placeholder.object_chunk_id(), "obj_3");
+        assert_eq!(placeholder.time_range(), (300.0, 300.0));
+        assert!(placeholder.is_empty());
+        assert!(placeholder.validate().is_ok());
+    }
+
+    #[test]
+    fn keyframe_added_to_placeholder_widens_it() {
+        let mut placeholder = KeyframeChunk::placeholder("obj", 3, 100.0);
+        placeholder.add_keyframe(350.0, 1.0, 2.0);
+        assert_eq!(placeholder.time_range(), (300.0, 350.0));
+        assert!(placeholder.validate().is_ok());
+    }
+
+    #[test]
+    fn concatenate_rejects_an_empty_list() {
+        assert!(KeyframeChunk::concatenate(&[]).is_err());
+    }
+
+    #[test]
+    fn velocity_on_a_linear_trajectory_is_constant() {
+        // 100 px right and 50 px down over 200 ms.
+        let c = chunk("obj_0", 0.0, 200.0, &[(0.0, 0.0, 0.0), (200.0, 100.0, 50.0)]);
+        for time in [0.0, 1.0, 99.5, 150.0, 199.0] {
+            assert_eq!(c.velocity(time, None, None), Vector2::new(0.5, 0.25));
+        }
+    }
+
+    #[test]
+    fn velocity_uses_the_segment_containing_the_time() {
+        let c = chunk("obj_0", 0.0, 300.0, &[(0.0, 0.0, 0.0), (100.0, 100.0, 0.0), (300.0, 100.0, -400.0)]);
+        assert_eq!(c.velocity(50.0, None, None), Vector2::new(1.0, 0.0));
+        assert_eq!(c.velocity(100.0, None, None), Vector2::new(0.0, -2.0));
+        assert_eq!(c.velocity(250.0, None, None), Vector2::new(0.0, -2.0));
+    }
+
+    #[test]
+    fn velocity_across_chunk_boundaries_uses_neighbouring_keyframes() {
+        let c = chunk("obj_1", 100.0, 200.0, &[(120.0, 20.0, 0.0), (180.0, 80.0, 0.0)]);
+        let prev = Keyframe::new(80.0, -20.0, 0.0);
+        let next = Keyframe::new(220.0, 80.0, 40.0);
+
+        assert_eq!(c.velocity(110.0, Some(&prev), None), Vector2::new(1.0, 0.0));
+        assert_eq!(c.velocity(190.0, None, Some(&next)), Vector2::new(0.0, 1.0));
+        assert_eq!(c.velocity(110.0, None, None), Vector2::new(0.0, 0.0));
+        assert_eq!(c.velocity(190.0, None, None), Vector2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn velocity_of_an_empty_chunk_is_zero() {
+        let c = chunk("obj_0", 0.0, 100.0, &[]);
+        assert_eq!(c.velocity(50.0, None, None), Vector2::new(0.0, 0.0));
+    }
+
+    /// 10,000 keyframes of a smooth path sampled every 16 ms, with sub-pixel positions.
+    fn smooth_track() -> Vec<Keyframe> {
+        (0..10_000)
+            .map(|i| {
+                let t = i as f32 * 16.0;
+                Keyframe::new(t, 400.0 + 300.0 * (t / 1500.0).sin(), 300.0 + 200.0 * (t / 900.0).cos())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delta_encoding_shrinks_a_10k_keyframe_track() {
+        let chunks = split_into_chunks("big", smooth_track(), 10_000.0);
+        let plain: usize = chunks.iter().map(|c| serde_json::to_string(c).unwrap().len()).sum();
+        let encoded: usize = chunks
+            .iter()
+            .map(|c| serde_json::to_string(&c.delta_encode().unwrap()).unwrap().len())
+            .sum();
+
+        // Measured as JSON: 628,953 bytes plain, 128,937 delta encoded, about a fifth.
+        assert!(encoded * 4 < plain, "delta encoded {} bytes, plain {} bytes", encoded, plain);
+    }
+
+    #[test]
+    fn delta_encoding_stays_within_half_a_quantum() {
+        let chunks = split_into_chunks("big", smooth_track(), 10_000.0);
+        for c in &chunks {
+            let decoded = c.delta_encode().unwrap().decode();
+            assert_eq!(decoded.keyframes().len(), c.keyframes().len());
+            for (a, b) in c.keyframes().iter().zip(decoded.keyframes()) {
+                assert!((a.time() - b.time()).abs() <= DELTA_TIME_QUANTUM / 2.0 + 1e-3);
+                assert!((a.x() - b.x()).abs() <= DELTA_POSITION_QUANTUM / 2.0 + 1e-3);
+                assert!((a.y() - b.y()).abs() <= DELTA_POSITION_QUANTUM / 2.0 + 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn delta_encoding_rejects_steps_too_large_for_i16() {
+        let c = chunk("obj_0", 0.0, 10.0, &[(0.0, 0.0, 0.0), (10.0, 5_000.0, 0.0)]);
+        assert!(c.delta_encode().is_err());
+    }
+
+    fn ids(chunks: &[KeyframeChunk]) -> Vec<&str> {
+        chunks.iter().map(KeyframeChunk::object_chunk_id).collect()
+    }
+
+    #[test]
+    fn split_keys_chunks_by_keyframe_time() {
+        let keyframes = [0.0, 40.0, 120.0, 199.0, 200.0].map(|t| Keyframe::new(t, 0.0, 0.0)).to_vec();
+        let chunks = split_into_chunks("obj", keyframes, 100.0);
+
+        assert_eq!(ids(&chunks), vec!["obj_0", "obj_1", "obj_2"]);
+        assert_eq!(times(&chunks[1]), vec![120.0, 199.0]);
+        assert_eq!(chunks[1].time_range(), (120.0, 199.0));
+        assert_eq!(times(&chunks[2]), vec![200.0]);
+    }
+
+    #[test]
+    fn split_fills_gaps_with_placeholders() {
+        let keyframes = [10.0, 20.0, 350.0].map(|t| Keyframe::new(t, 0.0, 0.0)).to_vec();
+        let chunks = split_into_chunks("obj", keyframes, 100.0);
+
+        assert_eq!(ids(&chunks), vec!["obj_0", "obj_1", "obj_2", "obj_3"]);
+        assert!(chunks[1].is_empty() && chunks[2].is_empty());
+        assert_eq!(chunks[2].time_range(), (200.0, 200.0));
+        assert_eq!(times(&chunks[3]), vec![350.0]);
+        assert!(chunks.iter().all(|c| c.validate().is_ok()));
+    }
+
+    #[test]
+    fn split_of_a_late_starting_track_leads_with_placeholders() {
+        let keyframes = [250.0, 260.0].map(|t| Keyframe::new(t, 0.0, 0.0)).to_vec();
+        let chunks = split_into_chunks("obj", keyframes, 100.0);
+
+        assert_eq!(ids(&chunks), vec!["obj_0", "obj_1", "obj_2"]);
+        assert!(chunks[0].is_empty() && chunks[1].is_empty());
+        assert_eq!(times(&chunks[2]), vec![250.0, 260.0]);
+    }
+
+    #[test]
+    fn split_of_no_keyframes_is_empty() {
+        assert!(split_into_chunks("obj", Vec::new(), 100.0).is_empty());
+    }
+
+    #[test]
+    fn migrating_chunk_size_keeps_every_keyframe_in_its_chunk() {
+        let keyframes: Vec<Keyframe> = [0.0, 30.0, 95.0, 480.0, 510.0, 1_990.0]
+            .iter()
+            .map(|&t| Keyframe::new_with_easing(t, t / 10.0, -t, Easing::EaseIn))
+            .collect();
+        let old_chunks = split_into_chunks("obj", keyframes.clone(), 100.0);
+
+        // What `migrate_chunk_size` does: gather every stored keyframe and split again.
+        let stored: Vec<Keyframe> = old_chunks.iter().flat_map(|c| c.keyframes().to_vec()).collect();
+        assert_eq!(stored, keyframes);
+        for new_chunk_size in [40.0, 250.0, 1_000.0, 5_000.0] {
+            let new_chunks = split_into_chunks("obj", stored.clone(), new_chunk_size);
+            let restored: Vec<Keyframe> = new_chunks.iter().flat_map(|c| c.keyframes().to_vec()).collect();
+            assert_eq!(restored, keyframes);
+
+            // Chunk ids run from 0 to the one holding the last keyframe, as `KeyframeStore` expects.
+            let last = (1_990.0 / new_chunk_size).floor() as usize;
+            assert_eq!(new_chunks.len(), last + 1);
+            for (idx, chunk) in new_chunks.iter().enumerate() {
+                assert_eq!(chunk.object_chunk_id(), format!("obj_{}", idx));
+                let range = (idx as f32 * new_chunk_size)..((idx + 1) as f32 * new_chunk_size);
+                assert!(chunk.keyframes().iter().all(|k| range.contains(&k.time())));
+            }
+        }
+    }
+
+    #[test]
+    fn set_time_accepts_zero_and_positive_times() {
+        let mut k = Keyframe::new(10.0, 1.0, 2.0);
+        k.set_time(0.0).unwrap();
+        assert_eq!(k.time(), 0.0);
+        k.set_time(250.5).unwrap();
+        assert_eq!(k.time(), 250.5);
+    }
+
+    #[test]
+    fn set_time_rejects_negative_and_non_finite_times() {
+        let mut k = Keyframe::new(10.0, 1.0, 2.0);
+        for t in [-0.5, f64::NAN, f64::INFINITY, 1e300] {
+            assert!(k.set_time(t).is_err(), "{} accepted", t);
+        }
+        assert_eq!(k, Keyframe::new(10.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn set_position_rejects_non_finite_coordinates() {
+        let mut k = Keyframe::new(10.0, 1.0, 2.0);
+        assert!(k.set_position(f64::NAN, 0.0).is_err());
+        assert!(k.set_position(0.0, f64::NEG_INFINITY).is_err());
+        assert!(k.set_x(f64::NAN).is_err());
+        assert!(k.set_y(f64::INFINITY).is_err());
+        assert_eq!(k, Keyframe::new(10.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn set_x_and_set_y_keep_the_other_coordinate() {
+        let mut k = Keyframe::new(10.0, 1.0, 2.0);
+        k.set_x(-3.5).unwrap();
+        assert_eq!((k.x(), k.y()), (-3.5, 2.0));
+        k.set_y(7.0).unwrap();
+        assert_eq!((k.x(), k.y()), (-3.5, 7.0));
+    }
+
+    #[test]
+    fn editing_keyframes_marks_the_chunk_dirty_until_sorted() {
+        let mut c = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0), (50.0, 5.0, 5.0), (100.0, 10.0, 10.0)]);
+        assert!(!c.is_dirty());
+        c.keyframes_mut()[0].set_time(75.0).unwrap();
+        assert!(c.is_dirty());
+        assert_eq!(c.validate(), Err(ValidationError::Unsorted { index: 1 }));
+
+        c.ensure_sorted();
+        assert!(!c.is_dirty());
+        assert_eq!(times(&c), vec![50.0, 75.0, 100.0]);
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn moving_a_keyframe_out_of_range_widens_the_chunk() {
+        let mut c = chunk("obj_1", 100.0, 200.0, &[(100.0, 0.0, 0.0), (200.0, 5.0, 5.0)]);
+        c.keyframes_mut()[1].set_time(260.0).unwrap();
+        assert_eq!(c.validate(), Err(ValidationError::OutOfRange { index: 1, time: 260.0 }));
+
+        c.ensure_sorted();
+        assert_eq!(c.time_range(), (100.0, 260.0));
+        assert!(c.validate().is_ok());
+    }
+
+    #[test]
+    fn ensure_sorted_on_a_clean_chunk_changes_nothing() {
+        let mut c = chunk("obj_0", 0.0, 100.0, &[(0.0, 0.0, 0.0), (100.0, 10.0, 10.0)]);
+        c.ensure_sorted();
+        assert_eq!(times(&c), vec![0.0, 100.0]);
+        assert_eq!(c.time_range(), (0.0, 100.0));
+    }
+
+    #[test]
+    fn interpolated_between_hits_both_ends() {
+        let a = Keyframe::new(10.0, 0.0, 100.0);
+        let b = Keyframe::new(30.0, 40.0, 0.0);
+        assert_eq!(Keyframe::interpolated_between(&a, &b, 0.0), a);
+        assert_eq!(Keyframe::interpolated_between(&a, &b, 1.0), Keyframe { easing: a.easing, ..b });
+    }
+
+    #[test]
+    fn midpoint_is_halfway_in_time_and_space() {
+        let a = Keyframe::new(10.0, 0.0, 100.0);
+        let b = Keyframe::new(30.0, 40.0, 0.0);
+        assert_eq!(Keyframe::midpoint(&a, &b), Keyframe::new(20.0, 20.0, 50.0));
+    }
+
+    #[test]
+    fn interpolated_between_clamps_t() {
+        let a = Keyframe::new(0.0, 0.0, 0.0);
+        let b = Keyframe::new(10.0, 10.0, 10.0);
+        assert_eq!(Keyframe::interpolated_between(&a, &b, -0.5), a);
+        assert_eq!(Keyframe::interpolated_between(&a, &b, 2.0), b);
+    }
+
+    #[test]
+    fn interpolated_between_eases_position_but_not_time() {
+        let a = Keyframe::new_with_easing(0.0, 0.0, 0.0, Easing::EaseIn);
+        let b = Keyframe::new(100.0, 100.0, -100.0);
+        let k = Keyframe::interpolated_between(&a, &b, 0.5);
+        assert_eq!((k.time, k.x, k.y), (50.0, 25.0, -25.0));
+        assert_eq!(k.easing, Easing::EaseIn);
+    }
+
+    #[test]
+    fn interpolated_between_keyframes_at_the_same_time() {
+        let a = Keyframe::new(40.0, 0.0, 8.0);
+        let b = Keyframe::new(40.0, 10.0, 0.0);
+        let k = Keyframe::interpolated_between(&a, &b, 0.25);
+        assert_eq!(k, Keyframe::new(40.0, 2.5, 6.0));
+        assert_eq!(Keyframe::midpoint(&a, &a), a);
+    }
+
+    #[test]
+    fn contains_time_includes_both_ends() {
+        let c = chunk("obj_1", 100.0, 200.0, &[(100.0, 0.0, 0.0), (200.0, 1.0, 1.0)]);
+        assert_eq!(c.time_range(), (100.0, 200.0));
+        assert!(c.contains_time(100.0));
+        assert!(c.contains_time(150.0));
+        assert!(c.contains_time(200.0));
+    }
+
+    #[test]
+    fn contains_time_excludes_times_just_outside() {
+        let c = chunk("obj_1", 100.0, 200.0, &[]);
+        assert!(!c.contains_time(99.999));
+        assert!(!c.contains_time(200.001));
+        assert!(!c.contains_time(f64::NAN));
+    }
+
+    #[test]
+    fn zero_width_chunk_contains_only_its_start() {
+        let c = KeyframeChunk::placeholder("obj", 2, 50.0);
+        assert_eq!(c.time_range(), (100.0, 100.0));
+        assert_eq!(c.duration(), 0.0);
+        assert!(c.contains_time(100.0));
+        assert!(!c.contains_time(100.001));
+    }
+
+    #[test]
+    fn keyframe_accessors_on_an_empty_chunk() {
+        let c = chunk("obj_0", 0.0, 100.0, &[]);
+        assert_eq!(c.keyframe_count(), 0);
+        assert!(c.first_keyframe().is_none());
+        assert!(c.last_keyframe().is_none());
+        assert_eq!(c.average_keyframe_spacing(), 0.0);
+        assert_eq!(c.duration(), 100.0);
+    }
+
+    #[test]
+    fn keyframe_accessors_on_a_single_keyframe() {
+        let c = chunk("obj_0", 0.0, 100.0, &[(40.0, 1.0, 2.0)]);
+        assert_eq!(c.keyframe_count(), 1);
+        assert_eq!(c.first_keyframe(), c.last_keyframe());
+        assert_eq!(c.average_keyframe_spacing(), 0.0);
+    }
+
+    #[test]
+    fn keyframe_accessors_on_several_keyframes() {
+        let c = chunk("obj_0", 0.0, 100.0, &[(10.0, 0.0, 0.0), (20.0, 0.0, 0.0), (70.0, 5.0, 5.0)]);
+        assert_eq!(c.keyframe_count(), 3);
+        assert_eq!(c.first_keyframe().map(Keyframe::time), Some(10.0));
+        assert_eq!(c.last_keyframe().map(Keyframe::time), Some(70.0));
+        assert_eq!(c.average_keyframe_spacing(), 30.0);
+    }
 }
@@ -1,20 +1,194 @@
-use idb::{Database, DatabaseEvent, Error, Factory,  KeyPath, ObjectStoreParams, TransactionMode};
+use idb::{Database, DatabaseEvent, Error, Factory, KeyPath, KeyRange, ObjectStoreParams, TransactionMode};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::future::IntoFuture;
+use std::rc::Rc;
 use std::sync::Arc;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{DomException, StorageEstimate};
+
+use serde::Deserialize;
+
+use crate::chunk_worker::ChunkWorker;
+use crate::keyframe::{self, DeltaEncodedChunk, Keyframe, KeyframeChunk, ValidationError};
+
+const DB_NAME: &str = "keyframe_db";
+const DB_VERSION: u32 = 1;
+const MAX_SAVE_RETRIES: u32 = 3;
+const RETRY_BASE_DELAY_MS: u32 = 50;
+
+/// What `save_chunks` does when IndexedDB reports that its quota is exhausted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvictionPolicy {
+    /// Reject the save straight away.
+    FailFast,
+    /// Clear every stored chunk and retry with exponential back-off.
+    ClearOnFull,
+}
+
+impl EvictionPolicy {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy {
+            "fail_fast" => Ok(EvictionPolicy::FailFast),
+            "clear_on_full" => Ok(EvictionPolicy::ClearOnFull),
+            other => Err(format!(
+                "Unknown eviction policy '{}', expected 'fail_fast' or 'clear_on_full'",
+                other
+            )),
+        }
+    }
+}
+
+/// How chunks are written. Reads accept either form regardless of the setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompressionLevel {
+    None,
+    /// Quantised delta encoding, see `KeyframeChunk::delta_encode`. Chunks whose steps
+    /// don't fit the encoding are stored uncompressed.
+    Delta,
+}
+
+impl CompressionLevel {
+    pub fn parse(level: &str) -> Result<Self, String> {
+        match level {
+            "none" => Ok(CompressionLevel::None),
+            "delta" => Ok(CompressionLevel::Delta),
+            other => Err(format!("Unknown compression level '{}', expected 'none' or 'delta'", other)),
+        }
+    }
+}
+
+/// A record as found in the object store.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredChunk {
+    Delta(DeltaEncodedChunk),
+    Plain(KeyframeChunk),
+}
+
+impl StoredChunk {
+    fn from_js(key: &str, js_val: JsValue) -> Result<KeyframeChunk, DbError> {
+        let stored: StoredChunk = serde_wasm_bindgen::from_value(js_val)
+            .map_err(|e| Error::AddFailed(JsValue::from_str(&format!("Deserialization error for '{}': {:?}", key, e))))?;
+        let chunk = match stored {
+            StoredChunk::Delta(encoded) => encoded.decode(),
+            StoredChunk::Plain(chunk) => chunk,
+        };
+        if !chunk.verify_checksum() {
+            return Err(DbError::CorruptedChunk(key.to_string()));
+        }
+        if let Err(e) = chunk.validate() {
+            web_sys::console::warn_1(&JsValue::from_str(&format!("Stored chunk '{}' is invalid: {}", key, e)));
+            return Err(DbError::CorruptedChunk(key.to_string()));
+        }
+        Ok(chunk)
+    }
+}
+
+#[derive(Debug)]
+pub enum DbError {
+    Idb(Error),
+    StorageFull(String),
+    /// The stored chunk with this key no longer matches its checksum, or fails validation.
+    CorruptedChunk(String),
+    /// A chunk about to be saved failed validation.
+    InvalidChunk(String, ValidationError),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Idb(e) => write!(f, "{}", e),
+            DbError::StorageFull(msg) => write!(f, "storage full: {}", msg),
+            DbError::CorruptedChunk(key) => write!(f, "chunk '{}' is corrupted", key),
+            DbError::InvalidChunk(key, e) => write!(f, "chunk '{}' is invalid: {}", key, e),
+        }
+    }
+}
+
+impl From<Error> for DbError {
+    fn from(e: Error) -> Self {
+        DbError::Idb(e)
+    }
+}
+
+fn is_quota_exceeded(e: &Error) -> bool {
+    let exception = match e {
+        Error::DomException(exception) => Some(exception.clone()),
+        Error::AddFailed(value) => value.dyn_ref::<DomException>().cloned(),
+        _ => None,
+    };
+    exception.is_some_and(|exception| exception.name() == "QuotaExceededError")
+}
+
+/// Where `save_chunks` writes its batches, split out so the quota retry logic in
+/// `save_batch_with_retries` can run against a mock.
+trait ChunkSink {
+    type Error: fmt::Display;
+
+    fn is_quota_exceeded(e: &Self::Error) -> bool;
+    async fn write(&self, batch: &[KeyframeChunk]) -> Result<(), Self::Error>;
+    /// Free space before retry `attempt` (0-based).
+    async fn evict(&self, attempt: u32) -> Result<(), Self::Error>;
+    async fn back_off(&self, delay_ms: u32);
+}
+
+/// Write `batch`, evicting and retrying with exponential back-off up to `MAX_SAVE_RETRIES`
+/// times while the sink reports a full quota under `EvictionPolicy::ClearOnFull`.
+async fn save_batch_with_retries<S: ChunkSink>(
+    sink: &S,
+    policy: EvictionPolicy,
+    batch: &[KeyframeChunk],
+) -> Result<(), DbError>
+where
+    DbError: From<S::Error>,
+{
+    let mut attempt = 0;
+    loop {
+        match sink.write(batch).await {
+            Ok(()) => return Ok(()),
+            Err(e) if S::is_quota_exceeded(&e) => {
+                if policy == EvictionPolicy::FailFast {
+                    return Err(DbError::StorageFull(e.to_string()));
+                }
+                if attempt >= MAX_SAVE_RETRIES {
+                    return Err(DbError::StorageFull(format!(
+                        "still full after {} retries: {}",
+                        MAX_SAVE_RETRIES, e
+                    )));
+                }
+                sink.evict(attempt).await?;
+                sink.back_off(RETRY_BASE_DELAY_MS << attempt).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
 
-use crate::keyframe::KeyframeChunk;
 pub struct KeyframeDatabase {
     db: Arc<Database>,
+    eviction_policy: Cell<EvictionPolicy>,
+    compression: Cell<CompressionLevel>,
+    fetch_worker: RefCell<Option<Rc<ChunkWorker>>>,
+    /// `(track id, chunk index)` pairs that playback needed but found uncached, loaded
+    /// ahead of the regular fetch by the engine's task loop.
+    pending_urgent_fetches: RefCell<VecDeque<(String, u32)>>,
 }
 
 impl KeyframeDatabase {
+    /// Start from an empty database, discarding whatever a previous page load stored.
     pub async fn new() -> Result<Arc<Self>, Error> {
-        let factory = Factory::new()?;
-        let db_name = "keyframe_db";
-        let db_version = 1;
-        factory.delete(db_name)?.await?;
+        Factory::new()?.delete(DB_NAME)?.await?;
+        Self::open_existing().await
+    }
 
-        let mut open_req = factory.open(db_name, Some(db_version))?;
+    /// Connect without clearing, e.g. from a worker sharing the page's database.
+    pub async fn open_existing() -> Result<Arc<Self>, Error> {
+        let factory = Factory::new()?;
+        let mut open_req = factory.open(DB_NAME, Some(DB_VERSION))?;
 
         open_req.on_upgrade_needed(|event| {
             let db = event.database().unwrap();
@@ -31,44 +205,251 @@ impl KeyframeDatabase {
 
         let raw_db: Database = open_req.await?;
         let db = Arc::new(raw_db);
-        Ok(Arc::new(Self { db }))
+        Ok(Arc::new(Self {
+            db,
+            eviction_policy: Cell::new(EvictionPolicy::FailFast),
+            compression: Cell::new(CompressionLevel::None),
+            fetch_worker: RefCell::new(None),
+            pending_urgent_fetches: RefCell::new(VecDeque::new()),
+        }))
     }
 
+    /// Queue a chunk for the next urgent fetch, unless it is already queued.
+    pub fn request_urgent_fetch(&self, object_id: &str, chunk_id: u32) {
+        let mut pending = self.pending_urgent_fetches.borrow_mut();
+        if !pending.iter().any(|(id, chunk)| id == object_id && *chunk == chunk_id) {
+            pending.push_back((object_id.to_string(), chunk_id));
+        }
+    }
+
+    pub fn has_urgent_fetches(&self) -> bool {
+        !self.pending_urgent_fetches.borrow().is_empty()
+    }
+
+    pub fn take_urgent_fetches(&self) -> VecDeque<(String, u32)> {
+        std::mem::take(&mut *self.pending_urgent_fetches.borrow_mut())
+    }
+
+    /// Route `fetch_chunk` through a worker, or back to this thread with `None`.
+    pub fn set_fetch_worker(&self, worker: Option<ChunkWorker>) {
+        *self.fetch_worker.borrow_mut() = worker.map(Rc::new);
+    }
+
+    /// Load a chunk for playback: through the fetch worker when one is set, falling back
+    /// to loading it here if the worker fails. Checksum failures are returned as they are.
+    pub async fn fetch_chunk(&self, object_id: &str, chunk_id: u32) -> Result<KeyframeChunk, DbError> {
+        let worker = self.fetch_worker.borrow().clone();
+        if let Some(worker) = worker {
+            match worker.fetch_chunk(object_id, chunk_id).await {
+                Err(DbError::Idb(e)) => web_sys::console::warn_1(&JsValue::from_str(&format!(
+                    "Chunk worker failed ({}), loading '{}_{}' on the main thread",
+                    e, object_id, chunk_id
+                ))),
+                result => return result,
+            }
+        }
+        self.load_chunk(object_id, chunk_id).await
+    }
+
+    pub fn set_eviction_policy(&self, policy: EvictionPolicy) {
+        self.eviction_policy.set(policy);
+    }
+
+    pub fn set_compression(&self, compression: CompressionLevel) {
+        self.compression.set(compression);
+    }
+
+    /// Checksum and save chunks in batches. A batch that hits the storage quota is retried
+    /// up to `MAX_SAVE_RETRIES` times under `EvictionPolicy::ClearOnFull`.
     pub async fn save_chunks(
         &self,
-        chunks: Vec<KeyframeChunk>
-    ) -> Result<(), Error> {
+        mut chunks: Vec<KeyframeChunk>
+    ) -> Result<(), DbError> {
         if chunks.is_empty() {
             return Ok(());
         }
+        for chunk in &mut chunks {
+            chunk
+                .validate()
+                .map_err(|e| DbError::InvalidChunk(chunk.object_chunk_id().to_string(), e))?;
+            chunk.seal();
+        }
 
         const BATCH_SIZE: usize = 200;
 
         for chunk_batch in chunks.chunks(BATCH_SIZE) {
-            let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
-            let store = tx.object_store("keyframe_chunks")?;
+            save_batch_with_retries(self, self.eviction_policy.get(), chunk_batch).await?;
+            gloo_timers::future::TimeoutFuture::new(0).await;
+        }
 
-            for chunk in chunk_batch {
-                let js_val: JsValue = serde_wasm_bindgen::to_value(chunk)
-                    .map_err(|e| Error::AddFailed(JsValue::from_str(&format!("Serialization error: {:?}", e))))?;
+        Ok(())
+    }
 
-                let req = store.put(&js_val, None)?;
-                req.await?;
-            }
+    async fn save_batch(&self, chunk_batch: &[KeyframeChunk]) -> Result<(), Error> {
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
+        let store = tx.object_store("keyframe_chunks")?;
 
-            tx.commit()?;
+        for chunk in chunk_batch {
+            let req = store.put(&self.encode_chunk(chunk)?, None)?;
+            req.await?;
+        }
 
-            gloo_timers::future::TimeoutFuture::new(0).await;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Serialise a sealed chunk in the current compression format.
+    fn encode_chunk(&self, chunk: &KeyframeChunk) -> Result<JsValue, Error> {
+        let encoded = match self.compression.get() {
+            CompressionLevel::Delta => chunk.delta_encode().ok().map(|mut encoded| {
+                encoded.seal();
+                encoded
+            }),
+            CompressionLevel::None => None,
+        };
+        match &encoded {
+            Some(encoded) => serde_wasm_bindgen::to_value(encoded),
+            None => serde_wasm_bindgen::to_value(chunk),
         }
+        .map_err(|e| Error::AddFailed(JsValue::from_str(&format!("Serialization error: {:?}", e))))
+    }
 
+    /// Re-split an object's stored track from `old_chunk_size` into `new_chunk_size` chunks.
+    /// Reading the old chunks, deleting them and writing the new ones all happen in one
+    /// read-write transaction, so a failure leaves the old chunks in place.
+    pub async fn migrate_chunk_size(
+        &self,
+        object_id: &str,
+        old_chunk_size: f64,
+        new_chunk_size: f64,
+    ) -> Result<(), DbError> {
+        if old_chunk_size == new_chunk_size {
+            return Ok(());
+        }
+        let lower = JsValue::from_str(&format!("{}_", object_id));
+        let upper = JsValue::from_str(&format!("{}_\u{ffff}", object_id));
+        let range = KeyRange::bound(&lower, &upper, None, None)?;
+
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
+        let store = tx.object_store("keyframe_chunks")?;
+
+        let mut keyframes: Vec<Keyframe> = Vec::new();
+        if let Some(cursor) = store.open_cursor(Some(range.clone().into()), None)?.await? {
+            let mut cursor = cursor.into_managed();
+            while let Some(js_val) = cursor.value()? {
+                let key = cursor.key()?.and_then(|key| key.as_string()).unwrap_or_default();
+                keyframes.extend(StoredChunk::from_js(&key, js_val)?.keyframes().iter().cloned());
+                cursor.next(None).await?;
+            }
+        }
+        keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+        let mut chunks = keyframe::split_into_chunks(object_id, keyframes, new_chunk_size as f32);
+        let written: Result<(), Error> = async {
+            store.delete(range)?.await?;
+            for chunk in &mut chunks {
+                chunk.seal();
+                store.put(&self.encode_chunk(chunk)?, None)?.await?;
+            }
+            Ok(())
+        }
+        .await;
+        match written {
+            Ok(()) => tx.commit()?,
+            Err(e) => {
+                tx.abort()?;
+                return Err(e.into());
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Remove every stored chunk, for all objects and shared tracks.
+    pub async fn clear_all(&self) -> Result<(), Error> {
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
+        let store = tx.object_store("keyframe_chunks")?;
+        store.clear()?.await?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Bytes used by this origin's storage, as reported by `navigator.storage.estimate()`.
+    /// Covers everything the origin stores, not only the keyframe chunks.
+    pub async fn estimate_storage_usage() -> Result<f64, JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window` exists"))?;
+        let promise = window.navigator().storage().estimate()?;
+        let estimate: StorageEstimate = JsFuture::from(promise).await?.unchecked_into();
+        Ok(estimate.get_usage().unwrap_or(0.0))
+    }
+
+    /// Number of keyframes across every stored chunk, counted with a cursor so only one
+    /// chunk is deserialised at a time.
+    pub async fn count_total_keyframes(&self) -> Result<u32, DbError> {
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadOnly)?;
+        let store = tx.object_store("keyframe_chunks")?;
+
+        let Some(cursor) = store.open_cursor(None, None)?.await? else {
+            return Ok(0);
+        };
+        let mut cursor = cursor.into_managed();
+        let mut total = 0;
+        while let Some(js_val) = cursor.value()? {
+            let key = cursor.key()?.and_then(|key| key.as_string()).unwrap_or_default();
+            total += StoredChunk::from_js(&key, js_val)?.keyframes().len() as u32;
+            cursor.next(None).await?;
+        }
+
+        Ok(total)
+    }
+
+    /// Ids of every object or track with at least one stored chunk, sorted.
+    pub async fn list_all_object_ids(&self) -> Result<Vec<String>, Error> {
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadOnly)?;
+        let index = tx.object_store("keyframe_chunks")?.index("by_object_chunk_id")?;
+
+        let mut object_ids = BTreeSet::new();
+        if let Some(cursor) = index.open_key_cursor(None, None)?.await? {
+            let mut cursor = cursor.into_managed();
+            while let Some(key) = cursor.key()? {
+                if let Some((object_id, _)) = key.as_string().as_deref().and_then(|key| key.rsplit_once('_')) {
+                    object_ids.insert(object_id.to_string());
+                }
+                cursor.next(None).await?;
+            }
+        }
+        Ok(object_ids.into_iter().collect())
+    }
+
+    /// Whether at least one chunk is stored for `object_id`.
+    pub async fn object_exists(&self, object_id: &str) -> Result<bool, Error> {
+        let lower = JsValue::from_str(&format!("{}_", object_id));
+        let upper = JsValue::from_str(&format!("{}_\u{ffff}", object_id));
+        let range = KeyRange::bound(&lower, &upper, None, None)?;
+
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadOnly)?;
+        let index = tx.object_store("keyframe_chunks")?.index("by_object_chunk_id")?;
+
+        // The range also matches ids that merely start with `{object_id}_`, such as
+        // `size_3` for `size`, so check what precedes each key's chunk index.
+        if let Some(cursor) = index.open_key_cursor(Some(range.into()), None)?.await? {
+            let mut cursor = cursor.into_managed();
+            while let Some(key) = cursor.key()? {
+                let key = key.as_string().unwrap_or_default();
+                if key.rsplit_once('_').is_some_and(|(id, _)| id == object_id) {
+                    return Ok(true);
+                }
+                cursor.next(None).await?;
+            }
+        }
+        Ok(false)
+    }
+
     pub async fn load_chunk(
         &self,
         object_id: &str,
         chunk_id: u32,
-    ) -> Result<KeyframeChunk, Error> {
+    ) -> Result<KeyframeChunk, DbError> {
         let key_str = format!("{}_{}", object_id, chunk_id);
         let js_key = JsValue::from_str(&key_str);
 
@@ -80,12 +461,224 @@ impl KeyframeDatabase {
         let maybe = req.await?;
         
         if let Some(js_val) = maybe {
-            let chunk: KeyframeChunk = serde_wasm_bindgen::from_value(js_val).unwrap();
-            Ok(chunk)
+            StoredChunk::from_js(&key_str, js_val)
         } else {
             Err(Error::AddFailed(JsValue::from_str(
                 &format!("No chunk found for key '{}'", key_str),
-            )))
+            )).into())
+        }
+    }
+
+    /// Load several chunks of one object through a single read-only transaction,
+    /// returned in the order of `chunk_ids`.
+    pub async fn bulk_load_chunks(
+        &self,
+        object_id: &str,
+        chunk_ids: &[u32],
+    ) -> Result<Vec<KeyframeChunk>, DbError> {
+        if chunk_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadOnly)?;
+        let store = tx.object_store("keyframe_chunks")?;
+
+        let keys: Vec<String> = chunk_ids
+            .iter()
+            .map(|chunk_id| format!("{}_{}", object_id, chunk_id))
+            .collect();
+        let requests = keys
+            .iter()
+            .map(|key| store.get(JsValue::from_str(key)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let results = futures::future::join_all(requests.into_iter().map(IntoFuture::into_future)).await;
+
+        let mut chunks = Vec::with_capacity(keys.len());
+        for (key, result) in keys.iter().zip(results) {
+            let js_val = result?.ok_or_else(|| {
+                Error::AddFailed(JsValue::from_str(&format!("No chunk found for key '{}'", key)))
+            })?;
+            chunks.push(StoredChunk::from_js(key, js_val)?);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Delete every chunk keyed `{object_id}_*` with a single key-range request.
+    pub async fn delete_object_chunks(&self, object_id: &str) -> Result<(), Error> {
+        let lower = JsValue::from_str(&format!("{}_", object_id));
+        let upper = JsValue::from_str(&format!("{}_\u{ffff}", object_id));
+        let range = KeyRange::bound(&lower, &upper, None, None)?;
+
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
+        let store = tx.object_store("keyframe_chunks")?;
+        store.delete(range)?.await?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub async fn delete_chunk(
+        &self,
+        object_id: &str,
+        chunk_id: u32,
+    ) -> Result<(), Error> {
+        let key_str = format!("{}_{}", object_id, chunk_id);
+        let js_key = JsValue::from_str(&key_str);
+
+        let tx = self.db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite)?;
+        let store = tx.object_store("keyframe_chunks")?;
+        store.delete(js_key)?.await?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+impl ChunkSink for KeyframeDatabase {
+    type Error = Error;
+
+    fn is_quota_exceeded(e: &Error) -> bool {
+        is_quota_exceeded(e)
+    }
+
+    async fn write(&self, batch: &[KeyframeChunk]) -> Result<(), Error> {
+        self.save_batch(batch).await
+    }
+
+    async fn evict(&self, attempt: u32) -> Result<(), Error> {
+        web_sys::console::warn_1(&JsValue::from_str(&format!(
+            "IndexedDB quota exceeded, clearing all stored keyframe chunks (retry {} of {})",
+            attempt + 1,
+            MAX_SAVE_RETRIES
+        )));
+        self.clear_all().await
+    }
+
+    async fn back_off(&self, delay_ms: u32) {
+        gloo_timers::future::TimeoutFuture::new(delay_ms).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[derive(Debug, PartialEq)]
+    enum MockError {
+        QuotaExceeded,
+        Broken,
+    }
+
+    impl fmt::Display for MockError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl From<MockError> for DbError {
+        fn from(e: MockError) -> Self {
+            DbError::StorageFull(e.to_string())
+        }
+    }
+
+    /// Store that fails the writes listed in `failing_writes` (1-based) with `error`.
+    struct MockSink {
+        failing_writes: Vec<u32>,
+        error: fn() -> MockError,
+        writes: Cell<u32>,
+        evictions: Cell<u32>,
+        delays: RefCell<Vec<u32>>,
+    }
+
+    impl MockSink {
+        fn failing_on(failing_writes: Vec<u32>, error: fn() -> MockError) -> Self {
+            Self {
+                failing_writes,
+                error,
+                writes: Cell::new(0),
+                evictions: Cell::new(0),
+                delays: RefCell::new(Vec::new()),
+            }
         }
     }
-}
\ No newline at end of file
+
+    impl ChunkSink for MockSink {
+        type Error = MockError;
+
+        fn is_quota_exceeded(e: &MockError) -> bool {
+            *e == MockError::QuotaExceeded
+        }
+
+        async fn write(&self, _batch: &[KeyframeChunk]) -> Result<(), MockError> {
+            self.writes.set(self.writes.get() + 1);
+            if self.failing_writes.contains(&self.writes.get()) {
+                return Err((self.error)());
+            }
+            Ok(())
+        }
+
+        async fn evict(&self, _attempt: u32) -> Result<(), MockError> {
+            self.evictions.set(self.evictions.get() + 1);
+            Ok(())
+        }
+
+        async fn back_off(&self, delay_ms: u32) {
+            self.delays.borrow_mut().push(delay_ms);
+        }
+    }
+
+    /// Save two batches, returning the result of the second.
+    fn save_two_batches(sink: &MockSink, policy: EvictionPolicy) -> Result<(), DbError> {
+        block_on(async {
+            save_batch_with_retries(sink, policy, &[]).await?;
+            save_batch_with_retries(sink, policy, &[]).await
+        })
+    }
+
+    #[test]
+    fn quota_error_on_second_write_evicts_and_retries() {
+        let sink = MockSink::failing_on(vec![2], || MockError::QuotaExceeded);
+        save_two_batches(&sink, EvictionPolicy::ClearOnFull).unwrap();
+
+        assert_eq!(sink.writes.get(), 3);
+        assert_eq!(sink.evictions.get(), 1);
+        assert_eq!(*sink.delays.borrow(), vec![RETRY_BASE_DELAY_MS]);
+    }
+
+    #[test]
+    fn quota_error_on_second_write_fails_fast() {
+        let sink = MockSink::failing_on(vec![2], || MockError::QuotaExceeded);
+        let result = save_two_batches(&sink, EvictionPolicy::FailFast);
+
+        assert!(matches!(result, Err(DbError::StorageFull(_))));
+        assert_eq!(sink.writes.get(), 2);
+        assert_eq!(sink.evictions.get(), 0);
+    }
+
+    #[test]
+    fn retries_back_off_exponentially_then_give_up() {
+        let sink = MockSink::failing_on((2..10).collect(), || MockError::QuotaExceeded);
+        let result = save_two_batches(&sink, EvictionPolicy::ClearOnFull);
+
+        match result {
+            Err(DbError::StorageFull(msg)) => assert!(msg.contains("still full"), "{}", msg),
+            other => panic!("expected StorageFull, got {:?}", other),
+        }
+        assert_eq!(sink.writes.get(), 2 + MAX_SAVE_RETRIES);
+        assert_eq!(sink.evictions.get(), MAX_SAVE_RETRIES);
+        assert_eq!(
+            *sink.delays.borrow(),
+            (0..MAX_SAVE_RETRIES).map(|attempt| RETRY_BASE_DELAY_MS << attempt).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let sink = MockSink::failing_on(vec![2], || MockError::Broken);
+        assert!(save_two_batches(&sink, EvictionPolicy::ClearOnFull).is_err());
+        assert_eq!(sink.writes.get(), 2);
+        assert_eq!(sink.evictions.get(), 0);
+    }
+}
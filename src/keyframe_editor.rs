@@ -0,0 +1,123 @@
+use idb::Error;
+use wasm_bindgen::prelude::*;
+
+use crate::easing::Easing;
+use crate::keyframe::{self, Keyframe};
+use crate::keyframe_database::{DbError, KeyframeDatabase};
+use crate::keyframe_store::KeyframeStore;
+
+/// Keyframe times are stored as `f32`, so edits match an existing keyframe within this tolerance.
+const TIME_EPSILON: f32 = 1e-3;
+
+pub enum KeyframeEdit {
+    Insert(Keyframe),
+    Delete { time: f64 },
+    Move { old_time: f64, new_x: f64, new_y: f64, new_time: f64 },
+}
+
+/// Batches keyframe edits for a single object; nothing touches the database until `commit`.
+#[wasm_bindgen]
+pub struct KeyframeEditor {
+    object_id: u32,
+    pending_changes: Vec<KeyframeEdit>,
+}
+
+#[wasm_bindgen]
+impl KeyframeEditor {
+    #[wasm_bindgen(getter)]
+    pub fn object_id(&self) -> u32 {
+        self.object_id
+    }
+
+    pub fn insert(&mut self, time: f64, x: f64, y: f64) {
+        self.pending_changes
+            .push(KeyframeEdit::Insert(Keyframe::new(time as f32, x as f32, y as f32)));
+    }
+
+    /// Insert a keyframe whose outgoing transition uses `easing`, e.g. `"ease_out_bounce"`.
+    pub fn insert_with_easing(&mut self, time: f64, x: f64, y: f64, easing: &str) -> Result<(), JsValue> {
+        let easing = Easing::parse(easing).map_err(|e| JsValue::from_str(&e))?;
+        self.pending_changes.push(KeyframeEdit::Insert(Keyframe::new_with_easing(
+            time as f32,
+            x as f32,
+            y as f32,
+            easing,
+        )));
+        Ok(())
+    }
+
+    pub fn delete(&mut self, time: f64) {
+        self.pending_changes.push(KeyframeEdit::Delete { time });
+    }
+
+    pub fn move_keyframe(&mut self, old_time: f64, new_time: f64, new_x: f64, new_y: f64) {
+        self.pending_changes.push(KeyframeEdit::Move { old_time, new_x, new_y, new_time });
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending_changes.len()
+    }
+}
+
+impl KeyframeEditor {
+    pub fn new(object_id: u32) -> Self {
+        Self {
+            object_id,
+            pending_changes: Vec::new(),
+        }
+    }
+
+    /// Apply all pending edits to the object's full track, re-split it into chunks,
+    /// persist them, and invalidate the store's cached chunks.
+    pub async fn commit(&self, db: &KeyframeDatabase, store: &mut KeyframeStore) -> Result<(), DbError> {
+        let mut keyframes = store.load_all_keyframes().await?;
+
+        for edit in &self.pending_changes {
+            match edit {
+                KeyframeEdit::Insert(keyframe) => keyframes.push(keyframe.clone()),
+                KeyframeEdit::Delete { time } => {
+                    let idx = Self::find_keyframe(&keyframes, *time)?;
+                    keyframes.remove(idx);
+                }
+                KeyframeEdit::Move { old_time, new_x, new_y, new_time } => {
+                    let idx = Self::find_keyframe(&keyframes, *old_time)?;
+                    let easing = keyframes[idx].easing();
+                    keyframes[idx] = Keyframe::new_with_easing(*new_time as f32, *new_x as f32, *new_y as f32, easing);
+                }
+            }
+        }
+
+        if keyframes.is_empty() {
+            return Err(Error::AddFailed(JsValue::from_str(
+                &format!("Edits would leave object {} without keyframes", self.object_id),
+            )).into());
+        }
+        keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+
+        let old_chunk_count = store.chunk_count();
+        let chunks = keyframe::split_into_chunks(store.object_id(), keyframes, store.chunk_size());
+        let total_duration = chunks
+            .iter()
+            .map(|chunk| chunk.end_time())
+            .fold(0.0, f32::max);
+
+        db.save_chunks(chunks).await?;
+        store.set_total_duration(total_duration.into());
+
+        for chunk_id in store.chunk_count()..old_chunk_count {
+            db.delete_chunk(store.object_id(), chunk_id).await?;
+        }
+
+        store.invalidate();
+        Ok(())
+    }
+
+    fn find_keyframe(keyframes: &[Keyframe], time: f64) -> Result<usize, Error> {
+        keyframes
+            .iter()
+            .position(|k| (k.time() - time as f32).abs() < TIME_EPSILON)
+            .ok_or_else(|| Error::AddFailed(JsValue::from_str(
+                &format!("No keyframe found at time {}", time),
+            )))
+    }
+}
@@ -1,14 +1,54 @@
-use std::{num::NonZero, sync::{Arc, RwLock}};
+use std::{collections::HashSet, num::NonZero, sync::{Arc, RwLock}};
 use lru::LruCache;
 
-use crate::{keyframe::KeyframeChunk, keyframe_database::KeyframeDatabase, math::Vector2};
+use crate::{keyframe::{Keyframe, KeyframeChunk}, keyframe_database::{DbError, KeyframeDatabase}, math::Vector2};
 
 const MAX_CHUNKS: usize = 2;
+const IN_FLIGHT_POLL_MS: u32 = 5;
+
+/// Chunk cache counters, shared by all clones of a store.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChunkCacheMetrics {
+    /// Position lookups that found their chunk cached.
+    pub hits: u64,
+    /// Position lookups that found their chunk missing.
+    pub misses: u64,
+    /// Chunks dropped by the LRU or by `evict_distant_chunks`.
+    pub evictions: u64,
+    /// Chunks read from the database into the cache.
+    pub loads: u64,
+    pub total_loaded_ms: f64,
+}
+
+impl ChunkCacheMetrics {
+    /// Fraction of lookups that hit, or 0 before the first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+
+    pub fn avg_load_ms(&self) -> f64 {
+        if self.loads == 0 {
+            return 0.0;
+        }
+        self.total_loaded_ms / self.loads as f64
+    }
+}
+
+#[derive(Clone)]
 pub struct KeyframeStore {
     object_id: String,
     chunk_size: f32,
     total_duration: f64,
     loaded_chunks: Arc<RwLock<LruCache<u32, KeyframeChunk>>>,
+    metrics: Arc<RwLock<ChunkCacheMetrics>>,
+    /// Chunks that failed their checksum and were deleted; they are not fetched again.
+    corrupted_chunk_ids: Arc<RwLock<HashSet<u32>>>,
+    /// Chunks currently being read from the database by some clone of this store.
+    in_flight: Arc<RwLock<HashSet<u32>>>,
     keyframe_db: Arc<KeyframeDatabase>,
 }
 
@@ -24,39 +64,320 @@ impl KeyframeStore {
             chunk_size,
             loaded_chunks: Arc::new(RwLock::new(LruCache::new(NonZero::new(MAX_CHUNKS).unwrap()))),
             total_duration,
+            metrics: Arc::new(RwLock::new(ChunkCacheMetrics::default())),
+            corrupted_chunk_ids: Arc::new(RwLock::new(HashSet::new())),
+            in_flight: Arc::new(RwLock::new(HashSet::new())),
             keyframe_db,
         }
     }
 
-    pub async fn fetch_data(&self, time: f64) -> Result<(), idb::Error> {
-        let t = time % self.total_duration;
+    /// `time` wrapped into the track. A track with no span holds at 0 rather than NaN.
+    fn track_time(&self, time: f64) -> f64 {
+        if self.total_duration > 0.0 {
+            time % self.total_duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Load the chunk covering `time` into the cache. A chunk that fails its checksum is
+    /// deleted and recorded in `corrupted_chunk_ids`; the object just holds its last
+    /// position through that stretch of the track.
+    pub async fn fetch_data(&self, time: f64) -> Result<(), DbError> {
+        let t = self.track_time(time);
         let chunk_idx = (t / self.chunk_size as f64).floor() as u32;
+        self.fetch_chunk(chunk_idx).await
+    }
 
-        {
-            let cache = self.loaded_chunks.read().unwrap();
-            if cache.contains(&chunk_idx) {
-                return Ok(());
+    /// Load chunk `chunk_idx` into the cache unless it is already there or known corrupted.
+    /// If another caller is already reading the same chunk, wait for it instead of issuing
+    /// a second read.
+    pub async fn fetch_chunk(&self, chunk_idx: u32) -> Result<(), DbError> {
+        loop {
+            {
+                let cache = self.loaded_chunks.read().unwrap();
+                if cache.contains(&chunk_idx) || self.corrupted_chunk_ids.read().unwrap().contains(&chunk_idx) {
+                    return Ok(());
+                }
             }
+            if self.in_flight.write().unwrap().insert(chunk_idx) {
+                break;
+            }
+            gloo_timers::future::TimeoutFuture::new(IN_FLIGHT_POLL_MS).await;
         }
 
-        let chunk = self
-            .keyframe_db
-            .load_chunk(&self.object_id, chunk_idx)
-            .await?;
+        let result = self.load_chunk(chunk_idx).await;
+        self.in_flight.write().unwrap().remove(&chunk_idx);
+        result
+    }
+
+    /// Load the chunk at the start of the track, and the one after it if the track spans more
+    /// than one chunk, so a new object has a position on its first frame.
+    pub async fn warm_up(&self) -> Result<(), DbError> {
+        self.fetch_chunk(0).await?;
+        if self.total_duration > self.chunk_size as f64 {
+            self.fetch_chunk(1).await?;
+        }
+        Ok(())
+    }
 
-        {
+    async fn load_chunk(&self, chunk_idx: u32) -> Result<(), DbError> {
+        let started = now();
+        let chunk = match self.keyframe_db.fetch_chunk(&self.object_id, chunk_idx).await {
+            Ok(chunk) => chunk,
+            Err(DbError::CorruptedChunk(key)) => {
+                web_sys::console::warn_1(&format!("Deleting corrupted keyframe chunk '{}'", key).into());
+                self.corrupted_chunk_ids.write().unwrap().insert(chunk_idx);
+                self.keyframe_db.delete_chunk(&self.object_id, chunk_idx).await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let evicted = {
             let mut cache = self.loaded_chunks.write().unwrap();
-            cache.put(chunk_idx, chunk);
+            cache.push(chunk_idx, chunk).is_some_and(|(idx, _)| idx != chunk_idx)
+        };
+        let mut metrics = self.metrics.write().unwrap();
+        metrics.loads += 1;
+        metrics.total_loaded_ms += now() - started;
+        if evicted {
+            metrics.evictions += 1;
         }
 
         Ok(())
     }
 
+    /// Position at `time`, or `None` if its chunk isn't cached. A miss is counted and the
+    /// chunk queued for an urgent fetch, so the caller only holds its last position briefly.
+    /// Lookups landing in a corrupted chunk count as neither hit nor miss. A cached
+    /// placeholder chunk is a hit with no position: the object holds still through the gap.
     pub fn get_interpolated_position(&self, time: f64) -> Option<Vector2> {
-        let t = time % self.total_duration;
+        let t = self.track_time(time);
+        let chunk_idx = (t / self.chunk_size as f64).floor() as u32;
+
+        let cached = self
+            .loaded_chunks
+            .write()
+            .unwrap()
+            .get_mut(&chunk_idx)
+            .map(|chunk| (!chunk.is_empty()).then(|| chunk.interpolate(t as f32)));
+        match cached {
+            Some(pos) => {
+                self.metrics.write().unwrap().hits += 1;
+                pos
+            }
+            None => {
+                if !self.corrupted_chunk_ids.read().unwrap().contains(&chunk_idx) {
+                    self.metrics.write().unwrap().misses += 1;
+                    self.keyframe_db.request_urgent_fetch(&self.object_id, chunk_idx);
+                }
+                None
+            }
+        }
+    }
+
+    /// Lookups that found their chunk missing, shared by all clones of this store.
+    pub fn position_miss_count(&self) -> u64 {
+        self.metrics.read().unwrap().misses
+    }
+
+    pub fn cache_metrics(&self) -> ChunkCacheMetrics {
+        *self.metrics.read().unwrap()
+    }
+
+    /// Zero the counters for this store and every clone of it.
+    pub fn reset_cache_metrics(&self) {
+        *self.metrics.write().unwrap() = ChunkCacheMetrics::default();
+    }
+
+    /// Position at `time`, clamped to the track. An uncached chunk is read straight from the
+    /// database without entering the cache, so lookups far from the playhead don't evict
+    /// the chunks playback is using.
+    pub async fn position_at(&self, time: f64) -> Result<Vector2, DbError> {
+        let t = time.clamp(0.0, self.total_duration);
+        let chunk_idx = (t / self.chunk_size as f64).floor() as u32;
+
+        let chunk = self.peek_or_load(chunk_idx).await?;
+        if !chunk.is_empty() {
+            return Ok(chunk.interpolate(t as f32));
+        }
+        // In a placeholder the object holds the last keyframe before the gap, or waits at
+        // the first one after it if the gap opens the track.
+        for idx in (0..chunk_idx).rev() {
+            if let Some(k) = self.peek_or_load(idx).await?.last_keyframe() {
+                return Ok(Vector2::new(k.x().into(), k.y().into()));
+            }
+        }
+        for idx in chunk_idx + 1..self.chunk_count() {
+            if let Some(k) = self.peek_or_load(idx).await?.first_keyframe() {
+                return Ok(Vector2::new(k.x().into(), k.y().into()));
+            }
+        }
+        Ok(Vector2::new(0.0, 0.0))
+    }
+
+    /// Chunk `chunk_idx` from the cache if present, otherwise straight from the database
+    /// without caching it.
+    async fn peek_or_load(&self, chunk_idx: u32) -> Result<KeyframeChunk, DbError> {
+        let cached = self.loaded_chunks.read().unwrap().peek(&chunk_idx).cloned();
+        match cached {
+            Some(chunk) => Ok(chunk),
+            None => self.keyframe_db.load_chunk(&self.object_id, chunk_idx).await,
+        }
+    }
+
+    /// Velocity at `time` in pixels per ms, or `None` if its chunk isn't loaded. Neighbouring
+    /// chunks are consulted only if they are already cached.
+    pub fn get_velocity_at(&self, time: f64) -> Option<Vector2> {
+        let t = self.track_time(time);
         let chunk_idx = (t / self.chunk_size as f64).floor() as u32;
 
+        let cache = self.loaded_chunks.read().unwrap();
+        let chunk = cache.peek(&chunk_idx)?;
+        let prev = chunk_idx
+            .checked_sub(1)
+            .and_then(|idx| cache.peek(&idx))
+            .and_then(|c| c.last_keyframe());
+        let next = cache.peek(&(chunk_idx + 1)).and_then(|c| c.first_keyframe());
+        Some(chunk.velocity(t as f32, prev, next))
+    }
+
+    /// Database key prefix of the track; differs from the owning object's id for shared tracks.
+    pub fn object_id(&self) -> &str {
+        &self.object_id
+    }
+
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
+
+    pub fn total_duration(&self) -> f64 {
+        self.total_duration
+    }
+
+    pub fn set_total_duration(&mut self, total_duration: f64) {
+        self.total_duration = total_duration;
+    }
+
+    /// Number of chunks the current track spans, i.e. the highest chunk index plus one.
+    pub fn chunk_count(&self) -> u32 {
+        (self.total_duration / self.chunk_size as f64).floor() as u32 + 1
+    }
+
+    /// Make room for one more object playing this track at its own phase, so instances
+    /// don't keep evicting each other's chunks. Capped at the number of chunks in the track.
+    pub fn grow_cache_for_instance(&self) {
+        let mut cache = self.loaded_chunks.write().unwrap();
+        let capacity = (cache.cap().get() + MAX_CHUNKS).min((self.chunk_count() as usize).max(MAX_CHUNKS));
+        cache.resize(NonZero::new(capacity).unwrap());
+    }
+
+    /// Insert a keyframe into the chunk covering `time`, creating that chunk if it lies past
+    /// the end of the track, then persist it and evict the stale cached copy. Chunks between
+    /// the old end of the track and a new chunk are written as empty placeholders, one per
+    /// chunk of the gap, so callers must bound `time` (see `add_keyframe_to_object`).
+    pub async fn add_keyframe(&mut self, time: f64, x: f64, y: f64) -> Result<(), DbError> {
+        let chunk_idx = (time / self.chunk_size as f64).floor() as u32;
+        let chunk_count = self.chunk_count();
+        // A corrupted chunk was deleted, so its keyframes are gone; start it afresh.
+        let corrupted = self.corrupted_chunk_ids.write().unwrap().remove(&chunk_idx);
+        let mut chunks: Vec<KeyframeChunk> = (chunk_count..chunk_idx)
+            .map(|idx| KeyframeChunk::placeholder(&self.object_id, idx, self.chunk_size))
+            .collect();
+        let mut chunk = if chunk_idx < chunk_count && !corrupted {
+            self.keyframe_db.load_chunk(&self.object_id, chunk_idx).await?
+        } else {
+            KeyframeChunk::new(
+                &format!("{}_{}", self.object_id, chunk_idx),
+                time as f32,
+                time as f32,
+                Vec::new(),
+            )
+        };
+        chunk.add_keyframe(time as f32, x as f32, y as f32);
+
+        let end_time = chunk.end_time() as f64;
+        chunks.push(chunk);
+        self.keyframe_db.save_chunks(chunks).await?;
+        self.total_duration = self.total_duration.max(end_time);
+
         let mut cache = self.loaded_chunks.write().unwrap();
-        cache.get_mut(&chunk_idx).map(|chunk| chunk.interpolate(t as f32))
+        cache.pop(&chunk_idx);
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Drop cached chunks whose time range lies more than `keep_window` ms from `current_time`.
+    /// Distances wrap around the end of the track, since playback loops.
+    pub fn evict_distant_chunks(&self, current_time: f64, keep_window: f64) {
+        let t = self.track_time(current_time);
+        let mut cache = self.loaded_chunks.write().unwrap();
+        let distant: Vec<u32> = cache
+            .iter()
+            .filter(|(_, chunk)| {
+                self.wrapped_distance(t, chunk.start_time().into(), chunk.end_time().into()) > keep_window
+            })
+            .map(|(chunk_idx, _)| *chunk_idx)
+            .collect();
+
+        for chunk_idx in &distant {
+            cache.pop(chunk_idx);
+        }
+        self.metrics.write().unwrap().evictions += distant.len() as u64;
+    }
+
+    /// Indices of chunks that failed their checksum, in ascending order.
+    pub fn corrupted_chunk_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.corrupted_chunk_ids.read().unwrap().iter().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Number of chunks dropped from the cache, shared by all clones of this store.
+    pub fn cache_evictions(&self) -> u64 {
+        self.metrics.read().unwrap().evictions
+    }
+
+    fn wrapped_distance(&self, time: f64, start: f64, end: f64) -> f64 {
+        if time < start {
+            (start - time).min(time + self.total_duration - end)
+        } else if time > end {
+            (time - end).min(self.total_duration - time + start)
+        } else {
+            0.0
+        }
+    }
+
+    /// Drop every cached chunk so the next `fetch_data` reloads from the database.
+    pub fn invalidate(&self) {
+        let mut cache = self.loaded_chunks.write().unwrap();
+        cache.clear();
+    }
+
+    /// Indices of the track's chunks still in the database, i.e. all but the corrupted ones.
+    pub fn intact_chunk_ids(&self) -> Vec<u32> {
+        let corrupted = self.corrupted_chunk_ids.read().unwrap();
+        (0..self.chunk_count()).filter(|idx| !corrupted.contains(idx)).collect()
+    }
+
+    /// Load every intact chunk of this object from the database, in time order,
+    /// and return the concatenated keyframe track.
+    pub async fn load_all_keyframes(&self) -> Result<Vec<Keyframe>, DbError> {
+        let chunks = self
+            .keyframe_db
+            .bulk_load_chunks(&self.object_id, &self.intact_chunk_ids())
+            .await?;
+
+        Ok(chunks
+            .iter()
+            .flat_map(|chunk| chunk.keyframes().iter().cloned())
+            .collect())
+    }
+}
+
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,178 @@
+use std::f64::consts::TAU;
+
+use serde::Deserialize;
+
+/// Distance between neighbouring centres, in pixels, when `spacing` is left out.
+const DEFAULT_SPACING: f64 = 120.0;
+
+/// Optional settings for `Alignment::parse`; anything left out takes a default.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutParams {
+    spacing: Option<f64>,
+    origin_x: Option<f64>,
+    origin_y: Option<f64>,
+    radius: Option<f64>,
+    columns: Option<u32>,
+}
+
+/// An arrangement of object centres starting from, or around, `origin`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alignment {
+    /// Left to right, `spacing` apart.
+    Row { origin: (f64, f64), spacing: f64 },
+    /// Top to bottom, `spacing` apart.
+    Column { origin: (f64, f64), spacing: f64 },
+    /// Evenly around `origin`, starting at the top and going clockwise. Without a
+    /// `radius`, one is chosen so neighbours are `spacing` apart along the circle.
+    Circle { origin: (f64, f64), radius: Option<f64>, spacing: f64 },
+    /// Rows of `columns` cells, `spacing` apart; a square grid when `columns` is left out.
+    Grid { origin: (f64, f64), spacing: f64, columns: Option<u32> },
+}
+
+impl Alignment {
+    pub fn parse(kind: &str, params: LayoutParams) -> Result<Self, String> {
+        let origin = (params.origin_x.unwrap_or(0.0), params.origin_y.unwrap_or(0.0));
+        if !origin.0.is_finite() || !origin.1.is_finite() {
+            return Err("Layout origin must be finite".to_string());
+        }
+        let spacing = params.spacing.unwrap_or(DEFAULT_SPACING);
+        if !spacing.is_finite() {
+            return Err("Layout spacing must be finite".to_string());
+        }
+        match kind {
+            "row" => Ok(Alignment::Row { origin, spacing }),
+            "column" => Ok(Alignment::Column { origin, spacing }),
+            "circle" => {
+                if let Some(radius) = params.radius.filter(|r| !r.is_finite() || *r < 0.0) {
+                    return Err(format!("Circle radius must be a non-negative number, got {}", radius));
+                }
+                Ok(Alignment::Circle { origin, radius: params.radius, spacing })
+            }
+            "grid" => {
+                if params.columns == Some(0) {
+                    return Err("Grid needs at least one column".to_string());
+                }
+                Ok(Alignment::Grid { origin, spacing, columns: params.columns })
+            }
+            other => Err(format!(
+                "Unknown alignment '{}', expected 'row', 'column', 'circle' or 'grid'",
+                other
+            )),
+        }
+    }
+
+    /// Centres for `count` objects, in order.
+    pub fn positions(&self, count: usize) -> Vec<(f64, f64)> {
+        match *self {
+            Alignment::Row { origin, spacing } => (0..count)
+                .map(|i| (origin.0 + spacing * i as f64, origin.1))
+                .collect(),
+            Alignment::Column { origin, spacing } => (0..count)
+                .map(|i| (origin.0, origin.1 + spacing * i as f64))
+                .collect(),
+            Alignment::Circle { origin, radius, spacing } => {
+                let radius = radius.unwrap_or(spacing * count as f64 / TAU);
+                (0..count)
+                    .map(|i| {
+                        let angle = TAU * i as f64 / count as f64;
+                        (origin.0 + radius * angle.sin(), origin.1 - radius * angle.cos())
+                    })
+                    .collect()
+            }
+            Alignment::Grid { origin, spacing, columns } => {
+                let columns = columns.map_or_else(|| (count as f64).sqrt().ceil().max(1.0) as usize, |c| c as usize);
+                (0..count)
+                    .map(|i| {
+                        let (row, column) = (i / columns, i % columns);
+                        (origin.0 + spacing * column as f64, origin.1 + spacing * row as f64)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn assert_positions(actual: &[(f64, f64)], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "expected {:?}, got {:?}", expected, actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a.0 - e.0).abs() < EPSILON && (a.1 - e.1).abs() < EPSILON, "expected {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    fn params(spacing: f64) -> LayoutParams {
+        LayoutParams { spacing: Some(spacing), origin_x: Some(100.0), origin_y: Some(50.0), ..LayoutParams::default() }
+    }
+
+    #[test]
+    fn row_steps_right_from_the_origin() {
+        let row = Alignment::parse("row", params(20.0)).unwrap();
+        assert_positions(&row.positions(3), &[(100.0, 50.0), (120.0, 50.0), (140.0, 50.0)]);
+    }
+
+    #[test]
+    fn column_steps_down_from_the_origin() {
+        let column = Alignment::parse("column", params(20.0)).unwrap();
+        assert_positions(&column.positions(3), &[(100.0, 50.0), (100.0, 70.0), (100.0, 90.0)]);
+    }
+
+    #[test]
+    fn circle_starts_at_the_top_and_goes_clockwise() {
+        let circle = Alignment::parse("circle", LayoutParams { radius: Some(10.0), ..params(20.0) }).unwrap();
+        assert_positions(&circle.positions(4), &[(100.0, 40.0), (110.0, 50.0), (100.0, 60.0), (90.0, 50.0)]);
+    }
+
+    #[test]
+    fn circle_without_a_radius_spaces_neighbours_along_the_arc() {
+        let circle = Alignment::parse("circle", params(30.0)).unwrap();
+        let positions = circle.positions(6);
+        let (x, y) = positions[0];
+        let radius = (x - 100.0).hypot(y - 50.0);
+        assert!((radius * TAU / 6.0 - 30.0).abs() < EPSILON, "radius {}", radius);
+    }
+
+    #[test]
+    fn grid_is_square_by_default() {
+        let grid = Alignment::parse("grid", params(10.0)).unwrap();
+        assert_positions(
+            &grid.positions(5),
+            &[(100.0, 50.0), (110.0, 50.0), (120.0, 50.0), (100.0, 60.0), (110.0, 60.0)],
+        );
+    }
+
+    #[test]
+    fn grid_wraps_after_the_given_columns() {
+        let grid = Alignment::parse("grid", LayoutParams { columns: Some(2), ..params(10.0) }).unwrap();
+        assert_positions(&grid.positions(3), &[(100.0, 50.0), (110.0, 50.0), (100.0, 60.0)]);
+    }
+
+    #[test]
+    fn no_objects_give_no_positions() {
+        for kind in ["row", "column", "circle", "grid"] {
+            assert!(Alignment::parse(kind, LayoutParams::default()).unwrap().positions(0).is_empty());
+        }
+    }
+
+    #[test]
+    fn defaults_start_at_zero_with_default_spacing() {
+        assert_eq!(
+            Alignment::parse("row", LayoutParams::default()),
+            Ok(Alignment::Row { origin: (0.0, 0.0), spacing: DEFAULT_SPACING })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_params() {
+        assert!(Alignment::parse("diagonal", LayoutParams::default()).is_err());
+        assert!(Alignment::parse("grid", LayoutParams { columns: Some(0), ..LayoutParams::default() }).is_err());
+        assert!(Alignment::parse("circle", LayoutParams { radius: Some(-1.0), ..LayoutParams::default() }).is_err());
+        assert!(Alignment::parse("row", LayoutParams { spacing: Some(f64::NAN), ..LayoutParams::default() }).is_err());
+        assert!(Alignment::parse("row", LayoutParams { origin_x: Some(f64::INFINITY), ..LayoutParams::default() }).is_err());
+    }
+}
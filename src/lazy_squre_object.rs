@@ -0,0 +1,70 @@
+#![warn(clippy::cast_precision_loss)]
+
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::aabb::AABB;
+use crate::math::Vector2;
+
+/// A square whose position comes from a JS function of time instead of stored keyframes.
+/// Nothing is precomputed or persisted: `pattern_fn(time_ms)` is called once per update and
+/// must return `{ x, y }`.
+pub struct LazySquareObject {
+    object_id: u32,
+    size: f64,
+    color: String,
+    current_time: f64,
+    cached_x: f64,
+    cached_y: f64,
+    pattern_fn: Function,
+}
+
+impl LazySquareObject {
+    pub fn new(object_id: u32, size: f64, color: &str, pattern_fn: Function) -> Result<Self, JsValue> {
+        let mut obj = LazySquareObject {
+            object_id,
+            size,
+            color: color.to_string(),
+            current_time: 0.0,
+            cached_x: 0.0,
+            cached_y: 0.0,
+            pattern_fn,
+        };
+        obj.refresh_position()?;
+        Ok(obj)
+    }
+
+    pub fn object_id(&self) -> u32 {
+        self.object_id
+    }
+
+    pub fn update(&mut self, delta_time: f64) -> Result<(), JsValue> {
+        self.current_time += delta_time;
+        self.refresh_position()
+    }
+
+    fn refresh_position(&mut self) -> Result<(), JsValue> {
+        let value = self
+            .pattern_fn
+            .call1(&JsValue::NULL, &JsValue::from_f64(self.current_time))?;
+        let pos: Vector2 = serde_wasm_bindgen::from_value(value).map_err(|e| {
+            JsValue::from_str(&format!(
+                "Pattern function of object {} must return {{ x, y }}: {}",
+                self.object_id, e
+            ))
+        })?;
+        self.cached_x = pos.x;
+        self.cached_y = pos.y;
+        Ok(())
+    }
+
+    pub fn bounds(&self) -> AABB {
+        AABB::new(self.cached_x, self.cached_y, self.cached_x + self.size, self.cached_y + self.size)
+    }
+
+    pub fn render(&self, context: &CanvasRenderingContext2d) {
+        context.set_fill_style_str(&self.color);
+        context.fill_rect(self.cached_x, self.cached_y, self.size, self.size);
+    }
+}
@@ -1,12 +1,33 @@
-mod aabb;
-mod animation_frame;
-mod squre_object;
-mod math;
-mod input;
-mod engine;
-mod keyframe;
-mod keyframe_database;
-mod keyframe_store;
+pub mod aabb;
+pub mod easing;
+pub mod animation_frame;
+pub mod background;
+pub mod binary_scene;
+pub mod chunk_worker;
+pub mod color;
+pub mod constraint;
+pub mod debug_display;
+pub mod squre_object;
+pub mod math;
+pub mod object_watcher;
+pub mod input;
+pub mod engine;
+pub mod error;
+pub mod fill_style;
+pub mod heat_map;
+pub mod keyframe;
+pub mod keyframe_database;
+pub mod keyframe_editor;
+pub mod keyframe_store;
+pub mod layout;
+pub mod lazy_squre_object;
+pub mod profiler;
+pub mod scene;
+pub mod scene_tree;
+pub mod timed_text;
+pub mod pool;
+pub mod quadtree;
+pub mod ring_buffer;
 
 use wasm_bindgen::prelude::*;
 
@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
 #[wasm_bindgen]
 pub struct Vector2 {
     pub x: f64,
@@ -12,4 +15,177 @@ impl Vector2 {
     pub fn new(x: f64, y: f64) -> Self {
         Self { x, y }
     }
-}
\ No newline at end of file
+
+    pub fn zero() -> Vector2 {
+        Vector2::new(0.0, 0.0)
+    }
+
+    pub fn one() -> Vector2 {
+        Vector2::new(1.0, 1.0)
+    }
+
+    /// Polar constructor: `magnitude` along `angle` radians from the +x axis.
+    pub fn from_angle(angle: f64, magnitude: f64) -> Vector2 {
+        Vector2::new(angle.cos() * magnitude, angle.sin() * magnitude)
+    }
+
+    pub fn dot(&self, other: &Vector2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Signed angle in radians rotating `self` onto `other`, in `(-PI, PI]`.
+    pub fn angle_to(&self, other: &Vector2) -> f64 {
+        let cross = self.x * other.y - self.y * other.x;
+        cross.atan2(self.dot(other))
+    }
+
+    /// Squared distance to `other`; cheaper than `distance` when only comparing.
+    pub fn distance_squared(&self, other: &Vector2) -> f64 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        dx * dx + dy * dy
+    }
+
+    pub fn distance(&self, other: &Vector2) -> f64 {
+        self.distance_squared(other).sqrt()
+    }
+
+    /// `self` rotated by 90 degrees.
+    pub fn perp(&self) -> Vector2 {
+        Vector2::new(-self.y, self.x)
+    }
+
+    /// Projection of `self` onto the direction of `onto`; zero if `onto` is zero.
+    pub fn project(&self, onto: &Vector2) -> Vector2 {
+        let len_sq = onto.dot(onto);
+        if len_sq == 0.0 {
+            return Vector2::new(0.0, 0.0);
+        }
+        let scale = self.dot(onto) / len_sq;
+        Vector2::new(onto.x * scale, onto.y * scale)
+    }
+
+    /// Component-wise minimum.
+    pub fn min(&self, other: &Vector2) -> Vector2 {
+        Vector2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// Component-wise maximum.
+    pub fn max(&self, other: &Vector2) -> Vector2 {
+        Vector2::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    pub fn abs(&self) -> Vector2 {
+        Vector2::new(self.x.abs(), self.y.abs())
+    }
+
+    pub fn floor(&self) -> Vector2 {
+        Vector2::new(self.x.floor(), self.y.floor())
+    }
+
+    pub fn ceil(&self) -> Vector2 {
+        Vector2::new(self.x.ceil(), self.y.ceil())
+    }
+
+    /// Each component rounded to the nearest integer, halves away from zero.
+    pub fn round(&self) -> Vector2 {
+        Vector2::new(self.x.round(), self.y.round())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    const EPSILON: f64 = 1e-12;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+    }
+
+    fn assert_vec_close(actual: &Vector2, expected: &Vector2) {
+        assert_close(actual.x, expected.x);
+        assert_close(actual.y, expected.y);
+    }
+
+    #[test]
+    fn angle_to_cardinal_directions() {
+        let east = Vector2::new(1.0, 0.0);
+        assert_close(east.angle_to(&Vector2::new(1.0, 0.0)), 0.0);
+        assert_close(east.angle_to(&Vector2::new(0.0, 1.0)), FRAC_PI_2);
+        assert_close(east.angle_to(&Vector2::new(-1.0, 0.0)), PI);
+        assert_close(east.angle_to(&Vector2::new(0.0, -1.0)), -FRAC_PI_2);
+    }
+
+    #[test]
+    fn angle_to_is_antisymmetric_and_ignores_length() {
+        let a = Vector2::new(0.0, 3.0);
+        let b = Vector2::new(-5.0, 0.0);
+        assert_close(a.angle_to(&b), FRAC_PI_2);
+        assert_close(b.angle_to(&a), -FRAC_PI_2);
+    }
+
+    #[test]
+    fn from_angle_builds_polar_vectors() {
+        assert_vec_close(&Vector2::from_angle(0.0, 2.0), &Vector2::new(2.0, 0.0));
+        assert_vec_close(&Vector2::from_angle(FRAC_PI_2, 3.0), &Vector2::new(0.0, 3.0));
+        assert_vec_close(&Vector2::from_angle(PI, 1.0), &Vector2::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn perp_is_orthogonal() {
+        for v in [Vector2::new(3.0, 4.0), Vector2::new(-2.5, 7.0), Vector2::new(0.0, -1.0)] {
+            assert_eq!(v.dot(&v.perp()), 0.0);
+        }
+        assert_eq!(Vector2::new(1.0, 0.0).perp(), Vector2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn project_onto_axis_and_zero() {
+        let v = Vector2::new(3.0, 4.0);
+        assert_eq!(v.project(&Vector2::new(10.0, 0.0)), Vector2::new(3.0, 0.0));
+        assert_eq!(v.project(&Vector2::zero()), Vector2::zero());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let v = Vector2::new(-3.25, 1e6);
+        let json = serde_json::to_value(&v).unwrap();
+        assert_eq!(json, serde_json::json!({ "x": -3.25, "y": 1e6 }));
+        assert_eq!(serde_json::from_value::<Vector2>(json).unwrap(), v);
+    }
+
+    #[test]
+    fn min_and_max_pick_each_component_independently() {
+        let a = Vector2::new(-3.0, 2.0);
+        let b = Vector2::new(1.0, -4.0);
+        assert_eq!(a.min(&b), Vector2::new(-3.0, -4.0));
+        assert_eq!(a.max(&b), Vector2::new(1.0, 2.0));
+        assert_eq!(a.min(&a), a);
+    }
+
+    #[test]
+    fn abs_of_mixed_signs() {
+        assert_eq!(Vector2::new(-2.5, 3.0).abs(), Vector2::new(2.5, 3.0));
+        assert_eq!(Vector2::new(4.0, -0.5).abs(), Vector2::new(4.0, 0.5));
+    }
+
+    #[test]
+    fn floor_rounds_towards_negative_infinity() {
+        assert_eq!(Vector2::new(1.7, -1.2).floor(), Vector2::new(1.0, -2.0));
+        assert_eq!(Vector2::new(-0.5, 3.0).floor(), Vector2::new(-1.0, 3.0));
+    }
+
+    #[test]
+    fn ceil_rounds_towards_positive_infinity() {
+        assert_eq!(Vector2::new(1.2, -1.7).ceil(), Vector2::new(2.0, -1.0));
+        assert_eq!(Vector2::new(-0.5, 3.0).ceil(), Vector2::new(-0.0, 3.0));
+    }
+
+    #[test]
+    fn round_takes_halves_away_from_zero() {
+        assert_eq!(Vector2::new(2.5, -2.5).round(), Vector2::new(3.0, -3.0));
+        assert_eq!(Vector2::new(1.49, -1.51).round(), Vector2::new(1.0, -2.0));
+    }
+}
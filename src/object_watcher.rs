@@ -0,0 +1,26 @@
+use js_sys::Function;
+
+/// Calls `callback(object_id, x, y)` whenever the object has moved more than `threshold`
+/// pixels from where it was when the callback last fired.
+pub struct ObjectWatcher {
+    pub id: u32,
+    pub object_id: u32,
+    pub last_x: f64,
+    pub last_y: f64,
+    pub threshold: f64,
+    pub callback: Function,
+}
+
+impl ObjectWatcher {
+    /// Record the new position and return true if it is far enough to notify.
+    pub fn check(&mut self, x: f64, y: f64) -> bool {
+        let dx = x - self.last_x;
+        let dy = y - self.last_y;
+        if dx * dx + dy * dy <= self.threshold * self.threshold {
+            return false;
+        }
+        self.last_x = x;
+        self.last_y = y;
+        true
+    }
+}
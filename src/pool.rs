@@ -0,0 +1,255 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::marker::PhantomData;
+
+/// Objects that can be returned to an `ObjectPool` and handed out again.
+pub trait Poolable {
+    /// Clear per-use state before the object is parked for reuse.
+    fn reset(&mut self);
+}
+
+/// Index of a live slot in an `ObjectPool`.
+pub struct PoolHandle<T> {
+    index: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Poolable> PoolHandle<T> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Reset the object and return its slot to the pool.
+    pub fn release(self, pool: &mut ObjectPool<T>) {
+        pool.release_index(self.index);
+    }
+}
+
+/// Slot storage that reuses both freed slots and released objects, so spawning and
+/// despawning does not keep growing, shifting or reallocating the backing storage.
+/// A reused slot keeps its position, so iteration order is slot order, not insertion order.
+/// Freed slots are reused lowest first, so a pool emptied by `release_all` refills in
+/// insertion order.
+pub struct ObjectPool<T: Poolable> {
+    slots: Vec<Option<T>>,
+    free_list: BinaryHeap<Reverse<usize>>,
+    spares: Vec<T>,
+}
+
+impl<T: Poolable> ObjectPool<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free_list: BinaryHeap::with_capacity(capacity),
+            spares: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Move a previously released object back into a slot. Its state was cleared by
+    /// `reset`, so re-initialise it through `get_mut`. `None` if nothing has been released.
+    pub fn acquire(&mut self) -> Option<PoolHandle<T>> {
+        let value = self.spares.pop()?;
+        Some(self.insert(value))
+    }
+
+    /// Place a new object in the lowest free slot, growing the pool only if there is none.
+    pub fn insert(&mut self, value: T) -> PoolHandle<T> {
+        let index = match self.free_list.pop() {
+            Some(Reverse(index)) => {
+                self.slots[index] = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Some(value));
+                self.slots.len() - 1
+            }
+        };
+        PoolHandle { index, _marker: PhantomData }
+    }
+
+    pub fn get(&self, handle: &PoolHandle<T>) -> Option<&T> {
+        self.slots.get(handle.index).and_then(Option::as_ref)
+    }
+
+    pub fn get_mut(&mut self, handle: &PoolHandle<T>) -> Option<&mut T> {
+        self.slots.get_mut(handle.index).and_then(Option::as_mut)
+    }
+
+    /// Release every live object for which `predicate` returns true.
+    pub fn release_where(&mut self, mut predicate: impl FnMut(&T) -> bool) {
+        for index in 0..self.slots.len() {
+            if self.slots[index].as_ref().is_some_and(&mut predicate) {
+                self.release_index(index);
+            }
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.release_where(|_| true);
+    }
+
+    fn release_index(&mut self, index: usize) {
+        if let Some(mut value) = self.slots.get_mut(index).and_then(Option::take) {
+            value.reset();
+            self.spares.push(value);
+            self.free_list.push(Reverse(index));
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().filter_map(Option::as_ref)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(Option::as_mut)
+    }
+
+    /// Number of live objects.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free_list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Released objects waiting in the pool for `acquire`.
+    pub fn spare_count(&self) -> usize {
+        self.spares.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Particle {
+        id: u32,
+        resets: u32,
+    }
+
+    impl Particle {
+        fn new(id: u32) -> Self {
+            Particle { id, resets: 0 }
+        }
+    }
+
+    impl Poolable for Particle {
+        fn reset(&mut self) {
+            self.id = 0;
+            self.resets += 1;
+        }
+    }
+
+    fn ids(pool: &ObjectPool<Particle>) -> Vec<u32> {
+        pool.iter().map(|p| p.id).collect()
+    }
+
+    #[test]
+    fn acquire_from_an_empty_pool_is_none() {
+        let mut pool: ObjectPool<Particle> = ObjectPool::with_capacity(4);
+        assert!(pool.acquire().is_none());
+    }
+
+    #[test]
+    fn insert_fills_slots_in_order() {
+        let mut pool = ObjectPool::with_capacity(4);
+        let a = pool.insert(Particle::new(1));
+        let b = pool.insert(Particle::new(2));
+        assert_eq!((a.index(), b.index()), (0, 1));
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.get(&b).unwrap().id, 2);
+    }
+
+    #[test]
+    fn release_resets_and_parks_the_object() {
+        let mut pool = ObjectPool::with_capacity(4);
+        let a = pool.insert(Particle::new(1));
+        pool.insert(Particle::new(2));
+
+        a.release(&mut pool);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.spare_count(), 1);
+        assert_eq!(ids(&pool), vec![2]);
+    }
+
+    #[test]
+    fn reacquire_reuses_the_released_object_and_slot() {
+        let mut pool = ObjectPool::with_capacity(4);
+        let a = pool.insert(Particle::new(1));
+        pool.insert(Particle::new(2));
+        a.release(&mut pool);
+
+        let reused = pool.acquire().unwrap();
+        assert_eq!(reused.index(), 0);
+        assert_eq!(pool.get(&reused), Some(&Particle { id: 0, resets: 1 }));
+        assert_eq!(pool.spare_count(), 0);
+
+        pool.get_mut(&reused).unwrap().id = 3;
+        assert_eq!(ids(&pool), vec![3, 2]);
+    }
+
+    #[test]
+    fn repeated_cycles_do_not_grow_the_pool() {
+        let mut pool = ObjectPool::with_capacity(2);
+        pool.insert(Particle::new(1));
+        for _ in 0..100 {
+            pool.release_all();
+            let handle = pool.acquire().unwrap();
+            assert_eq!(handle.index(), 0);
+        }
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.iter().next().unwrap().resets, 100);
+        assert!(pool.acquire().is_none());
+    }
+
+    #[test]
+    fn release_where_only_releases_matches() {
+        let mut pool = ObjectPool::with_capacity(4);
+        for id in 1..=4 {
+            pool.insert(Particle::new(id));
+        }
+        pool.release_where(|p| p.id % 2 == 0);
+        assert_eq!(ids(&pool), vec![1, 3]);
+        assert_eq!(pool.spare_count(), 2);
+
+        let newcomer = pool.insert(Particle::new(5));
+        assert_eq!(newcomer.index(), 1);
+        assert_eq!(pool.len(), 3);
+    }
+
+    #[test]
+    fn refilling_after_release_all_keeps_insertion_order() {
+        let mut pool = ObjectPool::with_capacity(3);
+        for id in 1..=3 {
+            pool.insert(Particle::new(id));
+        }
+        pool.release_all();
+        for id in 4..=6 {
+            pool.insert(Particle::new(id));
+        }
+        assert_eq!(ids(&pool), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn acquire_reuses_the_lowest_free_slot() {
+        let mut pool = ObjectPool::with_capacity(4);
+        for id in 1..=4 {
+            pool.insert(Particle::new(id));
+        }
+        pool.release_where(|p| p.id == 3);
+        pool.release_where(|p| p.id == 1);
+        assert_eq!(pool.acquire().unwrap().index(), 0);
+        assert_eq!(pool.acquire().unwrap().index(), 2);
+    }
+
+    #[test]
+    fn stale_handle_sees_nothing_after_release_all() {
+        let mut pool = ObjectPool::with_capacity(1);
+        let handle = pool.insert(Particle::new(1));
+        pool.release_all();
+        assert!(pool.get(&handle).is_none());
+        assert!(pool.is_empty());
+    }
+}
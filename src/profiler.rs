@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::ring_buffer::RingBuffer;
+
+const SAMPLES_PER_PHASE: usize = 120;
+
+/// Per-phase frame timings. `begin`/`end` pairs are no-ops while disabled.
+pub struct Profiler {
+    samples: HashMap<String, RingBuffer<f64>>,
+    started: HashMap<String, f64>,
+    enabled: bool,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            samples: HashMap::new(),
+            started: HashMap::new(),
+            enabled: false,
+        }
+    }
+
+    /// Enable or disable timing. Disabling drops collected samples.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.samples.clear();
+            self.started.clear();
+        }
+    }
+
+    pub fn begin(&mut self, name: &str) {
+        if self.enabled {
+            self.started.insert(name.to_string(), now());
+        }
+    }
+
+    /// Record the time since the matching `begin`; ignored if there was none.
+    pub fn end(&mut self, name: &str) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(start) = self.started.remove(name) {
+            self.samples
+                .entry(name.to_string())
+                .or_insert_with(|| RingBuffer::new(SAMPLES_PER_PHASE))
+                .push(now() - start);
+        }
+    }
+
+    /// Average duration in ms of each phase over its recent samples.
+    pub fn averages(&self) -> HashMap<String, f64> {
+        self.samples
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(name, samples)| (name.clone(), samples.iter().sum::<f64>() / samples.len() as f64))
+            .collect()
+    }
+}
+
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,263 @@
+use crate::aabb::AABB;
+
+/// Region quadtree over object bounds. Each node keeps the entries that don't fit wholly
+/// inside one of its quadrants, and splits once it holds more than `max_objects`.
+/// Entries outside the root bounds stay at the root, so nothing is ever dropped.
+pub struct Quadtree {
+    bounds: AABB,
+    max_objects: usize,
+    max_depth: u32,
+    depth: u32,
+    objects: Vec<(u32, AABB)>,
+    children: Option<Box<[Quadtree; 4]>>,
+}
+
+impl Quadtree {
+    pub fn new(bounds: AABB, max_objects: usize, max_depth: u32) -> Self {
+        Self::with_depth(bounds, max_objects, max_depth, 0)
+    }
+
+    fn with_depth(bounds: AABB, max_objects: usize, max_depth: u32, depth: u32) -> Self {
+        Self {
+            bounds,
+            max_objects,
+            max_depth,
+            depth,
+            objects: Vec::new(),
+            children: None,
+        }
+    }
+
+    pub fn insert(&mut self, id: u32, bounds: &AABB) {
+        if let Some(index) = self.child_index(bounds) {
+            if let Some(children) = self.children.as_mut() {
+                children[index].insert(id, bounds);
+                return;
+            }
+        }
+
+        self.objects.push((id, bounds.clone()));
+        if self.children.is_none() && self.objects.len() > self.max_objects && self.depth < self.max_depth {
+            self.split();
+        }
+    }
+
+    /// Ids of every entry whose bounds intersect `region`.
+    pub fn query(&self, region: &AABB) -> Vec<u32> {
+        let mut found = Vec::new();
+        self.query_into(region, &mut found);
+        found
+    }
+
+    fn query_into(&self, region: &AABB, found: &mut Vec<u32>) {
+        found.extend(
+            self.objects
+                .iter()
+                .filter(|(_, bounds)| bounds.intersects(region))
+                .map(|(id, _)| *id),
+        );
+        if let Some(children) = self.children.as_ref() {
+            for child in children.iter().filter(|child| child.bounds.intersects(region)) {
+                child.query_into(region, found);
+            }
+        }
+    }
+
+    /// Ids of every entry whose bounds contain the point.
+    pub fn query_point(&self, x: f64, y: f64) -> Vec<u32> {
+        self.query(&AABB::new(x, y, x, y))
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.children = None;
+    }
+
+    /// Number of entries in the whole tree.
+    pub fn len(&self) -> usize {
+        self.objects.len() + self.children.as_ref().map_or(0, |children| children.iter().map(Quadtree::len).sum())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn split(&mut self) {
+        let (min_x, min_y) = (self.bounds.min_x(), self.bounds.min_y());
+        let (max_x, max_y) = (self.bounds.max_x(), self.bounds.max_y());
+        let mid_x = min_x + self.bounds.width() / 2.0;
+        let mid_y = min_y + self.bounds.height() / 2.0;
+        let child = |bounds| Quadtree::with_depth(bounds, self.max_objects, self.max_depth, self.depth + 1);
+        self.children = Some(Box::new([
+            child(AABB::new(min_x, min_y, mid_x, mid_y)),
+            child(AABB::new(mid_x, min_y, max_x, mid_y)),
+            child(AABB::new(min_x, mid_y, mid_x, max_y)),
+            child(AABB::new(mid_x, mid_y, max_x, max_y)),
+        ]));
+
+        for (id, bounds) in std::mem::take(&mut self.objects) {
+            self.insert(id, &bounds);
+        }
+    }
+
+    /// Quadrant that wholly contains `bounds`, if any.
+    fn child_index(&self, bounds: &AABB) -> Option<usize> {
+        let mid_x = self.bounds.min_x() + self.bounds.width() / 2.0;
+        let mid_y = self.bounds.min_y() + self.bounds.height() / 2.0;
+        let inside = bounds.min_x() >= self.bounds.min_x()
+            && bounds.max_x() <= self.bounds.max_x()
+            && bounds.min_y() >= self.bounds.min_y()
+            && bounds.max_y() <= self.bounds.max_y();
+        if !inside {
+            return None;
+        }
+
+        let column = if bounds.max_x() < mid_x {
+            0
+        } else if bounds.min_x() >= mid_x {
+            1
+        } else {
+            return None;
+        };
+        let row = if bounds.max_y() < mid_y {
+            0
+        } else if bounds.min_y() >= mid_y {
+            1
+        } else {
+            return None;
+        };
+        Some(row * 2 + column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> Quadtree {
+        Quadtree::new(AABB::new(0.0, 0.0, 100.0, 100.0), 2, 4)
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> AABB {
+        AABB::new(x, y, x + size, y + size)
+    }
+
+    fn sorted(mut ids: Vec<u32>) -> Vec<u32> {
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Deterministic boxes spread over the world, some straddling quadrant lines.
+    fn scattered(count: u32) -> Vec<(u32, AABB)> {
+        let mut seed = 12_345u32;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            f64::from(seed >> 16) / 65_536.0
+        };
+        (0..count).map(|id| (id, square(next() * 95.0, next() * 95.0, 1.0 + next() * 4.0))).collect()
+    }
+
+    #[test]
+    fn new_tree_is_empty() {
+        let tree = world();
+        assert!(tree.is_empty());
+        assert!(tree.query(&AABB::new(0.0, 0.0, 100.0, 100.0)).is_empty());
+    }
+
+    #[test]
+    fn insert_counts_every_entry() {
+        let mut tree = world();
+        for (id, bounds) in scattered(50) {
+            tree.insert(id, &bounds);
+        }
+        assert_eq!(tree.len(), 50);
+    }
+
+    #[test]
+    fn query_finds_only_intersecting_entries() {
+        let mut tree = world();
+        tree.insert(1, &square(10.0, 10.0, 5.0));
+        tree.insert(2, &square(80.0, 80.0, 5.0));
+        tree.insert(3, &square(12.0, 60.0, 5.0));
+
+        assert_eq!(tree.query(&AABB::new(0.0, 0.0, 20.0, 20.0)), vec![1]);
+        assert_eq!(sorted(tree.query(&AABB::new(0.0, 0.0, 50.0, 100.0))), vec![1, 3]);
+        assert!(tree.query(&AABB::new(40.0, 40.0, 45.0, 45.0)).is_empty());
+    }
+
+    #[test]
+    fn query_after_splitting_matches_a_linear_scan() {
+        let entries = scattered(300);
+        let mut tree = world();
+        for (id, bounds) in &entries {
+            tree.insert(*id, bounds);
+        }
+
+        for region in [
+            AABB::new(0.0, 0.0, 100.0, 100.0),
+            AABB::new(25.0, 25.0, 75.0, 75.0),
+            AABB::new(49.0, 0.0, 51.0, 100.0),
+            AABB::new(90.0, 90.0, 95.0, 95.0),
+        ] {
+            let expected: Vec<u32> = entries.iter().filter(|(_, b)| b.intersects(&region)).map(|(id, _)| *id).collect();
+            assert_eq!(sorted(tree.query(&region)), expected);
+        }
+    }
+
+    #[test]
+    fn query_point_finds_containing_entries() {
+        let mut tree = world();
+        tree.insert(1, &square(10.0, 10.0, 10.0));
+        tree.insert(2, &square(15.0, 15.0, 10.0));
+        tree.insert(3, &square(70.0, 70.0, 10.0));
+
+        assert_eq!(sorted(tree.query_point(17.0, 17.0)), vec![1, 2]);
+        assert_eq!(tree.query_point(75.0, 75.0), vec![3]);
+        assert!(tree.query_point(50.0, 50.0).is_empty());
+    }
+
+    #[test]
+    fn query_point_includes_edges() {
+        let mut tree = world();
+        tree.insert(1, &square(10.0, 10.0, 10.0));
+        assert_eq!(tree.query_point(20.0, 10.0), vec![1]);
+    }
+
+    #[test]
+    fn entries_straddling_the_centre_are_still_found() {
+        let mut tree = world();
+        for (id, bounds) in scattered(20) {
+            tree.insert(id, &bounds);
+        }
+        tree.insert(99, &square(48.0, 48.0, 4.0));
+        assert!(tree.query_point(50.0, 50.0).contains(&99));
+    }
+
+    #[test]
+    fn entries_outside_the_root_are_kept() {
+        let mut tree = world();
+        tree.insert(1, &square(150.0, -30.0, 5.0));
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.query_point(152.0, -28.0), vec![1]);
+    }
+
+    #[test]
+    fn identical_entries_stop_splitting_at_max_depth() {
+        let mut tree = world();
+        for id in 0..100 {
+            tree.insert(id, &square(1.0, 1.0, 0.5));
+        }
+        assert_eq!(tree.query_point(1.25, 1.25).len(), 100);
+    }
+
+    #[test]
+    fn clear_removes_everything() {
+        let mut tree = world();
+        for (id, bounds) in scattered(30) {
+            tree.insert(id, &bounds);
+        }
+        tree.clear();
+        assert!(tree.is_empty());
+        assert!(tree.query(&AABB::new(0.0, 0.0, 100.0, 100.0)).is_empty());
+    }
+}
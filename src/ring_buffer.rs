@@ -0,0 +1,127 @@
+/// Fixed-capacity buffer that overwrites its oldest entry once full.
+pub struct RingBuffer<T> {
+    data: Vec<T>,
+    head: usize,
+    len: usize,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RingBuffer capacity must be non-zero");
+        Self {
+            data: Vec::with_capacity(capacity),
+            head: 0,
+            len: 0,
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.data.len() < self.capacity {
+            self.data.push(value);
+        } else {
+            self.data[self.head] = value;
+        }
+        self.head = (self.head + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+    }
+
+    /// Iterate from the oldest to the newest entry.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let start = (self.head + self.capacity - self.len) % self.capacity;
+        (0..self.len).map(move |i| &self.data[(start + i) % self.capacity])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(buffer: &RingBuffer<u32>) -> Vec<u32> {
+        buffer.iter().copied().collect()
+    }
+
+    #[test]
+    fn partially_filled_buffer_keeps_insertion_order() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.push(1);
+        buffer.push(2);
+        assert_eq!(contents(&buffer), vec![1, 2]);
+        assert_eq!(buffer.len(), 2);
+        assert!(!buffer.is_full());
+    }
+
+    #[test]
+    fn full_buffer_overwrites_oldest_entry() {
+        let mut buffer = RingBuffer::new(3);
+        for value in 1..=5 {
+            buffer.push(value);
+        }
+        assert_eq!(contents(&buffer), vec![3, 4, 5]);
+        assert_eq!(buffer.len(), 3);
+        assert!(buffer.is_full());
+    }
+
+    #[test]
+    fn wraps_around_many_times() {
+        let mut buffer = RingBuffer::new(4);
+        for value in 0..103 {
+            buffer.push(value);
+        }
+        assert_eq!(contents(&buffer), vec![99, 100, 101, 102]);
+    }
+
+    #[test]
+    fn capacity_one_holds_latest_value() {
+        let mut buffer = RingBuffer::new(1);
+        buffer.push(10);
+        buffer.push(20);
+        assert_eq!(contents(&buffer), vec![20]);
+        assert!(buffer.is_full());
+    }
+
+    #[test]
+    fn clear_after_wrap_starts_over() {
+        let mut buffer = RingBuffer::new(3);
+        for value in 0..7 {
+            buffer.push(value);
+        }
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(contents(&buffer), Vec::<u32>::new());
+
+        buffer.push(8);
+        buffer.push(9);
+        assert_eq!(contents(&buffer), vec![8, 9]);
+        assert_eq!(buffer.capacity(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_capacity_panics() {
+        RingBuffer::<u32>::new(0);
+    }
+}
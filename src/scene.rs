@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+
+use crate::keyframe::Keyframe;
+
+/// Schema version written into the root of every exported scene.
+pub const CURRENT_SCENE_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct SceneHeader {
+    schema_version: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SceneObjectV1 {
+    pub object_id: u32,
+    pub size: f64,
+    pub color: String,
+    pub chunk_size: f32,
+    pub keyframes: Vec<Keyframe>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SceneV1 {
+    pub schema_version: u32,
+    pub objects: Vec<SceneObjectV1>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneObjectV2 {
+    pub size: f64,
+    pub color: String,
+    pub chunk_size: f32,
+    pub keyframes: Vec<Keyframe>,
+}
+
+/// In-memory scene that every historical format is migrated to before import.
+/// Object ids are dropped because imports always allocate fresh ones; the rest of
+/// the layout is identical to v1, so exports keep writing v1.
+#[derive(Debug, Clone)]
+pub struct SceneV2 {
+    pub objects: Vec<SceneObjectV2>,
+}
+
+pub fn migrate_v1_to_v2(v1: SceneV1) -> SceneV2 {
+    SceneV2 {
+        objects: v1
+            .objects
+            .into_iter()
+            .map(|obj| SceneObjectV2 {
+                size: obj.size,
+                color: obj.color,
+                chunk_size: obj.chunk_size,
+                keyframes: obj.keyframes,
+            })
+            .collect(),
+    }
+}
+
+/// Chunk size used for declarative objects that don't give one.
+const DEFAULT_SPEC_CHUNK_SIZE: f32 = 10_000.0;
+pub const CURRENT_SPEC_VERSION: u32 = 1;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct KeyframeSpec {
+    pub time: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SceneObjectSpec {
+    pub size: f64,
+    pub color: String,
+    pub keyframes: Vec<KeyframeSpec>,
+    pub chunk_size: Option<f64>,
+    /// Only `"loop"`, the engine's sole playback mode, is accepted.
+    pub loop_mode: Option<String>,
+}
+
+/// Hand-written scene description, as opposed to the full dump made by `export_scene`.
+/// Keyframes may be given in any order. Unknown keys, such as a `layer`, are ignored;
+/// objects are drawn in creation order.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SceneSpec {
+    pub version: u32,
+    pub objects: Vec<SceneObjectSpec>,
+}
+
+impl SceneSpec {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let spec: SceneSpec = serde_json::from_str(json).map_err(|e| format!("Invalid scene spec: {}", e))?;
+        if spec.version != CURRENT_SPEC_VERSION {
+            return Err(format!("Unsupported scene spec version {}", spec.version));
+        }
+        Ok(spec)
+    }
+
+    pub fn into_scene(self) -> Result<SceneV2, String> {
+        let objects = self
+            .objects
+            .into_iter()
+            .enumerate()
+            .map(|(i, obj)| {
+                if obj.keyframes.len() < 2 {
+                    return Err(format!("Object {} needs at least 2 keyframes, got {}", i, obj.keyframes.len()));
+                }
+                if let Some(mode) = obj.loop_mode.as_deref().filter(|mode| *mode != "loop") {
+                    return Err(format!("Object {}: unsupported loop mode '{}'", i, mode));
+                }
+                let chunk_size = obj.chunk_size.map_or(DEFAULT_SPEC_CHUNK_SIZE, |size| size as f32);
+                if chunk_size <= 0.0 {
+                    return Err(format!("Object {}: chunk size must be positive", i));
+                }
+                let mut keyframes: Vec<Keyframe> = obj
+                    .keyframes
+                    .iter()
+                    .map(|k| Keyframe::new(k.time as f32, k.x as f32, k.y as f32))
+                    .collect();
+                keyframes.sort_by(|a, b| a.time().total_cmp(&b.time()));
+                let span = keyframes[keyframes.len() - 1].time() - keyframes[0].time();
+                if span.is_nan() || span <= 0.0 {
+                    return Err(format!("Object {}: keyframes must span a positive duration", i));
+                }
+                Ok(SceneObjectV2 {
+                    size: obj.size,
+                    color: obj.color,
+                    chunk_size,
+                    keyframes,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(SceneV2 { objects })
+    }
+}
+
+/// One variant per historical scene format, selected by the root `schema_version`.
+pub enum SceneDeserialiser {
+    V1(SceneV1),
+}
+
+impl SceneDeserialiser {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let header: SceneHeader = serde_json::from_str(json)
+            .map_err(|e| format!("Invalid scene header: {}", e))?;
+
+        match header.schema_version {
+            1 => serde_json::from_str(json)
+                .map(SceneDeserialiser::V1)
+                .map_err(|e| format!("Invalid v1 scene: {}", e)),
+            version => Err(format!("Unsupported scene schema version {}", version)),
+        }
+    }
+
+    pub fn into_latest(self) -> SceneV2 {
+        match self {
+            SceneDeserialiser::V1(v1) => migrate_v1_to_v2(v1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::easing::Easing;
+
+    fn v1_scene() -> SceneV1 {
+        SceneV1 {
+            schema_version: 1,
+            objects: vec![
+                SceneObjectV1 {
+                    object_id: 7,
+                    size: 12.5,
+                    color: "#ff8800".to_string(),
+                    chunk_size: 500.0,
+                    keyframes: vec![
+                        Keyframe::new(0.0, 1.25, -3.5),
+                        Keyframe::new_with_easing(250.0, 40.0, 80.75, Easing::EaseInOut),
+                        Keyframe::new(900.0, 0.0, 0.0),
+                    ],
+                },
+                SceneObjectV1 {
+                    object_id: 8,
+                    size: 30.0,
+                    color: "blue".to_string(),
+                    chunk_size: 10_000.0,
+                    keyframes: vec![Keyframe::new(0.0, 5.0, 5.0), Keyframe::new(100.0, 6.0, 7.0)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn v1_export_round_trips_through_deserialiser() {
+        let v1 = v1_scene();
+        let json = serde_json::to_string(&v1).unwrap();
+
+        let scene = SceneDeserialiser::from_json(&json).unwrap().into_latest();
+
+        assert_eq!(scene.objects.len(), v1.objects.len());
+        for (migrated, original) in scene.objects.iter().zip(&v1.objects) {
+            assert_eq!(migrated.size, original.size);
+            assert_eq!(migrated.color, original.color);
+            assert_eq!(migrated.chunk_size, original.chunk_size);
+            assert_eq!(migrated.keyframes, original.keyframes);
+        }
+    }
+
+    #[test]
+    fn unsupported_schema_version_is_rejected() {
+        let json = r#"{ "schema_version": 99, "objects": [] }"#;
+        assert!(SceneDeserialiser::from_json(json).is_err());
+    }
+
+    #[test]
+    fn missing_schema_version_is_rejected() {
+        assert!(SceneDeserialiser::from_json(r#"{ "objects": [] }"#).is_err());
+    }
+
+    fn spec(keyframes: &str) -> Result<SceneV2, String> {
+        let json = format!(r#"{{ "version": 1, "objects": [{{ "size": 10, "color": "red", "keyframes": {} }}] }}"#, keyframes);
+        SceneSpec::from_json(&json)?.into_scene()
+    }
+
+    #[test]
+    fn spec_with_two_keyframes_is_accepted_in_any_order() {
+        let scene = spec(r#"[{ "time": 500, "x": 5, "y": 5 }, { "time": 0, "x": 0, "y": 0 }]"#).unwrap();
+        let times: Vec<f32> = scene.objects[0].keyframes.iter().map(Keyframe::time).collect();
+        assert_eq!(times, vec![0.0, 500.0]);
+    }
+
+    #[test]
+    fn spec_with_fewer_than_two_keyframes_is_rejected() {
+        assert!(spec("[]").is_err());
+        assert!(spec(r#"[{ "time": 0, "x": 0, "y": 0 }]"#).is_err());
+    }
+
+    #[test]
+    fn spec_with_keyframes_at_one_time_is_rejected() {
+        let err = spec(r#"[{ "time": 100, "x": 0, "y": 0 }, { "time": 100, "x": 5, "y": 5 }]"#).unwrap_err();
+        assert!(err.contains("positive duration"), "{}", err);
+    }
+}
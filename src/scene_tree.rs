@@ -0,0 +1,94 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{Document, Element, Event, Window};
+
+const REFRESH_INTERVAL_MS: i32 = 1_000;
+
+type RefreshCallback = Closure<dyn FnMut()>;
+type ClickListener = Closure<dyn FnMut(Event)>;
+
+/// One object's line in the scene tree.
+pub struct SceneTreeRow {
+    pub object_id: u32,
+    pub color: String,
+    pub current_time: f64,
+    pub paused: bool,
+    pub selected: bool,
+}
+
+/// Replace the contents of `element` with a `<ul>` holding one `<li data-object-id>` per row.
+pub fn render_rows(document: &Document, element: &Element, rows: &[SceneTreeRow]) -> Result<(), JsValue> {
+    let list = document.create_element("ul")?;
+    for row in rows {
+        let item = document.create_element("li")?;
+        item.set_attribute("data-object-id", &row.object_id.to_string())?;
+        if row.selected {
+            item.set_attribute("class", "selected")?;
+        }
+        item.set_text_content(Some(&format!(
+            "#{} {} t={:.0}ms{}",
+            row.object_id,
+            row.color,
+            row.current_time,
+            if row.paused { " (paused)" } else { "" }
+        )));
+        list.append_child(&item)?;
+    }
+    element.set_inner_html("");
+    element.append_child(&list)?;
+    Ok(())
+}
+
+/// A DOM element kept in sync with the scene: `refresh` runs every second, and a click on
+/// an entry calls `on_select` with its object id. Dropping the panel stops both.
+pub struct ScenePanel {
+    window: Window,
+    element: Element,
+    interval_id: i32,
+    _refresh: RefreshCallback,
+    on_click: ClickListener,
+}
+
+impl ScenePanel {
+    pub fn new(
+        window: Window,
+        element: Element,
+        refresh: impl FnMut() + 'static,
+        mut on_select: impl FnMut(u32) + 'static,
+    ) -> Result<Self, JsValue> {
+        let refresh = Closure::wrap(Box::new(refresh) as Box<dyn FnMut()>);
+        let interval_id = window.set_interval_with_callback_and_timeout_and_arguments_0(
+            refresh.as_ref().unchecked_ref(),
+            REFRESH_INTERVAL_MS,
+        )?;
+
+        let on_click = Closure::wrap(Box::new(move |event: Event| {
+            let object_id = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest("li[data-object-id]").ok().flatten())
+                .and_then(|item| item.get_attribute("data-object-id"))
+                .and_then(|id| id.parse().ok());
+            if let Some(object_id) = object_id {
+                on_select(object_id);
+            }
+        }) as Box<dyn FnMut(Event)>);
+        element.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+
+        Ok(Self {
+            window,
+            element,
+            interval_id,
+            _refresh: refresh,
+            on_click,
+        })
+    }
+}
+
+impl Drop for ScenePanel {
+    fn drop(&mut self) {
+        self.window.clear_interval_with_handle(self.interval_id);
+        let _ = self
+            .element
+            .remove_event_listener_with_callback("click", self.on_click.as_ref().unchecked_ref());
+    }
+}
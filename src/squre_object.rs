@@ -2,30 +2,110 @@ use wasm_bindgen::JsValue;
 use web_sys::CanvasRenderingContext2d;
 use std::sync::Arc;
 
+use crate::aabb::AABB;
+use crate::fill_style::FillStyle;
 use crate::keyframe::{KeyframeChunk};
 use crate::keyframe_store::KeyframeStore;
 use crate::keyframe_database::KeyframeDatabase;
+use crate::math::Vector2;
+use crate::pool::Poolable;
+
+/// Frames between sweeps of the chunk cache for chunks far from the playhead.
+const DEFAULT_EVICTION_INTERVAL: u32 = 60;
+pub const DEFAULT_RENDER_ORDER: u32 = u32::MAX;
+pub const DEFAULT_HIGHLIGHT_COLOR: &str = "#FFD700";
+const HIGHLIGHT_MARGIN: f64 = 4.0;
+const HIGHLIGHT_ALPHA: f64 = 0.35;
+const HIGHLIGHT_BLUR: f64 = 12.0;
+
+/// `time` wrapped into a track of `total_duration` ms. A track with no span, e.g. a single
+/// keyframe, holds at 0 instead of turning the time into NaN.
+fn wrap_time(time: f64, total_duration: f64) -> f64 {
+    if total_duration > 0.0 {
+        time.rem_euclid(total_duration)
+    } else {
+        0.0
+    }
+}
+
+/// Motion that replaces keyframe control until `remaining_ms` runs out; infinite while the
+/// object has physics enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicsState {
+    /// Pixels per ms.
+    pub velocity_x: f64,
+    pub velocity_y: f64,
+    pub remaining_ms: f64,
+    /// Fraction of the velocity lost per ms.
+    pub friction: f64,
+}
+
+/// Per-object settings carried over by `Rust2DEngine::clone_object`; everything except
+/// identity, keyframes and playback state.
+#[derive(Clone)]
+pub struct ObjectSettings {
+    width: f64,
+    height: f64,
+    size_store: Option<KeyframeStore>,
+    eviction_interval: u32,
+    eviction_window: f64,
+    hit_padding: f64,
+    hit_padding_percentage: f64,
+    physics_enabled: bool,
+    friction: Option<f64>,
+    gradient: Option<FillStyle>,
+}
 
 pub struct SquareObject {
     object_id: u32,
-    size: f64,
+    width: f64,
+    height: f64,
     color: String,
     current_time: f64,
     total_duration: f64,
     cached_x: f64,
     cached_y: f64,
     keyframe_store: KeyframeStore,
+    /// Optional track animating the dimensions, with keyframe x as width and y as height.
+    size_store: Option<KeyframeStore>,
+    eviction_interval: u32,
+    eviction_window: f64,
+    frames_since_eviction: u32,
+    hit_padding: f64,
+    hit_padding_percentage: f64,
+    selected: bool,
+    highlight_color: String,
+    position_override: bool,
+    /// Displacement from the keyframed path built up by spring constraints, and its rate of
+    /// change in pixels per ms.
+    constraint_offset: (f64, f64),
+    constraint_velocity: (f64, f64),
+    physics: Option<PhysicsState>,
+    /// Keyframes are ignored and the square moves only under forces and gravity.
+    physics_enabled: bool,
+    /// Frozen in place: `update` leaves time, position and physics untouched.
+    paused: bool,
+    /// Lower orders are drawn first; `DEFAULT_RENDER_ORDER` keeps insertion order.
+    render_order: u32,
+    /// When the engine spawned this object, relative to the others; breaks render order ties.
+    spawn_seq: u64,
+    /// CSS filter wrapped around this square's draw, on top of the engine's render filter.
+    filter: Option<String>,
+    /// Drawn instead of the flat `color` when set.
+    gradient: Option<FillStyle>,
+    /// Unique name registered with the engine, if one was given.
+    name: Option<String>,
 }
 
 impl SquareObject {
-    pub async fn new(
+    /// Persist an object's chunks and build the store that streams them back, with the
+    /// opening chunks already cached.
+    pub async fn create_store(
         object_id: u32,
-        size: f64, 
-        color: &str,
         chunks: Vec<KeyframeChunk>,
         chunk_size: f32,
         keyframe_db: Arc<KeyframeDatabase>,
-    ) -> SquareObject {
+    ) -> KeyframeStore {
 
         let total_duration = chunks
             .iter()
@@ -36,51 +116,415 @@ impl SquareObject {
             .save_chunks(chunks)
             .await;
 
-        let keyframe_store = KeyframeStore::new(
+        let store = KeyframeStore::new(
             object_id.to_string(), 
             chunk_size,
             total_duration.into(),
             keyframe_db.into(),
         );
+        // A cold cache would leave the object at (0, 0) until its first fetch lands.
+        if let Err(e) = store.warm_up().await {
+            web_sys::console::warn_1(&format!("Failed to warm up keyframe cache for {}: {}", object_id, e).into());
+        }
+        store
+    }
+
+    /// Build an object around an existing store, e.g. a clone of a shared track's store,
+    /// starting `time_offset` ms into the animation.
+    pub fn with_store(
+        object_id: u32,
+        size: f64,
+        color: &str,
+        keyframe_store: KeyframeStore,
+        time_offset: f64,
+    ) -> SquareObject {
+        let total_duration = keyframe_store.total_duration();
         SquareObject {
             object_id,
-            size,
+            width: size,
+            height: size,
             color: color.to_string(),
-            current_time: 0.0,
-            total_duration: total_duration.into(),
+            current_time: wrap_time(time_offset, total_duration),
+            total_duration,
             cached_x: 0.0,
             cached_y: 0.0,
-            keyframe_store: keyframe_store,
+            eviction_interval: DEFAULT_EVICTION_INTERVAL,
+            eviction_window: keyframe_store.chunk_size().into(),
+            frames_since_eviction: 0,
+            hit_padding: 0.0,
+            hit_padding_percentage: 0.0,
+            selected: false,
+            highlight_color: DEFAULT_HIGHLIGHT_COLOR.to_string(),
+            position_override: false,
+            constraint_offset: (0.0, 0.0),
+            constraint_velocity: (0.0, 0.0),
+            physics: None,
+            physics_enabled: false,
+            paused: false,
+            render_order: DEFAULT_RENDER_ORDER,
+            spawn_seq: 0,
+            filter: None,
+            gradient: None,
+            name: None,
+            keyframe_store,
+            size_store: None,
         }
     }
 
+    /// Turn a pooled object into a new one, reusing its allocations where possible.
+    pub fn reinit(
+        &mut self,
+        object_id: u32,
+        size: f64,
+        color: &str,
+        keyframe_store: KeyframeStore,
+        time_offset: f64,
+    ) {
+        self.object_id = object_id;
+        self.width = size;
+        self.height = size;
+        self.color.clear();
+        self.color.push_str(color);
+        self.total_duration = keyframe_store.total_duration();
+        self.current_time = wrap_time(time_offset, self.total_duration);
+        self.eviction_window = keyframe_store.chunk_size().into();
+        self.keyframe_store = keyframe_store;
+    }
+
+    pub fn settings(&self) -> ObjectSettings {
+        ObjectSettings {
+            width: self.width,
+            height: self.height,
+            size_store: self.size_store.clone(),
+            eviction_interval: self.eviction_interval,
+            eviction_window: self.eviction_window,
+            hit_padding: self.hit_padding,
+            hit_padding_percentage: self.hit_padding_percentage,
+            physics_enabled: self.physics_enabled,
+            friction: self.physics.map(|physics| physics.friction),
+            gradient: self.gradient.clone(),
+        }
+    }
+
+    /// Take on another object's settings. Physics starts from rest rather than copying the
+    /// source's velocity.
+    pub fn apply_settings(&mut self, settings: ObjectSettings) {
+        self.width = settings.width;
+        self.height = settings.height;
+        self.size_store = settings.size_store;
+        self.eviction_interval = settings.eviction_interval;
+        self.eviction_window = settings.eviction_window;
+        self.hit_padding = settings.hit_padding;
+        self.hit_padding_percentage = settings.hit_padding_percentage;
+        self.gradient = settings.gradient;
+        if let (true, Some(friction)) = (settings.physics_enabled, settings.friction) {
+            self.enable_physics(friction);
+        }
+        self.refresh_position();
+    }
+
     /// Unique index for this square
     pub fn object_id(&self) -> u32 {
         self.object_id
     }
 
-    pub async fn fetch_data(&mut self) -> Result<(), JsValue> {
-        let _ = self.keyframe_store.fetch_data(self.current_time).await;
-        Ok(())
+    pub fn current_time(&self) -> f64 {
+        self.current_time
     }
 
     /// Advance animation by delta_time seconds
     pub fn update(&mut self, delta_time: f64) -> Result<(), JsValue> {
-        self.current_time = (self.current_time + delta_time) % self.total_duration;
-        if let Some(pos) = self.keyframe_store.get_interpolated_position(self.current_time) {
-            self.cached_x = pos.x;
-            self.cached_y = pos.y;
+        if self.paused {
+            return Ok(());
         }
+        self.current_time = wrap_time(self.current_time + delta_time, self.total_duration);
+        self.integrate_constraint_offset(delta_time);
+        self.step_physics(delta_time);
+        self.refresh_position();
+        self.maybe_evict_chunks();
         Ok(())
     }
 
+    /// Sweep the chunk cache every `interval` frames, keeping chunks within `keep_window` ms
+    /// of the playhead. An interval of 0 disables the sweep.
+    pub fn set_chunk_eviction(&mut self, interval: u32, keep_window: f64) {
+        self.eviction_interval = interval;
+        self.eviction_window = keep_window;
+        self.frames_since_eviction = 0;
+    }
+
+    fn maybe_evict_chunks(&mut self) {
+        // Instances of a shared track play it at different phases; one instance must not
+        // evict the chunks another is using.
+        if self.eviction_interval == 0 || self.keyframe_store.object_id() != self.object_id.to_string() {
+            return;
+        }
+        self.frames_since_eviction += 1;
+        if self.frames_since_eviction >= self.eviction_interval {
+            self.frames_since_eviction = 0;
+            self.keyframe_store.evict_distant_chunks(self.current_time, self.eviction_window);
+        }
+    }
+
+    /// Jump to `time` (wrapped into the animation length) and refresh the cached position
+    /// if the chunk for that time is loaded.
+    pub fn seek(&mut self, time: f64) {
+        self.current_time = wrap_time(time, self.total_duration);
+        self.refresh_position();
+    }
+
+    /// Take the position from the keyframes, unless it is being driven manually, and the
+    /// dimensions from the size track if there is one.
+    fn refresh_position(&mut self) {
+        let size = self.size_store.as_ref().and_then(|store| store.get_interpolated_position(self.current_time));
+        if let Some(size) = size {
+            self.width = size.x;
+            self.height = size.y;
+        }
+        if self.position_override || self.physics.is_some() {
+            return;
+        }
+        if let Some(pos) = self.keyframe_store.get_interpolated_position(self.current_time) {
+            self.cached_x = pos.x + self.constraint_offset.0;
+            self.cached_y = pos.y + self.constraint_offset.1;
+        }
+    }
+
+    /// Push the square with velocity `(vx, vy)` pixels per ms for `duration_ms`, decaying by
+    /// `friction` per ms. Keyframes take over again once the time is up.
+    /// With physics enabled the push is added to the current velocity and never expires.
+    pub fn apply_force(&mut self, vx: f64, vy: f64, duration_ms: f64, friction: f64) {
+        if self.physics_enabled {
+            if let Some(physics) = self.physics.as_mut() {
+                physics.velocity_x += vx;
+                physics.velocity_y += vy;
+                return;
+            }
+        }
+        self.physics = Some(PhysicsState {
+            velocity_x: vx,
+            velocity_y: vy,
+            remaining_ms: duration_ms,
+            friction,
+        });
+    }
+
+    /// Hand the square over to physics for good, keeping any velocity it already has.
+    pub fn enable_physics(&mut self, friction: f64) {
+        self.physics_enabled = true;
+        let physics = self.physics.get_or_insert(PhysicsState {
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            remaining_ms: 0.0,
+            friction,
+        });
+        physics.remaining_ms = f64::INFINITY;
+    }
+
+    /// Stop all motion and return the square to its keyframed path.
+    pub fn disable_physics(&mut self) {
+        self.physics_enabled = false;
+        self.physics = None;
+        self.refresh_position();
+    }
+
+    /// Accelerate by `(gx, gy)` pixels per ms² over `delta_time`; only with physics enabled.
+    pub fn apply_gravity(&mut self, gx: f64, gy: f64, delta_time: f64) {
+        if !self.physics_enabled {
+            return;
+        }
+        if let Some(physics) = self.physics.as_mut() {
+            physics.velocity_x += gx * delta_time;
+            physics.velocity_y += gy * delta_time;
+        }
+    }
+
+    /// Keep a physics-enabled square inside `area`, reflecting its velocity off any edge it
+    /// crossed and scaling it by `bounce` (0 stops dead, 1 is perfectly elastic).
+    pub fn bounce_within(&mut self, area: &AABB, bounce: f64) {
+        if !self.physics_enabled {
+            return;
+        }
+        let Some(physics) = self.physics.as_mut() else {
+            return;
+        };
+        let max_x = (area.max_x() - self.width).max(area.min_x());
+        let max_y = (area.max_y() - self.height).max(area.min_y());
+        if self.cached_x < area.min_x() || self.cached_x > max_x {
+            self.cached_x = self.cached_x.clamp(area.min_x(), max_x);
+            physics.velocity_x = -physics.velocity_x * bounce;
+        }
+        if self.cached_y < area.min_y() || self.cached_y > max_y {
+            self.cached_y = self.cached_y.clamp(area.min_y(), max_y);
+            physics.velocity_y = -physics.velocity_y * bounce;
+        }
+    }
+
+    pub fn set_physics_friction(&mut self, friction: f64) {
+        if let Some(physics) = self.physics.as_mut() {
+            physics.friction = friction;
+        }
+    }
+
+    fn step_physics(&mut self, delta_time: f64) {
+        let Some(physics) = self.physics.as_mut() else {
+            return;
+        };
+        let step = delta_time.min(physics.remaining_ms);
+        self.cached_x += physics.velocity_x * step;
+        self.cached_y += physics.velocity_y * step;
+        let decay = (1.0 - physics.friction).max(0.0).powf(step);
+        physics.velocity_x *= decay;
+        physics.velocity_y *= decay;
+        physics.remaining_ms -= delta_time;
+        if physics.remaining_ms <= 0.0 {
+            self.physics = None;
+        }
+    }
+
+    /// Advance the spring displacement, shifting the cached position with it so the change
+    /// also shows while the keyframe chunk isn't loaded.
+    fn integrate_constraint_offset(&mut self, delta_time: f64) {
+        let (vx, vy) = self.constraint_velocity;
+        if vx == 0.0 && vy == 0.0 {
+            return;
+        }
+        self.constraint_offset.0 += vx * delta_time;
+        self.constraint_offset.1 += vy * delta_time;
+        if !self.position_override {
+            self.cached_x += vx * delta_time;
+            self.cached_y += vy * delta_time;
+        }
+    }
+
+    /// Shift the square for this frame only; the next keyframe refresh replaces it.
+    pub fn nudge(&mut self, dx: f64, dy: f64) {
+        self.cached_x += dx;
+        self.cached_y += dy;
+    }
+
+    /// Change the constraint velocity by `(ax, ay)` pixels per ms² over `delta_time` ms.
+    pub fn accelerate(&mut self, ax: f64, ay: f64, delta_time: f64) {
+        self.constraint_velocity.0 += ax * delta_time;
+        self.constraint_velocity.1 += ay * delta_time;
+    }
+
+    /// Zero the constraint velocity, leaving the square where the springs put it.
+    pub fn stop_constraint_motion(&mut self) {
+        self.constraint_velocity = (0.0, 0.0);
+    }
+
+    pub fn constraint_velocity(&self) -> Vector2 {
+        Vector2::new(self.constraint_velocity.0, self.constraint_velocity.1)
+    }
+
+    /// Place the square manually. Keyframes stop moving it until `clear_position_override`,
+    /// though its animation time keeps advancing so it resumes in phase.
+    pub fn set_position(&mut self, x: f64, y: f64) {
+        self.cached_x = x;
+        self.cached_y = y;
+        self.position_override = true;
+    }
+
+    pub fn clear_position_override(&mut self) {
+        self.position_override = false;
+        self.refresh_position();
+    }
+
+    /// Instantaneous velocity at animation `time`, in pixels per ms. Zero while the
+    /// chunk for that time isn't loaded.
+    pub fn get_velocity_at(&self, time: f64) -> Vector2 {
+        self.keyframe_store
+            .get_velocity_at(wrap_time(time, self.total_duration))
+            .unwrap_or_else(|| Vector2::new(0.0, 0.0))
+    }
+
     /// Render the square at interpolated position, with fixed size and color
     pub fn render(&self, context: &CanvasRenderingContext2d) -> Result<(), JsValue>{
-        context.set_fill_style(&JsValue::from_str(&self.color));
-        context.fill_rect(self.cached_x, self.cached_y, self.size, self.size);
+        if let Some(filter) = &self.filter {
+            context.save();
+            // `filter` replaces the current value, so keep the scene-wide one in front.
+            let inherited = context.filter();
+            if inherited == "none" {
+                context.set_filter(filter);
+            } else {
+                context.set_filter(&format!("{} {}", inherited, filter));
+            }
+        }
+        if self.selected {
+            self.render_highlight(context);
+        }
+        match &self.gradient {
+            Some(gradient) => {
+                context.set_fill_style_canvas_gradient(&gradient.create(context, self.cached_x, self.cached_y)?);
+            }
+            None => context.set_fill_style(&JsValue::from_str(&self.color)),
+        }
+        context.fill_rect(self.cached_x, self.cached_y, self.width, self.height);
+        if self.filter.is_some() {
+            context.restore();
+        }
         Ok(())
     }
 
+    /// Translucent, blurred halo slightly larger than the square, drawn beneath it.
+    fn render_highlight(&self, context: &CanvasRenderingContext2d) {
+        context.save();
+        context.set_global_alpha(HIGHLIGHT_ALPHA);
+        context.set_shadow_blur(HIGHLIGHT_BLUR);
+        context.set_shadow_color(&self.highlight_color);
+        context.set_fill_style_str(&self.highlight_color);
+        context.fill_rect(
+            self.cached_x - HIGHLIGHT_MARGIN,
+            self.cached_y - HIGHLIGHT_MARGIN,
+            self.width + HIGHLIGHT_MARGIN * 2.0,
+            self.height + HIGHLIGHT_MARGIN * 2.0,
+        );
+        context.restore();
+    }
+
+    pub fn render_order(&self) -> u32 {
+        self.render_order
+    }
+
+    pub fn set_render_order(&mut self, order: u32) {
+        self.render_order = order;
+    }
+
+    pub fn spawn_seq(&self) -> u64 {
+        self.spawn_seq
+    }
+
+    pub fn set_spawn_seq(&mut self, seq: u64) {
+        self.spawn_seq = seq;
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn set_selected(&mut self, selected: bool) {
+        self.selected = selected;
+    }
+
+    /// `"none"` removes the filter.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = (filter != "none").then(|| filter.to_string());
+    }
+
+    pub fn set_highlight_color(&mut self, color: &str) {
+        self.highlight_color.clear();
+        self.highlight_color.push_str(color);
+    }
+
     // pub fn reset(&mut self) {
     //     self.current_time = 0.0;
     // }
@@ -93,7 +537,143 @@ impl SquareObject {
         self.cached_y
     }
 
+    /// Side length of a square; the width of a rectangle.
     pub fn get_size(&self) -> f64 {
-        self.size
+        self.width
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Turn the square into a `width` x `height` rectangle. A size track, if any,
+    /// overrides this on the next update.
+    pub fn set_dimensions(&mut self, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn size_store(&self) -> Option<&KeyframeStore> {
+        self.size_store.as_ref()
+    }
+
+    pub fn set_size_store(&mut self, size_store: Option<KeyframeStore>) {
+        self.size_store = size_store;
+        self.refresh_position();
+    }
+
+    /// Area covered when drawn, without hit padding.
+    pub fn bounds(&self) -> AABB {
+        AABB::new(self.cached_x, self.cached_y, self.cached_x + self.width, self.cached_y + self.height)
+    }
+
+    /// Extra margin, in pixels, added on every side of the square for hit testing.
+    pub fn set_hit_padding(&mut self, padding: f64) {
+        self.hit_padding = padding;
+    }
+
+    /// Hit-test margin as a percentage of the square's shorter side; the larger of this and
+    /// the fixed `hit_padding` is used.
+    pub fn set_hit_padding_percentage(&mut self, percentage: f64) {
+        self.hit_padding_percentage = percentage;
+    }
+
+    pub fn hit_padding(&self) -> f64 {
+        self.hit_padding.max(self.width.min(self.height) * self.hit_padding_percentage / 100.0)
+    }
+
+    pub fn center(&self) -> Vector2 {
+        Vector2::new(self.cached_x + self.width / 2.0, self.cached_y + self.height / 2.0)
+    }
+
+    /// Bounds used for hit testing: the drawn square grown by `hit_padding` on each side.
+    pub fn hit_bounds(&self) -> AABB {
+        let padding = self.hit_padding();
+        AABB::new(
+            self.cached_x - padding,
+            self.cached_y - padding,
+            self.cached_x + self.width + padding,
+            self.cached_y + self.height + padding,
+        )
+    }
+
+    pub fn color(&self) -> &str {
+        &self.color
+    }
+
+    /// Fill with `gradient` instead of the flat colour, or go back to the colour with `None`.
+    pub fn set_gradient(&mut self, gradient: Option<FillStyle>) {
+        self.gradient = gradient;
+    }
+
+    pub fn set_color(&mut self, color: &str) {
+        self.color.clear();
+        self.color.push_str(color);
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn keyframe_store(&self) -> &KeyframeStore {
+        &self.keyframe_store
+    }
+
+    /// Swap in a store whose track was rewritten, keeping playback within the new duration.
+    pub fn set_keyframe_store(&mut self, keyframe_store: KeyframeStore) {
+        self.total_duration = keyframe_store.total_duration();
+        self.current_time = wrap_time(self.current_time, self.total_duration);
+        self.keyframe_store = keyframe_store;
+    }
+}
+
+impl Poolable for SquareObject {
+    fn reset(&mut self) {
+        self.current_time = 0.0;
+        self.cached_x = 0.0;
+        self.cached_y = 0.0;
+        self.eviction_interval = DEFAULT_EVICTION_INTERVAL;
+        self.frames_since_eviction = 0;
+        self.hit_padding = 0.0;
+        self.hit_padding_percentage = 0.0;
+        self.selected = false;
+        self.position_override = false;
+        self.constraint_offset = (0.0, 0.0);
+        self.constraint_velocity = (0.0, 0.0);
+        self.size_store = None;
+        self.physics = None;
+        self.physics_enabled = false;
+        self.paused = false;
+        self.render_order = DEFAULT_RENDER_ORDER;
+        self.filter = None;
+        self.gradient = None;
+        self.name = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_time_loops_within_the_track() {
+        assert_eq!(wrap_time(250.0, 100.0), 50.0);
+        assert_eq!(wrap_time(-30.0, 100.0), 70.0);
+        assert_eq!(wrap_time(100.0, 100.0), 0.0);
+    }
+
+    #[test]
+    fn wrap_time_holds_at_zero_for_a_track_without_span() {
+        for time in [0.0, 16.7, -5.0, 1e9] {
+            assert_eq!(wrap_time(time, 0.0), 0.0);
+        }
     }
 }
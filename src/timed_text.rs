@@ -0,0 +1,26 @@
+use wasm_bindgen::JsValue;
+use web_sys::CanvasRenderingContext2d;
+
+/// Text drawn in screen space while the engine's global time lies within `[start_time, end_time]`.
+pub struct TimedText {
+    pub id: u32,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub font: String,
+    pub color: String,
+}
+
+impl TimedText {
+    pub fn is_active(&self, time: f64) -> bool {
+        time >= self.start_time && time <= self.end_time
+    }
+
+    pub fn render(&self, context: &CanvasRenderingContext2d) -> Result<(), JsValue> {
+        context.set_font(&self.font);
+        context.set_fill_style_str(&self.color);
+        context.fill_text(&self.text, self.x, self.y)
+    }
+}
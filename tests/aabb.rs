@@ -0,0 +1,246 @@
+use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+use rust_2d_engine::aabb::AABB;
+
+const EPSILON: f64 = 1e-9;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+}
+
+fn unit_box() -> AABB {
+    AABB::new(0.0, 0.0, 10.0, 10.0)
+}
+
+#[test]
+fn new_stores_each_edge() {
+    let b = AABB::new(1.0, 2.0, 3.0, 4.0);
+    assert_eq!((b.min_x(), b.min_y(), b.max_x(), b.max_y()), (1.0, 2.0, 3.0, 4.0));
+}
+
+#[test]
+fn width_and_height_span_the_edges() {
+    let b = AABB::new(-5.0, 2.0, 15.0, 8.0);
+    assert_eq!(b.width(), 20.0);
+    assert_eq!(b.height(), 6.0);
+}
+
+#[test]
+fn from_no_points_is_none() {
+    assert!(AABB::from_points(Vec::new()).is_none());
+}
+
+#[test]
+fn from_a_single_point_is_degenerate() {
+    let b = AABB::from_points([(3.0, -4.0)]).unwrap();
+    assert_eq!(b, AABB::new(3.0, -4.0, 3.0, -4.0));
+    assert_eq!(b.width(), 0.0);
+}
+
+#[test]
+fn from_points_bounds_every_point() {
+    let b = AABB::from_points([(1.0, 5.0), (-2.0, 3.0), (4.0, -1.0)]).unwrap();
+    assert_eq!(b, AABB::new(-2.0, -1.0, 4.0, 5.0));
+}
+
+#[test]
+fn contains_point_includes_edges_and_corners() {
+    let b = unit_box();
+    assert!(b.contains_point(5.0, 5.0));
+    assert!(b.contains_point(0.0, 5.0));
+    assert!(b.contains_point(10.0, 10.0));
+}
+
+#[test]
+fn contains_point_excludes_points_outside() {
+    let b = unit_box();
+    assert!(!b.contains_point(-0.1, 5.0));
+    assert!(!b.contains_point(5.0, 10.1));
+}
+
+#[test]
+fn overlapping_boxes_intersect() {
+    assert!(unit_box().intersects(&AABB::new(5.0, 5.0, 15.0, 15.0)));
+}
+
+#[test]
+fn boxes_sharing_an_edge_intersect() {
+    assert!(unit_box().intersects(&AABB::new(10.0, 0.0, 20.0, 10.0)));
+}
+
+#[test]
+fn nested_boxes_intersect_both_ways() {
+    let inner = AABB::new(2.0, 2.0, 3.0, 3.0);
+    assert!(unit_box().intersects(&inner));
+    assert!(inner.intersects(&unit_box()));
+}
+
+#[test]
+fn separated_boxes_do_not_intersect() {
+    assert!(!unit_box().intersects(&AABB::new(11.0, 0.0, 20.0, 10.0)));
+    assert!(!unit_box().intersects(&AABB::new(0.0, -5.0, 10.0, -1.0)));
+}
+
+#[test]
+fn ray_from_outside_hits_the_near_edge() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 5.0, 1.0, 0.0), Some(5.0));
+}
+
+#[test]
+fn ray_pointing_away_misses() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 5.0, -1.0, 0.0), None);
+}
+
+#[test]
+fn ray_starting_inside_hits_at_zero() {
+    assert_eq!(unit_box().intersect_ray(5.0, 5.0, 0.0, 1.0), Some(0.0));
+}
+
+#[test]
+fn horizontal_rays_hit_the_facing_edge() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 3.0, 1.0, 0.0), Some(5.0));
+    assert_eq!(unit_box().intersect_ray(18.0, 3.0, -1.0, 0.0), Some(8.0));
+}
+
+#[test]
+fn vertical_rays_hit_the_facing_edge() {
+    assert_eq!(unit_box().intersect_ray(4.0, -2.0, 0.0, 1.0), Some(2.0));
+    assert_eq!(unit_box().intersect_ray(4.0, 13.0, 0.0, -1.0), Some(3.0));
+    assert_eq!(unit_box().intersect_ray(4.0, 13.0, 0.0, 1.0), None);
+}
+
+#[test]
+fn diagonal_ray_hits_the_corner() {
+    let t = unit_box().intersect_ray(-3.0, -3.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2).unwrap();
+    assert_close(t, 3.0 * SQRT_2);
+}
+
+#[test]
+fn diagonal_ray_enters_through_the_later_slab() {
+    // Crosses x = 0 at t = 2 but only reaches y = 0 at t = 4.
+    assert_eq!(unit_box().intersect_ray(-2.0, -4.0, 1.0, 1.0), Some(4.0));
+}
+
+#[test]
+fn diagonal_ray_passing_a_corner_misses() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 8.0, 1.0, 1.0), None);
+    assert_eq!(unit_box().intersect_ray(12.0, -1.0, 1.0, 1.0), None);
+}
+
+#[test]
+fn ray_along_an_edge_hits() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 10.0, 1.0, 0.0), Some(5.0));
+}
+
+#[test]
+fn ray_t_scales_with_the_direction_length() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 3.0, 2.0, 0.0), Some(2.5));
+}
+
+#[test]
+fn ray_parallel_to_the_box_outside_it_misses() {
+    assert_eq!(unit_box().intersect_ray(-5.0, 20.0, 1.0, 0.0), None);
+}
+
+#[test]
+fn closest_point_of_an_outside_point_is_clamped_onto_the_box() {
+    assert_eq!(unit_box().closest_point_on_boundary(15.0, 5.0), (10.0, 5.0));
+    assert_eq!(unit_box().closest_point_on_boundary(-3.0, -4.0), (0.0, 0.0));
+}
+
+#[test]
+fn closest_point_of_an_inside_point_is_on_the_nearest_edge() {
+    assert_eq!(unit_box().closest_point_on_boundary(5.0, 9.0), (5.0, 10.0));
+}
+
+#[test]
+fn signed_distance_is_negative_inside_and_positive_outside() {
+    assert_close(unit_box().signed_distance(5.0, 8.0), -2.0);
+    assert_close(unit_box().signed_distance(13.0, 14.0), 5.0);
+    assert_close(unit_box().signed_distance(10.0, 5.0), 0.0);
+}
+
+type Point = (f64, f64);
+
+/// A point in each of the eight regions around `unit_box`, its projection and its distance.
+const OUTSIDE: [(Point, Point, f64); 8] = [
+    ((-3.0, 5.0), (0.0, 5.0), 3.0),
+    ((14.0, 5.0), (10.0, 5.0), 4.0),
+    ((5.0, -2.0), (5.0, 0.0), 2.0),
+    ((5.0, 11.5), (5.0, 10.0), 1.5),
+    ((-3.0, -4.0), (0.0, 0.0), 5.0),
+    ((13.0, -4.0), (10.0, 0.0), 5.0),
+    ((-6.0, 18.0), (0.0, 10.0), 10.0),
+    ((13.0, 14.0), (10.0, 10.0), 5.0),
+];
+
+#[test]
+fn closest_point_from_each_outside_region() {
+    for ((x, y), expected, _) in OUTSIDE {
+        assert_eq!(unit_box().closest_point_on_boundary(x, y), expected, "from ({}, {})", x, y);
+    }
+}
+
+#[test]
+fn signed_distance_from_each_outside_region() {
+    for ((x, y), _, expected) in OUTSIDE {
+        assert_close(unit_box().signed_distance(x, y), expected);
+    }
+}
+
+#[test]
+fn closest_point_from_inside_picks_each_edge() {
+    let b = unit_box();
+    assert_eq!(b.closest_point_on_boundary(1.0, 5.0), (0.0, 5.0));
+    assert_eq!(b.closest_point_on_boundary(8.5, 5.0), (10.0, 5.0));
+    assert_eq!(b.closest_point_on_boundary(5.0, 2.0), (5.0, 0.0));
+    assert_eq!(b.closest_point_on_boundary(4.0, 9.0), (4.0, 10.0));
+}
+
+#[test]
+fn closest_point_at_the_centre_breaks_the_tie_to_the_left_edge() {
+    assert_eq!(unit_box().closest_point_on_boundary(5.0, 5.0), (0.0, 5.0));
+    assert_close(unit_box().signed_distance(5.0, 5.0), -5.0);
+}
+
+#[test]
+fn points_on_the_boundary_are_their_own_closest_point() {
+    let b = unit_box();
+    for (x, y) in [(0.0, 3.0), (10.0, 7.0), (6.0, 0.0), (2.0, 10.0), (0.0, 0.0), (10.0, 10.0)] {
+        assert_eq!(b.closest_point_on_boundary(x, y), (x, y));
+        assert_close(b.signed_distance(x, y), 0.0);
+    }
+}
+
+#[test]
+fn lerp_hits_both_ends_and_the_midpoint() {
+    let a = unit_box();
+    let b = AABB::new(10.0, 20.0, 30.0, 40.0);
+    assert_eq!(a.lerp(&b, 0.0), a);
+    assert_eq!(a.lerp(&b, 1.0), b);
+    assert_eq!(a.lerp(&b, 0.5), AABB::new(5.0, 10.0, 20.0, 25.0));
+}
+
+#[test]
+fn scale_keeps_the_anchor_fixed() {
+    let scaled = unit_box().scale(2.0, 10.0, 10.0);
+    assert_eq!(scaled, AABB::new(-10.0, -10.0, 10.0, 10.0));
+    assert_eq!(unit_box().scale(1.0, 3.0, 7.0), unit_box());
+}
+
+#[test]
+fn to_world_and_from_world_are_inverses() {
+    let screen = AABB::new(12.0, 34.0, 56.0, 78.0);
+    let back = screen.to_world(-100.0, 250.0, 1.5).from_world(-100.0, 250.0, 1.5);
+    assert_close(back.min_x(), screen.min_x());
+    assert_close(back.min_y(), screen.min_y());
+    assert_close(back.max_x(), screen.max_x());
+    assert_close(back.max_y(), screen.max_y());
+}
+
+#[test]
+fn json_uses_snake_case_edge_names() {
+    let json = serde_json::to_value(AABB::new(1.0, 2.0, 3.0, 4.0)).unwrap();
+    assert_eq!(json, serde_json::json!({ "min_x": 1.0, "min_y": 2.0, "max_x": 3.0, "max_y": 4.0 }));
+    assert_eq!(serde_json::from_value::<AABB>(json).unwrap(), AABB::new(1.0, 2.0, 3.0, 4.0));
+}
@@ -0,0 +1,177 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::{cell::Cell, rc::Rc};
+
+use rust_2d_engine::input::{InputEvent, InputHandler};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_test::*;
+use web_sys::{Event, HtmlCanvasElement, KeyboardEvent, KeyboardEventInit, MouseEvent, MouseEventInit};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+type ErrorListener = Closure<dyn FnMut(Event)>;
+
+fn attached_canvas() -> HtmlCanvasElement {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let canvas: HtmlCanvasElement = document.create_element("canvas").unwrap().unchecked_into();
+    document.body().unwrap().append_child(&canvas).unwrap();
+    canvas
+}
+
+/// Count uncaught errors reported on the window until the returned closure is dropped.
+fn count_window_errors() -> (Rc<Cell<u32>>, ErrorListener) {
+    let errors = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&errors);
+    let listener = Closure::wrap(Box::new(move |_event: Event| {
+        counter.set(counter.get() + 1);
+    }) as Box<dyn FnMut(_)>);
+    web_sys::window()
+        .unwrap()
+        .add_event_listener_with_callback("error", listener.as_ref().unchecked_ref())
+        .unwrap();
+    (errors, listener)
+}
+
+#[wasm_bindgen_test]
+fn mouse_events_reach_a_live_handler() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+
+    canvas.dispatch_event(&MouseEvent::new("mousedown").unwrap()).unwrap();
+    assert!(handler.is_mouse_button_pressed(0));
+
+    canvas.dispatch_event(&MouseEvent::new("mouseup").unwrap()).unwrap();
+    assert!(!handler.is_mouse_button_pressed(0));
+}
+
+#[wasm_bindgen_test]
+fn dropping_handler_removes_its_listeners() {
+    let canvas = attached_canvas();
+    let (errors, error_listener) = count_window_errors();
+
+    let handler = InputHandler::new(&canvas).unwrap();
+    drop(handler);
+
+    // A listener left behind would call into a freed closure and throw.
+    for name in ["mousemove", "mousedown", "mouseup"] {
+        canvas.dispatch_event(&MouseEvent::new(name).unwrap()).unwrap();
+    }
+    for name in ["pointerdown", "pointerup", "lostpointercapture"] {
+        canvas.dispatch_event(&Event::new(name).unwrap()).unwrap();
+    }
+    let window = web_sys::window().unwrap();
+    for name in ["keydown", "keyup"] {
+        window.dispatch_event(&Event::new(name).unwrap()).unwrap();
+    }
+
+    assert_eq!(errors.get(), 0);
+    window
+        .remove_event_listener_with_callback("error", error_listener.as_ref().unchecked_ref())
+        .unwrap();
+}
+
+#[wasm_bindgen_test]
+fn handlers_can_be_created_and_dropped_repeatedly() {
+    let canvas = attached_canvas();
+    for _ in 0..10 {
+        drop(InputHandler::new(&canvas).unwrap());
+    }
+    let handler = InputHandler::new(&canvas).unwrap();
+    canvas.dispatch_event(&MouseEvent::new("mousedown").unwrap()).unwrap();
+    assert!(handler.is_mouse_button_pressed(0));
+}
+
+fn key_event(name: &str, key: &str) -> KeyboardEvent {
+    let init = KeyboardEventInit::new();
+    init.set_key(key);
+    KeyboardEvent::new_with_keyboard_event_init_dict(name, &init).unwrap()
+}
+
+fn mouse_event(name: &str, button: i16) -> MouseEvent {
+    let init = MouseEventInit::new();
+    init.set_button(button);
+    MouseEvent::new_with_mouse_event_init_dict(name, &init).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn each_mouse_button_is_tracked_separately() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+
+    canvas.dispatch_event(&mouse_event("mousedown", 2)).unwrap();
+    assert!(handler.is_mouse_button_pressed(2));
+    assert!(!handler.is_mouse_button_pressed(0));
+    assert!(!handler.is_mouse_button_pressed(7));
+
+    canvas.dispatch_event(&mouse_event("mouseup", 2)).unwrap();
+    assert!(!handler.is_mouse_button_pressed(2));
+}
+
+#[wasm_bindgen_test]
+fn mouse_move_reports_canvas_relative_position() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+    let rect = canvas.get_bounding_client_rect();
+
+    let init = MouseEventInit::new();
+    init.set_client_x(rect.left() as i32 + 15);
+    init.set_client_y(rect.top() as i32 + 25);
+    canvas
+        .dispatch_event(&MouseEvent::new_with_mouse_event_init_dict("mousemove", &init).unwrap())
+        .unwrap();
+
+    let position = handler.get_mouse_position();
+    assert_eq!(position.x, rect.left().trunc() + 15.0 - rect.left());
+    assert_eq!(position.y, rect.top().trunc() + 25.0 - rect.top());
+}
+
+#[wasm_bindgen_test]
+fn key_presses_are_reported_for_one_frame() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+    let window = web_sys::window().unwrap();
+
+    window.dispatch_event(&key_event("keydown", "a")).unwrap();
+    handler.tick(0.0);
+    assert!(handler.is_key_just_pressed("a"));
+    handler.tick(16.0);
+    assert!(!handler.is_key_just_pressed("a"));
+
+    window.dispatch_event(&key_event("keyup", "a")).unwrap();
+    handler.tick(32.0);
+    assert!(handler.is_key_just_released("a"));
+    handler.tick(48.0);
+    assert!(!handler.is_key_just_released("a"));
+}
+
+#[wasm_bindgen_test]
+fn recorded_events_are_replayed() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+
+    handler.start_recording();
+    canvas.dispatch_event(&mouse_event("mousedown", 0)).unwrap();
+    canvas.dispatch_event(&mouse_event("mouseup", 0)).unwrap();
+    let events = handler.stop_recording();
+    assert_eq!(events.len(), 2);
+
+    handler.start_replay(vec![(0.0, InputEvent::MouseDown(1))]);
+    // Live input is ignored while replaying.
+    canvas.dispatch_event(&mouse_event("mousedown", 0)).unwrap();
+    handler.tick(f64::MAX);
+    assert!(handler.is_mouse_button_pressed(1));
+    assert!(!handler.is_mouse_button_pressed(0));
+}
+
+#[wasm_bindgen_test]
+fn lost_pointer_capture_releases_every_button() {
+    let canvas = attached_canvas();
+    let handler = InputHandler::new(&canvas).unwrap();
+
+    canvas.dispatch_event(&mouse_event("mousedown", 0)).unwrap();
+    canvas.dispatch_event(&mouse_event("mousedown", 2)).unwrap();
+    canvas.dispatch_event(&Event::new("lostpointercapture").unwrap()).unwrap();
+
+    assert!(!handler.is_mouse_button_pressed(0));
+    assert!(!handler.is_mouse_button_pressed(2));
+}
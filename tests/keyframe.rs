@@ -0,0 +1,105 @@
+use rust_2d_engine::easing::Easing;
+use rust_2d_engine::keyframe::{self, DeltaEncodedChunk, Keyframe, KeyframeChunk};
+
+fn track() -> Vec<Keyframe> {
+    vec![
+        Keyframe::new(0.0, 0.0, 0.0),
+        Keyframe::new_with_easing(40.0, 12.5, -3.0, Easing::EaseIn),
+        Keyframe::new(90.0, 30.0, 7.75),
+    ]
+}
+
+fn json_round_trip(chunk: &KeyframeChunk) -> KeyframeChunk {
+    serde_json::from_str(&serde_json::to_string(chunk).unwrap()).unwrap()
+}
+
+#[test]
+fn keyframe_json_round_trip() {
+    let k = Keyframe::new(12.5, -3.25, 8.0);
+    let json = serde_json::to_string(&k).unwrap();
+    assert_eq!(serde_json::from_str::<Keyframe>(&json).unwrap(), k);
+}
+
+#[test]
+fn keyframe_easing_is_serialised_by_name() {
+    let k = Keyframe::new_with_easing(0.0, 0.0, 0.0, Easing::EaseOutBounce);
+    let json = serde_json::to_value(&k).unwrap();
+    assert_eq!(json["easing"], "EaseOutBounce");
+    assert_eq!(serde_json::from_value::<Keyframe>(json).unwrap().easing(), Easing::EaseOutBounce);
+}
+
+#[test]
+fn keyframe_without_easing_defaults_to_linear() {
+    let k: Keyframe = serde_json::from_str(r#"{"time":5,"x":1,"y":2}"#).unwrap();
+    assert_eq!(k, Keyframe::new(5.0, 1.0, 2.0));
+    assert_eq!(k.easing(), Easing::Linear);
+}
+
+#[test]
+fn keyframe_with_an_unknown_easing_is_rejected() {
+    assert!(serde_json::from_str::<Keyframe>(r#"{"time":5,"x":1,"y":2,"easing":"Wobble"}"#).is_err());
+}
+
+#[test]
+fn chunk_json_round_trip() {
+    let chunk = KeyframeChunk::new("obj_0", 0.0, 90.0, track());
+    let restored = json_round_trip(&chunk);
+    assert_eq!(restored.object_chunk_id(), "obj_0");
+    assert_eq!((restored.start_time(), restored.end_time()), (0.0, 90.0));
+    assert_eq!(restored.keyframes(), chunk.keyframes());
+}
+
+#[test]
+fn empty_chunk_json_round_trip() {
+    let restored = json_round_trip(&KeyframeChunk::placeholder("obj", 3, 100.0));
+    assert!(restored.is_empty());
+    assert_eq!(restored.time_range(), (300.0, 300.0));
+}
+
+#[test]
+fn chunk_from_before_checksums_defaults_them() {
+    let chunk: KeyframeChunk =
+        serde_json::from_str(r#"{"object_chunk_id":"obj_0","start_time":0,"end_time":10,"keyframes":[]}"#).unwrap();
+    assert!(!chunk.is_baked());
+    assert!(chunk.verify_checksum());
+}
+
+#[test]
+fn sealed_chunk_checksum_survives_json() {
+    let mut chunk = KeyframeChunk::new("obj_0", 0.0, 90.0, track());
+    chunk.seal();
+    assert!(json_round_trip(&chunk).verify_checksum());
+}
+
+#[test]
+fn tampered_chunk_fails_its_checksum() {
+    let mut chunk = KeyframeChunk::new("obj_0", 0.0, 90.0, track());
+    chunk.seal();
+    let mut json = serde_json::to_value(&chunk).unwrap();
+    json["keyframes"][1]["x"] = serde_json::json!(99.0);
+    let tampered: KeyframeChunk = serde_json::from_value(json).unwrap();
+    assert!(!tampered.verify_checksum());
+}
+
+#[test]
+fn delta_encoded_chunk_json_round_trip() {
+    let chunk = KeyframeChunk::new("obj_0", 0.0, 90.0, track());
+    let encoded = chunk.delta_encode().unwrap();
+    let restored: DeltaEncodedChunk = serde_json::from_str(&serde_json::to_string(&encoded).unwrap()).unwrap();
+    let decoded = restored.decode();
+    assert_eq!(decoded.keyframes().len(), 3);
+    assert_eq!(decoded.keyframes()[1].easing(), Easing::EaseIn);
+    assert_eq!(decoded.time_range(), (0.0, 90.0));
+}
+
+#[test]
+fn split_chunks_rebuild_the_track_after_json() {
+    let keyframes: Vec<Keyframe> = (0..25).map(|i| Keyframe::new(i as f32 * 20.0, i as f32, 0.0)).collect();
+    let chunks = keyframe::split_into_chunks("obj", keyframes.clone(), 100.0);
+    let restored: Vec<Keyframe> = chunks
+        .iter()
+        .map(json_round_trip)
+        .flat_map(|chunk| chunk.keyframes().to_vec())
+        .collect();
+    assert_eq!(restored, keyframes);
+}
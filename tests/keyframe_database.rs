@@ -0,0 +1,115 @@
+#![cfg(target_arch = "wasm32")]
+
+use rust_2d_engine::easing::Easing;
+use rust_2d_engine::keyframe::{self, Keyframe};
+use rust_2d_engine::keyframe_database::{CompressionLevel, KeyframeDatabase};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const CHUNK_SIZE: f32 = 100.0;
+const CHUNK_COUNT: u32 = 200;
+
+fn now() -> f64 {
+    web_sys::window().unwrap().performance().unwrap().now()
+}
+
+fn keyframes(count: u32) -> Vec<Keyframe> {
+    (0..count).map(|i| Keyframe::new(i as f32 * 10.0, i as f32, -(i as f32))).collect()
+}
+
+/// Times one `load_chunk` per chunk against a single `bulk_load_chunks` over the same ids,
+/// and checks both return the same keyframes.
+#[wasm_bindgen_test]
+async fn bulk_load_matches_and_benchmarks_single_loads() {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    let object_id = "bench_bulk_load";
+    db.save_chunks(keyframe::split_into_chunks(object_id, keyframes(CHUNK_COUNT * 10), CHUNK_SIZE))
+        .await
+        .unwrap();
+    let chunk_ids: Vec<u32> = (0..CHUNK_COUNT).collect();
+
+    let start = now();
+    let mut single = Vec::with_capacity(chunk_ids.len());
+    for &chunk_id in &chunk_ids {
+        single.push(db.load_chunk(object_id, chunk_id).await.unwrap());
+    }
+    let single_ms = now() - start;
+
+    let start = now();
+    let bulk = db.bulk_load_chunks(object_id, &chunk_ids).await.unwrap();
+    let bulk_ms = now() - start;
+
+    console_log!(
+        "{} chunks: single loads {:.1}ms, bulk load {:.1}ms",
+        CHUNK_COUNT,
+        single_ms,
+        bulk_ms
+    );
+    assert_eq!(bulk.len(), single.len());
+    for (bulk, single) in bulk.iter().zip(&single) {
+        assert_eq!(bulk.keyframes(), single.keyframes());
+    }
+}
+
+#[wasm_bindgen_test]
+async fn bulk_load_of_no_ids_is_empty() {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    assert!(db.bulk_load_chunks("bench_bulk_load", &[]).await.unwrap().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn bulk_load_fails_on_a_missing_chunk() {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    assert!(db.bulk_load_chunks("bench_missing", &[0]).await.is_err());
+}
+
+/// Save a bouncing track under `object_id` and check every keyframe's easing survives a
+/// load, with `compression` used for the write.
+async fn assert_easing_round_trips(object_id: &str, compression: CompressionLevel) {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    let saved = vec![
+        Keyframe::new_with_easing(0.0, 0.0, 0.0, Easing::EaseOutBounce),
+        Keyframe::new(50.0, 5.0, 5.0),
+        Keyframe::new_with_easing(80.0, 8.0, 8.0, Easing::EaseOutBounce),
+    ];
+    db.set_compression(compression);
+    let saved_chunks = keyframe::split_into_chunks(object_id, saved.clone(), CHUNK_SIZE);
+    let result = db.save_chunks(saved_chunks).await;
+    db.set_compression(CompressionLevel::None);
+    result.unwrap();
+
+    let loaded = db.load_chunk(object_id, 0).await.unwrap();
+    assert_eq!(loaded.keyframes(), saved.as_slice());
+    assert_eq!(loaded.keyframes()[0].easing(), Easing::EaseOutBounce);
+    assert_eq!(loaded.keyframes()[1].easing(), Easing::Linear);
+}
+
+#[wasm_bindgen_test]
+async fn ease_out_bounce_keyframe_is_stored_and_loaded() {
+    assert_easing_round_trips("easing_plain", CompressionLevel::None).await;
+}
+
+#[wasm_bindgen_test]
+async fn ease_out_bounce_keyframe_survives_delta_compression() {
+    assert_easing_round_trips("easing_delta", CompressionLevel::Delta).await;
+}
+
+#[wasm_bindgen_test]
+async fn migrating_chunk_size_rekeys_chunks_by_time() {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    let object_id = "migrate_gaps";
+    let saved = vec![Keyframe::new(0.0, 0.0, 0.0), Keyframe::new(40.0, 4.0, 4.0), Keyframe::new(500.0, 50.0, 50.0)];
+    db.save_chunks(keyframe::split_into_chunks(object_id, saved.clone(), CHUNK_SIZE)).await.unwrap();
+
+    db.migrate_chunk_size(object_id, CHUNK_SIZE.into(), 30.0).await.unwrap();
+
+    // 500 / 30 puts the last keyframe in chunk 16; every id before it must exist.
+    let chunk_ids: Vec<u32> = (0..=16).collect();
+    let chunks = db.bulk_load_chunks(object_id, &chunk_ids).await.unwrap();
+    let loaded: Vec<Keyframe> = chunks.iter().flat_map(|chunk| chunk.keyframes().to_vec()).collect();
+    assert_eq!(loaded, saved);
+    assert_eq!(chunks[1].keyframes(), &saved[1..2]);
+    assert_eq!(chunks[16].keyframes(), &saved[2..]);
+    assert!(db.load_chunk(object_id, 17).await.is_err());
+}
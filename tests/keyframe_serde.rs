@@ -0,0 +1,218 @@
+#![cfg(target_arch = "wasm32")]
+
+//! `KeyframeChunk` through `serde_wasm_bindgen`, the encoding `KeyframeDatabase` uses for
+//! IndexedDB records. Needs no DOM, so runs under node as well as in a browser.
+
+use js_sys::{Array, Object, Reflect};
+use rust_2d_engine::easing::Easing;
+use rust_2d_engine::keyframe::{DeltaEncodedChunk, Keyframe, KeyframeChunk, ValidationError};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+fn round_trip(chunk: &KeyframeChunk) -> KeyframeChunk {
+    let value = serde_wasm_bindgen::to_value(chunk).unwrap();
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+fn assert_same_chunk(a: &KeyframeChunk, b: &KeyframeChunk) {
+    assert_eq!(a.object_chunk_id(), b.object_chunk_id());
+    assert_eq!(a.start_time(), b.start_time());
+    assert_eq!(a.end_time(), b.end_time());
+    assert_eq!(a.keyframes(), b.keyframes());
+}
+
+fn parse(json: &str) -> JsValue {
+    js_sys::JSON::parse(json).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn chunk_with_known_keyframes_round_trips() {
+    let chunk = KeyframeChunk::new(
+        "obj_0",
+        0.0,
+        90.0,
+        vec![
+            Keyframe::new(0.0, 1.5, -2.25),
+            Keyframe::new_with_easing(45.0, 100.0, 200.0, Easing::EaseInOut),
+            Keyframe::new_with_easing(90.0, -0.125, 3.0, Easing::EaseOutBounce),
+        ],
+    );
+    assert_same_chunk(&round_trip(&chunk), &chunk);
+}
+
+#[wasm_bindgen_test]
+fn sealed_chunk_keeps_a_valid_checksum() {
+    let mut chunk = KeyframeChunk::new("obj_0", 0.0, 10.0, vec![Keyframe::new(0.0, 1.0, 2.0), Keyframe::new(10.0, 3.0, 4.0)]);
+    chunk.seal();
+    assert!(round_trip(&chunk).verify_checksum());
+}
+
+#[wasm_bindgen_test]
+fn empty_chunk_round_trips() {
+    let chunk = KeyframeChunk::new("obj_3", 300.0, 300.0, Vec::new());
+    let restored = round_trip(&chunk);
+    assert_same_chunk(&restored, &chunk);
+    assert!(restored.is_empty());
+    assert!(restored.validate().is_ok());
+}
+
+#[wasm_bindgen_test]
+fn single_keyframe_chunk_round_trips() {
+    let chunk = KeyframeChunk::new("obj_0", 5.0, 5.0, vec![Keyframe::new(5.0, 7.0, 8.0)]);
+    let restored = round_trip(&chunk);
+    assert_same_chunk(&restored, &chunk);
+    assert_eq!(restored.interpolate(5.0).x, 7.0);
+}
+
+#[wasm_bindgen_test]
+fn largest_f32_time_round_trips() {
+    let chunk = KeyframeChunk::new("obj_0", 0.0, f32::MAX, vec![Keyframe::new(0.0, 0.0, 0.0), Keyframe::new(f32::MAX, 1.0, 1.0)]);
+    let restored = round_trip(&chunk);
+    assert_same_chunk(&restored, &chunk);
+    assert!(restored.validate().is_ok());
+}
+
+#[wasm_bindgen_test]
+fn f64_max_time_decodes_as_infinite_and_fails_validation() {
+    let record = parse(&format!(
+        r#"{{"object_chunk_id":"obj_0","start_time":0,"end_time":{max},"keyframes":[{{"time":{max},"x":1,"y":1}}]}}"#,
+        max = f64::MAX
+    ));
+    let chunk: KeyframeChunk = serde_wasm_bindgen::from_value(record).unwrap();
+    assert_eq!(chunk.keyframes()[0].time(), f32::INFINITY);
+    assert!(chunk.validate().is_err());
+}
+
+#[wasm_bindgen_test]
+fn nan_position_round_trips_and_fails_validation() {
+    let chunk = KeyframeChunk::new("obj_0", 0.0, 10.0, vec![Keyframe::new(0.0, 0.0, 0.0), Keyframe::new(10.0, f32::NAN, f32::NAN)]);
+    let restored = round_trip(&chunk);
+    assert!(restored.keyframes()[1].x().is_nan());
+    assert!(restored.keyframes()[1].y().is_nan());
+    assert_eq!(restored.validate(), Err(ValidationError::NonFiniteValue { index: 1 }));
+}
+
+#[wasm_bindgen_test]
+fn record_with_a_non_numeric_position_is_an_error() {
+    let record = parse(r#"{"object_chunk_id":"obj_0","start_time":0,"end_time":0,"keyframes":[{"time":0,"x":"left","y":0}]}"#);
+    assert!(serde_wasm_bindgen::from_value::<KeyframeChunk>(record).is_err());
+}
+
+#[wasm_bindgen_test]
+fn v1_record_without_easing_decodes_as_linear() {
+    let record = parse(
+        r#"{"object_chunk_id":"obj_0","start_time":0,"end_time":10,"keyframes":[{"time":0,"x":0,"y":0},{"time":10,"x":10,"y":20}]}"#,
+    );
+    let chunk: KeyframeChunk = serde_wasm_bindgen::from_value(record).unwrap();
+
+    assert!(chunk.keyframes().iter().all(|k| k.easing() == Easing::Linear));
+    // Without a checksum the record predates sealing and is not verified.
+    assert!(chunk.verify_checksum());
+    assert_eq!(chunk.interpolate(5.0).y, 10.0);
+}
+
+/// Linear congruential generator, so a failing fuzz case can be reproduced.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u32 {
+        self.0 = self.0.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) as u32
+    }
+
+    fn below(&mut self, n: u32) -> u32 {
+        self.next() % n
+    }
+}
+
+/// Awkward numbers for chunk fields: edge values, non-finite values and out-of-range ones.
+const NUMBERS: [f64; 14] = [
+    0.0, -0.0, 1.0, -1.0, 0.5, 99.0, 1e3, -1e9, f64::MAX, f64::MIN_POSITIVE, f64::NAN, f64::INFINITY,
+    f64::NEG_INFINITY, 65_536.0,
+];
+
+fn random_value(rng: &mut Lcg, depth: u32) -> JsValue {
+    match rng.below(if depth == 0 { 5 } else { 7 }) {
+        0 => JsValue::from_f64(NUMBERS[rng.below(NUMBERS.len() as u32) as usize]),
+        1 => JsValue::from_f64(f64::from(rng.next()) - f64::from(u32::MAX / 2)),
+        2 => JsValue::from_str(["", "obj_0", "obj_x", "_1", "a_b_7", "EaseIn", "Wobble"][rng.below(7) as usize]),
+        3 => JsValue::from_bool(rng.below(2) == 0),
+        4 => [JsValue::NULL, JsValue::UNDEFINED][rng.below(2) as usize].clone(),
+        5 => (0..rng.below(4)).map(|_| random_value(rng, depth - 1)).collect::<Array>().into(),
+        _ => random_keyframe(rng, depth - 1),
+    }
+}
+
+/// Mostly keyframe-shaped object; each field is randomly dropped, kept plausible or replaced.
+fn random_keyframe(rng: &mut Lcg, depth: u32) -> JsValue {
+    let object = Object::new();
+    for field in ["time", "x", "y", "easing"] {
+        let value = match rng.below(4) {
+            0 => continue,
+            1 if field == "easing" => JsValue::from_str("EaseOutBounce"),
+            1 => JsValue::from_f64(f64::from(rng.below(200))),
+            _ => random_value(rng, depth),
+        };
+        Reflect::set(&object, &field.into(), &value).unwrap();
+    }
+    object.into()
+}
+
+fn random_field(rng: &mut Lcg, plausible: JsValue) -> Option<JsValue> {
+    match rng.below(5) {
+        0 => None,
+        1 | 2 => Some(plausible),
+        _ => Some(random_value(rng, 2)),
+    }
+}
+
+/// Object shaped like a stored chunk, plain or delta encoded, with random damage.
+fn random_chunk(rng: &mut Lcg) -> JsValue {
+    let object = Object::new();
+    let keyframes: Array = (0..rng.below(6)).map(|_| random_keyframe(rng, 2)).collect();
+    let deltas: Array = (0..rng.below(6))
+        .map(|_| (0..3).map(|_| JsValue::from_f64(f64::from(rng.below(70_000)) - 35_000.0)).collect::<Array>())
+        .collect();
+    let fields = [
+        ("object_chunk_id", JsValue::from_str("obj_0")),
+        ("start_time", JsValue::from_f64(f64::from(rng.below(100)))),
+        ("end_time", JsValue::from_f64(f64::from(rng.below(300)))),
+        ("keyframes", keyframes.into()),
+        ("first", random_keyframe(rng, 1)),
+        ("deltas", deltas.into()),
+        ("baked", JsValue::from_bool(rng.below(2) == 0)),
+        ("checksum", JsValue::from_f64(f64::from(rng.next()))),
+    ];
+    for (name, plausible) in fields {
+        if let Some(value) = random_field(rng, plausible) {
+            Reflect::set(&object, &name.into(), &value).unwrap();
+        }
+    }
+    object.into()
+}
+
+#[wasm_bindgen_test]
+fn random_records_never_panic_on_decode_or_validate() {
+    let mut rng = Lcg(0x0dd_ba11);
+    let (mut decoded, mut valid) = (0, 0);
+    for _ in 0..5_000 {
+        let value = if rng.below(10) == 0 { random_value(&mut rng, 3) } else { random_chunk(&mut rng) };
+
+        if let Ok(chunk) = serde_wasm_bindgen::from_value::<KeyframeChunk>(value.clone()) {
+            decoded += 1;
+            if chunk.validate().is_ok() {
+                valid += 1;
+                chunk.verify_checksum();
+                chunk.interpolate(50.0);
+            }
+        }
+        if let Ok(encoded) = serde_wasm_bindgen::from_value::<DeltaEncodedChunk>(value) {
+            let chunk = encoded.decode();
+            if chunk.validate().is_ok() {
+                chunk.interpolate(50.0);
+            }
+        }
+    }
+    // Make sure the generator reaches both the decoder's and the validator's success paths.
+    assert!(decoded > 0 && valid > 0, "decoded {}, valid {}", decoded, valid);
+}
@@ -0,0 +1,183 @@
+#![cfg(target_arch = "wasm32")]
+
+use std::sync::Arc;
+
+use idb::{Factory, TransactionMode};
+use rust_2d_engine::keyframe::{self, Keyframe, KeyframeChunk};
+use rust_2d_engine::keyframe_database::KeyframeDatabase;
+use rust_2d_engine::keyframe_store::KeyframeStore;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const CHUNK_SIZE: f32 = 100.0;
+
+/// Save `keyframes` under `object_id` and return a store streaming them. Tests share one
+/// database, so each uses its own object id.
+async fn store_with(object_id: &str, keyframes: Vec<Keyframe>) -> KeyframeStore {
+    let db = KeyframeDatabase::open_existing().await.unwrap();
+    let chunks = keyframe::split_into_chunks(object_id, keyframes, CHUNK_SIZE);
+    let total_duration = chunks.iter().map(|chunk| chunk.end_time()).fold(0.0, f32::max);
+    db.save_chunks(chunks).await.unwrap();
+    KeyframeStore::new(object_id.to_string(), CHUNK_SIZE, total_duration.into(), Arc::clone(&db))
+}
+
+fn times(keyframes: &[Keyframe]) -> Vec<f32> {
+    keyframes.iter().map(Keyframe::time).collect()
+}
+
+#[wasm_bindgen_test]
+async fn add_keyframe_inside_the_track_inserts_in_order() {
+    let mut store = store_with("inside", vec![Keyframe::new(0.0, 0.0, 0.0), Keyframe::new(80.0, 8.0, 8.0)]).await;
+    store.add_keyframe(40.0, 4.0, 4.0).await.unwrap();
+
+    let keyframes = store.load_all_keyframes().await.unwrap();
+    assert_eq!(times(&keyframes), vec![0.0, 40.0, 80.0]);
+    assert_eq!(store.total_duration(), 80.0);
+}
+
+#[wasm_bindgen_test]
+async fn add_keyframe_far_past_the_end_fills_the_gap() {
+    let mut store = store_with("far", vec![Keyframe::new(0.0, 0.0, 0.0), Keyframe::new(50.0, 5.0, 5.0)]).await;
+    store.add_keyframe(530.0, 53.0, 53.0).await.unwrap();
+
+    assert_eq!(store.total_duration(), 530.0);
+    assert_eq!(store.chunk_count(), 6);
+    let keyframes = store.load_all_keyframes().await.unwrap();
+    assert_eq!(times(&keyframes), vec![0.0, 50.0, 530.0]);
+
+    // Inside the gap the object holds the last keyframe before it.
+    let held = store.position_at(250.0).await.unwrap();
+    assert_eq!((held.x, held.y), (5.0, 5.0));
+    let end = store.position_at(530.0).await.unwrap();
+    assert_eq!((end.x, end.y), (53.0, 53.0));
+}
+
+#[wasm_bindgen_test]
+async fn add_keyframe_into_a_placeholder_keeps_the_track_loadable() {
+    let mut store = store_with("refill", vec![Keyframe::new(0.0, 0.0, 0.0)]).await;
+    store.add_keyframe(420.0, 42.0, 0.0).await.unwrap();
+    store.add_keyframe(250.0, 25.0, 0.0).await.unwrap();
+
+    let keyframes = store.load_all_keyframes().await.unwrap();
+    assert_eq!(times(&keyframes), vec![0.0, 250.0, 420.0]);
+    assert_eq!(store.total_duration(), 420.0);
+}
+
+#[wasm_bindgen_test]
+async fn cached_placeholder_gives_no_position() {
+    let mut store = store_with("cached", vec![Keyframe::new(0.0, 0.0, 0.0)]).await;
+    store.add_keyframe(300.0, 30.0, 30.0).await.unwrap();
+
+    store.fetch_chunk(1).await.unwrap();
+    assert!(store.get_interpolated_position(150.0).is_none());
+    assert_eq!(store.cache_metrics().hits, 1);
+    assert_eq!(store.cache_metrics().misses, 0);
+}
+
+/// A store over keyframes every 50 ms from 0 to 450, i.e. chunks 0 to 4.
+async fn five_chunk_store(object_id: &str) -> KeyframeStore {
+    let keyframes = (0..10).map(|i| Keyframe::new(i as f32 * 50.0, i as f32, 0.0)).collect();
+    store_with(object_id, keyframes).await
+}
+
+#[wasm_bindgen_test]
+async fn lru_cache_evicts_the_oldest_chunk() {
+    let store = five_chunk_store("evict_lru").await;
+    for chunk_idx in 0..3 {
+        store.fetch_chunk(chunk_idx).await.unwrap();
+    }
+
+    assert_eq!(store.cache_evictions(), 1);
+    assert!(store.get_interpolated_position(20.0).is_none());
+    assert!(store.get_interpolated_position(120.0).is_some());
+    assert!(store.get_interpolated_position(220.0).is_some());
+}
+
+#[wasm_bindgen_test]
+async fn fetching_a_cached_chunk_does_not_reload_it() {
+    let store = five_chunk_store("evict_refetch").await;
+    store.fetch_chunk(0).await.unwrap();
+    store.fetch_chunk(0).await.unwrap();
+
+    assert_eq!(store.cache_metrics().loads, 1);
+    assert_eq!(store.cache_evictions(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn distant_chunks_are_evicted() {
+    let store = five_chunk_store("evict_distant").await;
+    store.fetch_chunk(0).await.unwrap();
+    store.fetch_chunk(1).await.unwrap();
+    store.evict_distant_chunks(10.0, 60.0);
+
+    assert_eq!(store.cache_evictions(), 1);
+    assert!(store.get_interpolated_position(10.0).is_some());
+    assert!(store.get_interpolated_position(120.0).is_none());
+}
+
+#[wasm_bindgen_test]
+async fn eviction_distance_wraps_around_the_end_of_the_track() {
+    let store = five_chunk_store("evict_wrap").await;
+    store.fetch_chunk(4).await.unwrap();
+    // The last chunk ends at 450, the end of the track, so it is 10 ms behind time 10.
+    store.evict_distant_chunks(10.0, 60.0);
+
+    assert_eq!(store.cache_evictions(), 0);
+    assert!(store.get_interpolated_position(420.0).is_some());
+}
+
+#[wasm_bindgen_test]
+async fn growing_the_cache_keeps_more_chunks() {
+    let store = five_chunk_store("evict_grow").await;
+    store.grow_cache_for_instance();
+    for chunk_idx in 0..4 {
+        store.fetch_chunk(chunk_idx).await.unwrap();
+    }
+
+    assert_eq!(store.cache_evictions(), 0);
+    assert!(store.get_interpolated_position(20.0).is_some());
+}
+
+#[wasm_bindgen_test]
+async fn invalidate_empties_the_cache() {
+    let store = five_chunk_store("evict_invalidate").await;
+    store.fetch_chunk(0).await.unwrap();
+    store.invalidate();
+
+    assert!(store.get_interpolated_position(20.0).is_none());
+    assert_eq!(store.cache_metrics().misses, 1);
+}
+
+/// Overwrite chunk `chunk_idx` of `object_id` with a record whose checksum no longer
+/// matches its keyframes, bypassing `KeyframeDatabase`.
+async fn corrupt_chunk(object_id: &str, chunk_idx: u32) {
+    let db = Factory::new().unwrap().open("keyframe_db", None).unwrap().await.unwrap();
+    let mut chunk = KeyframeChunk::new(
+        &format!("{}_{}", object_id, chunk_idx),
+        0.0,
+        0.0,
+        vec![Keyframe::new(0.0, 1.0, 1.0)],
+    );
+    chunk.seal();
+    let mut record = serde_json::to_value(&chunk).unwrap();
+    record["keyframes"][0]["x"] = serde_json::json!(999.0);
+    let record = js_sys::JSON::parse(&record.to_string()).unwrap();
+
+    let tx = db.transaction(&["keyframe_chunks"], TransactionMode::ReadWrite).unwrap();
+    tx.object_store("keyframe_chunks").unwrap().put(&record, None).unwrap().await.unwrap();
+    tx.commit().unwrap().await.unwrap();
+    db.close();
+}
+
+#[wasm_bindgen_test]
+async fn load_all_keyframes_skips_corrupted_chunks() {
+    let store = five_chunk_store("corrupt_load_all").await;
+    corrupt_chunk("corrupt_load_all", 2).await;
+    store.fetch_chunk(2).await.unwrap();
+    assert_eq!(store.corrupted_chunk_ids(), vec![2]);
+    assert_eq!(store.intact_chunk_ids(), vec![0, 1, 3, 4]);
+
+    let keyframes = store.load_all_keyframes().await.unwrap();
+    assert_eq!(times(&keyframes), vec![0.0, 50.0, 100.0, 150.0, 300.0, 350.0, 400.0, 450.0]);
+}
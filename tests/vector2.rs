@@ -0,0 +1,101 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use rust_2d_engine::math::Vector2;
+
+const EPSILON: f64 = 1e-9;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < EPSILON, "expected {}, got {}", expected, actual);
+}
+
+#[test]
+fn zero_and_one_constants() {
+    assert_eq!(Vector2::zero(), Vector2::new(0.0, 0.0));
+    assert_eq!(Vector2::one(), Vector2::new(1.0, 1.0));
+}
+
+#[test]
+fn from_angle_points_along_the_angle() {
+    let v = Vector2::from_angle(FRAC_PI_2, 3.0);
+    assert_close(v.x, 0.0);
+    assert_close(v.y, 3.0);
+}
+
+#[test]
+fn dot_of_perpendicular_vectors_is_zero() {
+    assert_eq!(Vector2::new(2.0, 0.0).dot(&Vector2::new(0.0, 5.0)), 0.0);
+}
+
+#[test]
+fn dot_multiplies_and_sums_components() {
+    assert_eq!(Vector2::new(1.0, 2.0).dot(&Vector2::new(3.0, -4.0)), -5.0);
+}
+
+#[test]
+fn angle_to_is_signed() {
+    let east = Vector2::new(1.0, 0.0);
+    assert_close(east.angle_to(&Vector2::new(0.0, 1.0)), FRAC_PI_2);
+    assert_close(east.angle_to(&Vector2::new(0.0, -1.0)), -FRAC_PI_2);
+    assert_close(east.angle_to(&Vector2::new(-1.0, 0.0)), PI);
+}
+
+#[test]
+fn distance_of_a_3_4_5_triangle() {
+    let a = Vector2::new(1.0, 1.0);
+    let b = Vector2::new(4.0, 5.0);
+    assert_eq!(a.distance_squared(&b), 25.0);
+    assert_eq!(a.distance(&b), 5.0);
+    assert_eq!(b.distance(&a), 5.0);
+}
+
+#[test]
+fn perp_is_a_quarter_turn_counter_clockwise() {
+    let v = Vector2::new(3.0, 1.0);
+    assert_eq!(v.perp(), Vector2::new(-1.0, 3.0));
+    assert_eq!(v.dot(&v.perp()), 0.0);
+}
+
+#[test]
+fn project_onto_an_axis_keeps_that_component() {
+    let v = Vector2::new(3.0, 4.0);
+    assert_eq!(v.project(&Vector2::new(10.0, 0.0)), Vector2::new(3.0, 0.0));
+    assert_eq!(v.project(&Vector2::new(0.0, -2.0)), Vector2::new(0.0, 4.0));
+}
+
+#[test]
+fn project_onto_zero_is_zero() {
+    assert_eq!(Vector2::new(3.0, 4.0).project(&Vector2::zero()), Vector2::zero());
+}
+
+#[test]
+fn min_and_max_are_component_wise() {
+    let a = Vector2::new(1.0, -5.0);
+    let b = Vector2::new(-2.0, 3.0);
+    assert_eq!(a.min(&b), Vector2::new(-2.0, -5.0));
+    assert_eq!(a.max(&b), Vector2::new(1.0, 3.0));
+}
+
+#[test]
+fn abs_flips_negative_components() {
+    assert_eq!(Vector2::new(-1.5, 2.5).abs(), Vector2::new(1.5, 2.5));
+}
+
+#[test]
+fn floor_and_ceil_round_negatives_away_and_towards_zero() {
+    let v = Vector2::new(-1.5, 2.5);
+    assert_eq!(v.floor(), Vector2::new(-2.0, 2.0));
+    assert_eq!(v.ceil(), Vector2::new(-1.0, 3.0));
+}
+
+#[test]
+fn round_takes_halves_away_from_zero() {
+    assert_eq!(Vector2::new(-2.5, 2.5).round(), Vector2::new(-3.0, 3.0));
+    assert_eq!(Vector2::new(0.4, -0.4).round(), Vector2::new(0.0, -0.0));
+}
+
+#[test]
+fn json_round_trip() {
+    let v = Vector2::new(0.1, -7.25);
+    let json = serde_json::to_string(&v).unwrap();
+    assert_eq!(serde_json::from_str::<Vector2>(&json).unwrap(), v);
+}